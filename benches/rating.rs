@@ -0,0 +1,39 @@
+//! Benchmarks the rating aggregation, which the what-if calculators call many times over.
+//! Run with `cargo bench --bench rating`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+/// Reproducible, somewhat realistically distributed SSRs
+fn ssrs(count: usize) -> Vec<f32> {
+	let mut seed: u32 = 42;
+	(0..count)
+		.map(|_| {
+			seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+			10.0 + (seed >> 8) as f32 / (1 << 24) as f32 * 25.0
+		})
+		.collect()
+}
+
+fn bench_skillset_rating(c: &mut Criterion) {
+	let mut group = c.benchmark_group("calculate_player_skillset_rating");
+	for count in [10, 100, 1_000, 10_000] {
+		let ssrs = ssrs(count);
+		group.bench_with_input(BenchmarkId::from_parameter(count), &ssrs, |b, ssrs| {
+			b.iter(|| etternaonline_api::rating::calculate_player_skillset_rating(black_box(ssrs)))
+		});
+	}
+	group.finish();
+}
+
+fn bench_overall(c: &mut Criterion) {
+	let skillsets = [24.3, 27.8, 25.1, 22.9, 26.4, 23.0, 21.7];
+	c.bench_function("calculate_player_overall", |b| {
+		b.iter(|| etternaonline_api::rating::calculate_player_overall(black_box(&skillsets)))
+	});
+	c.bench_function("calculate_chart_overall", |b| {
+		b.iter(|| etternaonline_api::rating::calculate_chart_overall(black_box(&skillsets)))
+	});
+}
+
+criterion_group!(benches, bench_skillset_rating, bench_overall);
+criterion_main!(benches);
@@ -0,0 +1,236 @@
+//! Analysis of replays across multiple plays, e.g. to find out whether a player's hands differ in
+//! accuracy
+
+use crate::Replay;
+
+/// Which hand plays a lane
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+pub enum Hand {
+	Left,
+	Right,
+}
+
+/// Assigns each lane of a keymode to a hand
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandMapping {
+	hands: Vec<Option<Hand>>,
+}
+
+impl HandMapping {
+	/// Creates a mapping from the hand of each lane, starting with the leftmost lane. Lanes
+	/// mapped to `None`, e.g. a thumb lane, are left out of the analysis
+	pub fn new(hands: Vec<Option<Hand>>) -> Self {
+		Self { hands }
+	}
+
+	/// The usual mapping: the left half of the lanes is played by the left hand, the right half by
+	/// the right hand. With an odd number of lanes, the middle lane is left out
+	pub fn split_in_half(keymode: usize) -> Self {
+		Self::new(
+			(0..keymode)
+				.map(|lane| {
+					if keymode % 2 == 1 && lane == keymode / 2 {
+						None
+					} else if lane < keymode / 2 {
+						Some(Hand::Left)
+					} else {
+						Some(Hand::Right)
+					}
+				})
+				.collect(),
+		)
+	}
+
+	/// The hand that plays the given lane, if any
+	pub fn hand(&self, lane: u8) -> Option<Hand> {
+		self.hands.get(lane as usize).copied().flatten()
+	}
+}
+
+/// Accuracy and timing of one hand. See [`hand_bias`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct HandStats {
+	pub num_notes: u32,
+	pub num_misses: u32,
+	/// Wife3 accuracy over all notes of this hand. None if the hand had no notes
+	pub accuracy: Option<etterna::Wifescore>,
+	/// Mean hit offset. None if the hand hit no notes
+	pub mean_deviation: Option<crate::Offset>,
+	/// Standard deviation of the hit offsets, i.e. how consistent the timing is. None if the hand
+	/// hit no notes
+	pub deviation_spread: Option<crate::Offset>,
+}
+
+/// Comparison of the left and right hand. See [`hand_bias`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct HandBias {
+	pub left: HandStats,
+	pub right: HandStats,
+	/// Number of replays that were analyzed. Replays without lane information are skipped
+	pub num_replays: u32,
+}
+
+impl HandBias {
+	/// How many percentage points the left hand is more accurate than the right hand. Negative if
+	/// the right hand is more accurate. None if either hand had no notes
+	pub fn accuracy_difference(&self) -> Option<f32> {
+		Some(self.left.accuracy?.as_percent() - self.right.accuracy?.as_percent())
+	}
+
+	/// The hand that is less accurate, if the difference is bigger than `threshold_percent`
+	pub fn weaker_hand(&self, threshold_percent: f32) -> Option<Hand> {
+		let difference = self.accuracy_difference()?;
+		if difference < -threshold_percent {
+			Some(Hand::Left)
+		} else if difference > threshold_percent {
+			Some(Hand::Right)
+		} else {
+			None
+		}
+	}
+}
+
+#[derive(Default)]
+struct HandAccumulator {
+	num_notes: u32,
+	num_misses: u32,
+	wife_points: f32,
+	deviations: Vec<f32>,
+}
+
+impl HandAccumulator {
+	fn finish(self) -> HandStats {
+		let num_hits = self.deviations.len() as f32;
+		let mean_deviation = if self.deviations.is_empty() {
+			None
+		} else {
+			Some(self.deviations.iter().sum::<f32>() / num_hits)
+		};
+		let deviation_spread = mean_deviation.map(|mean| {
+			let variance =
+				self.deviations
+					.iter()
+					.map(|deviation| (deviation - mean).powi(2))
+					.sum::<f32>() / num_hits;
+			variance.sqrt()
+		});
+
+		HandStats {
+			num_notes: self.num_notes,
+			num_misses: self.num_misses,
+			accuracy: if self.num_notes == 0 {
+				None
+			} else {
+				etterna::Wifescore::from_proportion(self.wife_points / self.num_notes as f32)
+			},
+			mean_deviation: mean_deviation.map(crate::Offset::from_seconds),
+			deviation_spread: deviation_spread.map(crate::Offset::from_seconds),
+		}
+	}
+}
+
+/// Compares the accuracy and timing of the left and right hand over a batch of replays. The replays
+/// should all be of the keymode that `mapping` was made for.
+///
+/// Only taps and hold heads are counted. Replays without lane information are skipped; if a
+/// replay has no note type information, all its notes are counted.
+///
+/// # Example
+/// ```rust,no_run
+/// # let replays: Vec<etternaonline_api::Replay> = vec![];
+/// use etternaonline_api::analysis::*;
+///
+/// let bias = hand_bias(&replays, &HandMapping::split_in_half(4), etterna::J4);
+/// if let Some(hand) = bias.weaker_hand(0.5) {
+/// 	println!("Your {:?} hand is less accurate", hand);
+/// }
+/// ```
+pub fn hand_bias<'a>(
+	replays: impl IntoIterator<Item = &'a Replay>,
+	mapping: &HandMapping,
+	judge: &etterna::Judge,
+) -> HandBias {
+	let mut left = HandAccumulator::default();
+	let mut right = HandAccumulator::default();
+	let mut num_replays = 0;
+
+	for replay in replays {
+		if replay.notes.first().is_none_or(|note| note.lane.is_none()) {
+			continue;
+		}
+		num_replays += 1;
+
+		for note in &replay.notes {
+			if note.is_tap() == Some(false) {
+				continue;
+			}
+			let hand = match note.lane.and_then(|lane| mapping.hand(lane)) {
+				Some(Hand::Left) => &mut left,
+				Some(Hand::Right) => &mut right,
+				None => continue,
+			};
+
+			hand.num_notes += 1;
+			hand.wife_points += etterna::wife3(note.hit, judge);
+			match note.hit {
+				etterna::Hit::Hit { deviation } => hand.deviations.push(deviation),
+				etterna::Hit::Miss => hand.num_misses += 1,
+			}
+		}
+	}
+
+	HandBias {
+		left: left.finish(),
+		right: right.finish(),
+		num_replays,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_hand_bias() {
+		let note = |lane, hit| crate::ReplayNote {
+			time: 0.0,
+			hit,
+			lane: Some(lane),
+			note_type: Some(etterna::NoteType::Tap),
+			tick: None,
+		};
+		let replay = Replay {
+			notes: vec![
+				note(0, etterna::Hit::Hit { deviation: -0.01 }),
+				note(1, etterna::Hit::Hit { deviation: -0.03 }),
+				note(2, etterna::Hit::Miss),
+				note(3, etterna::Hit::Hit { deviation: 0.0 }),
+			],
+			raw: None,
+		};
+
+		let bias = hand_bias(&[replay], &HandMapping::split_in_half(4), etterna::J4);
+		assert_eq!(bias.num_replays, 1);
+		assert_eq!((bias.left.num_notes, bias.left.num_misses), (2, 0));
+		assert_eq!((bias.right.num_notes, bias.right.num_misses), (2, 1));
+		assert!((bias.left.mean_deviation.unwrap().as_millis() + 20.0).abs() < 1e-3);
+		assert_eq!(bias.weaker_hand(1.0), Some(Hand::Right));
+	}
+}
@@ -0,0 +1,317 @@
+//! Conversion of score, leaderboard and user collections into Arrow record batches, for loading
+//! API results into dataframe libraries like Polars or pandas without extracting columns by hand.
+//!
+//! Every supported collection implements [`ToRecordBatch`]. Each struct field becomes one column;
+//! skillset values are spread into one column per skillset, e.g. `ssr_overall`, `ssr_stream`...
+//! Wifescores are stored as percentages.
+
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, BooleanArray, Float32Array, RecordBatch, StringArray, UInt32Array};
+use arrow_schema::{Field, Schema};
+use etterna::Skillset8;
+
+/// Converts a collection of API structs into an Arrow [`RecordBatch`]
+///
+/// # Example
+/// ```rust,no_run
+/// # async fn run() -> Result<(), etternaonline_api::Error> {
+/// use etternaonline_api::arrow::ToRecordBatch;
+/// # let session: etternaonline_api::v2::Session = unimplemented!();
+///
+/// let leaderboard = session.chart_leaderboard("X4a15f62b66a80b62ec64521704f98c6c03d98e03").await?;
+/// let batch = leaderboard.to_record_batch();
+/// println!("{} rows, {} columns", batch.num_rows(), batch.num_columns());
+/// # Ok(()) }
+/// ```
+pub trait ToRecordBatch {
+	fn to_record_batch(&self) -> RecordBatch;
+}
+
+/// Collects the columns of a record batch one by one
+struct Columns<'a, T> {
+	rows: &'a [T],
+	fields: Vec<Field>,
+	arrays: Vec<ArrayRef>,
+}
+
+impl<'a, T> Columns<'a, T> {
+	fn new(rows: &'a [T]) -> Self {
+		Self {
+			rows,
+			fields: Vec::new(),
+			arrays: Vec::new(),
+		}
+	}
+
+	fn push(mut self, name: &str, nullable: bool, array: ArrayRef) -> Self {
+		self.fields
+			.push(Field::new(name, array.data_type().clone(), nullable));
+		self.arrays.push(array);
+		self
+	}
+
+	fn string<S: AsRef<str>>(self, name: &str, f: impl Fn(&'a T) -> S) -> Self {
+		let array = StringArray::from_iter_values(self.rows.iter().map(f));
+		self.push(name, false, Arc::new(array))
+	}
+
+	fn optional_string<S: AsRef<str>>(self, name: &str, f: impl Fn(&'a T) -> Option<S>) -> Self {
+		let array: StringArray = self.rows.iter().map(f).collect();
+		self.push(name, true, Arc::new(array))
+	}
+
+	fn f32(self, name: &str, f: impl Fn(&T) -> f32) -> Self {
+		let array = Float32Array::from_iter_values(self.rows.iter().map(f));
+		self.push(name, false, Arc::new(array))
+	}
+
+	fn optional_f32(self, name: &str, f: impl Fn(&T) -> Option<f32>) -> Self {
+		let array: Float32Array = self.rows.iter().map(f).collect();
+		self.push(name, true, Arc::new(array))
+	}
+
+	fn u32(self, name: &str, f: impl Fn(&T) -> u32) -> Self {
+		let array = UInt32Array::from_iter_values(self.rows.iter().map(f));
+		self.push(name, false, Arc::new(array))
+	}
+
+	fn bool(self, name: &str, f: impl Fn(&T) -> bool) -> Self {
+		let array: BooleanArray = self.rows.iter().map(|row| Some(f(row))).collect();
+		self.push(name, false, Arc::new(array))
+	}
+
+	/// Adds one column per skillset, named `{prefix}_{skillset}`
+	fn skillsets(self, prefix: &str, f: impl Fn(&T) -> &etterna::Skillsets8) -> Self {
+		Skillset8::iter().fold(self, |columns, skillset| {
+			columns.f32(&skillset_column(prefix, skillset), |row| {
+				f(row).get(skillset)
+			})
+		})
+	}
+
+	/// Like [`Self::skillsets`], for rows that may not have skillset values
+	fn optional_skillsets(
+		self,
+		prefix: &str,
+		f: impl Fn(&T) -> Option<&etterna::Skillsets8>,
+	) -> Self {
+		Skillset8::iter().fold(self, |columns, skillset| {
+			columns.optional_f32(&skillset_column(prefix, skillset), |row| {
+				f(row).map(|skillsets| skillsets.get(skillset))
+			})
+		})
+	}
+
+	fn finish(self) -> RecordBatch {
+		// UNWRAP: every column has one value per row and its field was derived from the array
+		RecordBatch::try_new(Arc::new(Schema::new(self.fields)), self.arrays).unwrap()
+	}
+}
+
+fn skillset_column(prefix: &str, skillset: Skillset8) -> String {
+	format!("{}_{}", prefix, format!("{:?}", skillset).to_lowercase())
+}
+
+mod v2_impls {
+	use super::*;
+	use crate::v2::*;
+
+	impl ToRecordBatch for [TopScore] {
+		fn to_record_batch(&self) -> RecordBatch {
+			Columns::new(self)
+				.string("scorekey", |s| &s.scorekey)
+				.string("song_name", |s| &s.song_name)
+				.f32("ssr_overall", |s| s.ssr_overall)
+				.f32("wifescore", |s| s.wifescore.as_percent())
+				.f32("rate", |s| s.rate.as_f32())
+				.string("difficulty", |s| s.difficulty.to_short_string())
+				.string("chartkey", |s| &s.chartkey)
+				.skillsets("base_msd", |s| &s.base_msd)
+				.finish()
+		}
+	}
+
+	impl ToRecordBatch for [LatestScore] {
+		fn to_record_batch(&self) -> RecordBatch {
+			Columns::new(self)
+				.string("scorekey", |s| &s.scorekey)
+				.string("song_name", |s| &s.song_name)
+				.f32("ssr_overall", |s| s.ssr_overall)
+				.f32("wifescore", |s| s.wifescore.as_percent())
+				.f32("rate", |s| s.rate.as_f32())
+				.string("difficulty", |s| s.difficulty.to_short_string())
+				.finish()
+		}
+	}
+
+	impl ToRecordBatch for [TopScorePerSkillset] {
+		fn to_record_batch(&self) -> RecordBatch {
+			Columns::new(self)
+				.string("scorekey", |s| &s.scorekey)
+				.string("song_name", |s| &s.song_name)
+				.f32("rate", |s| s.rate.as_f32())
+				.f32("wifescore", |s| s.wifescore.as_percent())
+				.string("chartkey", |s| &s.chartkey)
+				.string("difficulty", |s| s.difficulty.to_short_string())
+				.skillsets("ssr", |s| &s.ssr)
+				.finish()
+		}
+	}
+
+	impl ToRecordBatch for [ChartLeaderboardScore] {
+		fn to_record_batch(&self) -> RecordBatch {
+			Columns::new(self)
+				.string("scorekey", |s| &s.scorekey)
+				.string("username", |s| &s.user.username)
+				.string("country_code", |s| &s.user.country_code)
+				.f32("wifescore", |s| s.wifescore.as_percent())
+				.f32("rate", |s| s.rate.as_f32())
+				.u32("max_combo", |s| s.max_combo)
+				.bool("is_valid", |s| s.is_valid)
+				.bool("has_chord_cohesion", |s| s.has_chord_cohesion)
+				.string("datetime", |s| &s.datetime)
+				.string("modifiers", |s| &s.modifiers)
+				.bool("has_replay", |s| s.has_replay)
+				.u32("marvelouses", |s| s.judgements.marvelouses)
+				.u32("perfects", |s| s.judgements.perfects)
+				.u32("greats", |s| s.judgements.greats)
+				.u32("goods", |s| s.judgements.goods)
+				.u32("bads", |s| s.judgements.bads)
+				.u32("misses", |s| s.judgements.misses)
+				.skillsets("ssr", |s| &s.ssr)
+				.finish()
+		}
+	}
+
+	impl ToRecordBatch for [LeaderboardEntry] {
+		fn to_record_batch(&self) -> RecordBatch {
+			Columns::new(self)
+				.string("username", |e| &e.user.username)
+				.string("country_code", |e| &e.user.country_code)
+				.string("avatar", |e| &e.user.avatar)
+				.skillsets("rating", |e| &e.rating)
+				.finish()
+		}
+	}
+
+	impl ToRecordBatch for [UserDetails] {
+		fn to_record_batch(&self) -> RecordBatch {
+			Columns::new(self)
+				.string("username", |u| &u.username)
+				.string("country_code", |u| &u.country_code)
+				.bool("is_moderator", |u| u.is_moderator)
+				.bool("is_patreon", |u| u.is_patreon)
+				.skillsets("rating", |u| &u.rating)
+				.finish()
+		}
+	}
+}
+
+mod web_impls {
+	use super::*;
+	use crate::web::*;
+
+	impl ToRecordBatch for [UserScore] {
+		fn to_record_batch(&self) -> RecordBatch {
+			Columns::new(self)
+				.optional_string("scorekey", |s| {
+					s.validity_dependant.as_ref().map(|info| &info.scorekey)
+				})
+				.string("song_name", |s| &s.song_name)
+				.u32("song_id", |s| s.song_id.0)
+				.f32("rate", |s| s.rate.as_f32())
+				.f32("wifescore", |s| s.wifescore.as_percent())
+				.string("date", |s| &s.date)
+				.bool("has_chord_cohesion", |s| s.has_chord_cohesion)
+				.u32("marvelouses", |s| s.judgements.marvelouses)
+				.u32("perfects", |s| s.judgements.perfects)
+				.u32("greats", |s| s.judgements.greats)
+				.u32("goods", |s| s.judgements.goods)
+				.u32("bads", |s| s.judgements.bads)
+				.u32("misses", |s| s.judgements.misses)
+				.optional_f32("ssr_overall_nerfed", |s| {
+					s.validity_dependant
+						.as_ref()
+						.map(|info| info.ssr_overall_nerfed)
+				})
+				.optional_skillsets("ssr", |s| {
+					s.validity_dependant.as_ref().map(|info| &info.ssr)
+				})
+				.finish()
+		}
+	}
+
+	impl ToRecordBatch for [ChartLeaderboardEntry] {
+		fn to_record_batch(&self) -> RecordBatch {
+			Columns::new(self)
+				.string("scorekey", |e| &e.scorekey)
+				.string("username", |e| &e.username)
+				.u32("user_id", |e| e.user_id)
+				.f32("ssr_overall", |e| e.ssr_overall)
+				.f32("ssr_overall_nerfed", |e| e.ssr_overall_nerfed)
+				.f32("rate", |e| e.rate.as_f32())
+				.f32("wifescore", |e| e.wifescore.as_percent())
+				.string("date", |e| &e.date)
+				.u32("max_combo", |e| e.max_combo)
+				.u32("marvelouses", |e| e.judgements.marvelouses)
+				.u32("perfects", |e| e.judgements.perfects)
+				.u32("greats", |e| e.judgements.greats)
+				.u32("goods", |e| e.judgements.goods)
+				.u32("bads", |e| e.judgements.bads)
+				.u32("misses", |e| e.judgements.misses)
+				.finish()
+		}
+	}
+
+	impl ToRecordBatch for [LeaderboardEntry] {
+		fn to_record_batch(&self) -> RecordBatch {
+			Columns::new(self)
+				.u32("rank", |e| e.rank)
+				.string("username", |e| &e.username)
+				.optional_string("country_code", |e| e.country.as_ref().map(|c| &c.code))
+				.string("avatar", |e| &e.avatar)
+				.skillsets("rating", |e| &e.rating)
+				.finish()
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_user_scores_batch() {
+		let valid = crate::web::UserScore {
+			song_name: "Game Time".to_owned(),
+			song_id: crate::SongId(2858),
+			validity_dependant: Some(crate::web::ValidUserScoreInfo {
+				user_id: 1,
+				ssr: etterna::Skillsets8 {
+					overall: 30.0,
+					..Default::default()
+				},
+				ssr_overall_nerfed: 29.0,
+				scorekey: "S65565b5bc377c6d78b60c0aecfd9e05955b4cf63".parse().unwrap(),
+			}),
+			rate: etterna::Rate::from_f32(1.0).unwrap(),
+			wifescore: etterna::Wifescore::from_percent(93.0).unwrap(),
+			judgements: Default::default(),
+			date: "2020-07-25 12:00:00".to_owned(),
+			has_chord_cohesion: false,
+		};
+		let invalid = crate::web::UserScore {
+			validity_dependant: None,
+			..valid.clone()
+		};
+
+		let batch = [valid, invalid].to_record_batch();
+		assert_eq!(batch.num_rows(), 2);
+		assert_eq!(batch.num_columns(), 14 + 8);
+
+		let ssr_overall = batch.column_by_name("ssr_overall").unwrap();
+		assert_eq!(ssr_overall.data_type(), &arrow_schema::DataType::Float32);
+		assert_eq!(ssr_overall.null_count(), 1);
+	}
+}
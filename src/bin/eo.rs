@@ -0,0 +1,106 @@
+//! Command line tool for quick EtternaOnline queries. Build with `--features cli`.
+//!
+//! Uses the v1 API, so the API key must be provided in the `EO_API_KEY` environment variable.
+
+use etternaonline_api::v1;
+
+const USAGE: &str = "\
+Usage: eo [--json] <command>
+
+Commands:
+    user <username>            Show a user's profile
+    score <scorekey>           Show a score
+    leaderboard [country]      Show the world or a country's leaderboard
+    packlist [--search <text>] List packs, optionally only those whose name contains <text>
+
+Options:
+    --json    Print JSON instead of the human-readable output
+
+The API key is read from the EO_API_KEY environment variable";
+
+fn print<T: std::fmt::Debug + serde_::Serialize>(value: &T, json: bool) {
+	if json {
+		// UNWRAP: the crate's structs always serialize successfully
+		println!("{}", serde_json::to_string_pretty(value).unwrap());
+	} else {
+		println!("{:#?}", value);
+	}
+}
+
+async fn run(session: &v1::Session, args: &[String], json: bool) -> Result<(), String> {
+	let args = args.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+	match args.as_slice() {
+		["user", username] => print(
+			&session
+				.user_data(username)
+				.await
+				.map_err(|e| e.to_string())?,
+			json,
+		),
+		["score", scorekey] => print(
+			&session
+				.score_data(scorekey)
+				.await
+				.map_err(|e| e.to_string())?,
+			json,
+		),
+		["leaderboard"] => print(
+			&session
+				.global_leaderboard()
+				.await
+				.map_err(|e| e.to_string())?,
+			json,
+		),
+		["leaderboard", country_code] => print(
+			&session
+				.country_leaderboard(country_code)
+				.await
+				.map_err(|e| e.to_string())?,
+			json,
+		),
+		["packlist", rest @ ..] => {
+			let search = match rest {
+				[] => None,
+				["--search", search] => Some(search.to_lowercase()),
+				_ => return Err(USAGE.to_owned()),
+			};
+			let mut packs = session.pack_list().await.map_err(|e| e.to_string())?;
+			if let Some(search) = search {
+				packs.retain(|pack| pack.name.to_lowercase().contains(&search));
+			}
+			print(&packs, json);
+		}
+		_ => return Err(USAGE.to_owned()),
+	}
+	Ok(())
+}
+
+fn main() {
+	let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+	let json = match args.iter().position(|arg| arg == "--json") {
+		Some(i) => {
+			args.remove(i);
+			true
+		}
+		None => false,
+	};
+
+	let api_key = match std::env::var("EO_API_KEY") {
+		Ok(api_key) => api_key,
+		Err(_) => {
+			eprintln!("{}", USAGE);
+			std::process::exit(1);
+		}
+	};
+	let session = v1::Session::new(api_key, std::time::Duration::from_millis(500), None);
+
+	// UNWRAP: only fails if the OS refuses to set up the runtime's resources
+	let runtime = tokio::runtime::Builder::new_current_thread()
+		.enable_all()
+		.build()
+		.unwrap();
+	if let Err(e) = runtime.block_on(run(&session, &args, json)) {
+		eprintln!("{}", e);
+		std::process::exit(1);
+	}
+}
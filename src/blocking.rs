@@ -0,0 +1,35 @@
+//! Shared parts of the blocking sessions in [`crate::v1::blocking`] and [`crate::web::blocking`]
+
+/// Each blocking session drives its requests on its own single-threaded runtime
+pub(crate) fn runtime() -> tokio::runtime::Runtime {
+	// UNWRAP: only fails if the OS refuses to set up the runtime's resources
+	tokio::runtime::Builder::new_current_thread()
+		.enable_all()
+		.build()
+		.unwrap()
+}
+
+/// Generates blocking versions of the given async session methods. Expects `Session` to be a
+/// struct with an `inner` async session and a `runtime`
+macro_rules! blocking_methods {
+	($(
+		$(#[$attr:meta])*
+		fn $method:ident(&self $(, $arg:ident: $arg_ty:ty)* $(,)?) -> $ret:ty;
+	)*) => {
+		impl Session {
+			$(
+				#[doc = concat!(
+					"Blocking version of [`Session::",
+					stringify!($method),
+					"`](super::Session::",
+					stringify!($method),
+					")",
+				)]
+				$(#[$attr])*
+				pub fn $method(&self $(, $arg: $arg_ty)*) -> $ret {
+					self.runtime.block_on(self.inner.$method($($arg),*))
+				}
+			)*
+		}
+	};
+}
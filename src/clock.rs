@@ -0,0 +1,222 @@
+//! Time sources used for rate limiting. By default, sessions use [`RealClock`]; tests can swap in
+//! [`InstantClock`] via e.g. [`v2::Session::set_clock`](crate::v2::Session::set_clock) to run
+//! without actually waiting
+
+type BoxFuture<T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send + Sync>>;
+
+/// A source of time that the rate limiter uses to find out the current time and to wait
+pub trait Clock: Send + Sync {
+	fn now(&self) -> std::time::Instant;
+
+	/// Returns a future that completes once [`Self::now`] has reached `deadline`
+	fn sleep_until(&self, deadline: std::time::Instant) -> BoxFuture<()>;
+}
+
+/// The system clock. Sleeping is done using tokio's timer if the `tokio` feature is enabled, and
+/// with a timer thread that is shared by all sleeps otherwise.
+///
+/// Without `tokio`, the clock works with any executor. Note that this doesn't extend to the v1, v2
+/// and web sessions: they send their requests through reqwest, which needs a tokio runtime
+/// regardless
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RealClock;
+
+impl Clock for RealClock {
+	fn now(&self) -> std::time::Instant {
+		std::time::Instant::now()
+	}
+
+	#[cfg(feature = "tokio")]
+	fn sleep_until(&self, deadline: std::time::Instant) -> BoxFuture<()> {
+		Box::pin(tokio::time::sleep_until(deadline.into()))
+	}
+
+	#[cfg(not(feature = "tokio"))]
+	fn sleep_until(&self, deadline: std::time::Instant) -> BoxFuture<()> {
+		Box::pin(TimerSleep { deadline })
+	}
+}
+
+/// Executor-independent sleep future, woken by the [`TimerThread`]
+#[cfg(not(feature = "tokio"))]
+struct TimerSleep {
+	deadline: std::time::Instant,
+}
+
+#[cfg(not(feature = "tokio"))]
+impl std::future::Future for TimerSleep {
+	type Output = ();
+
+	fn poll(
+		self: std::pin::Pin<&mut Self>,
+		cx: &mut std::task::Context<'_>,
+	) -> std::task::Poll<()> {
+		if std::time::Instant::now() >= self.deadline {
+			return std::task::Poll::Ready(());
+		}
+		TIMER_THREAD.wake_at(self.deadline, cx.waker().clone());
+		std::task::Poll::Pending
+	}
+}
+
+/// Wakes sleeping tasks once their deadline has passed. A single thread serves all sleeps in the
+/// process; it's started on the first sleep
+#[cfg(not(feature = "tokio"))]
+struct TimerThread {
+	sleepers: std::sync::Mutex<Vec<(std::time::Instant, std::task::Waker)>>,
+	/// Notified when a sleeper is added, which may have an earlier deadline than the others
+	new_sleeper: std::sync::Condvar,
+	started: std::sync::Once,
+}
+
+#[cfg(not(feature = "tokio"))]
+static TIMER_THREAD: TimerThread = TimerThread {
+	sleepers: std::sync::Mutex::new(Vec::new()),
+	new_sleeper: std::sync::Condvar::new(),
+	started: std::sync::Once::new(),
+};
+
+#[cfg(not(feature = "tokio"))]
+impl TimerThread {
+	fn wake_at(&'static self, deadline: std::time::Instant, waker: std::task::Waker) {
+		self.started.call_once(|| {
+			std::thread::Builder::new()
+				.name("etternaonline_api timer".to_owned())
+				.spawn(move || self.run())
+				// UNWRAP: sleeping can't work without the thread
+				.unwrap();
+		});
+
+		// UNWRAP: propagate panics
+		self.sleepers.lock().unwrap().push((deadline, waker));
+		self.new_sleeper.notify_one();
+	}
+
+	fn run(&self) {
+		loop {
+			// UNWRAP: propagate panics
+			let mut sleepers = self.sleepers.lock().unwrap();
+			let now = std::time::Instant::now();
+			let (due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut *sleepers)
+				.into_iter()
+				.partition(|&(deadline, _)| deadline <= now);
+			*sleepers = pending;
+
+			if due.is_empty() {
+				let next_deadline = sleepers.iter().map(|&(deadline, _)| deadline).min();
+				// UNWRAP: propagate panics
+				drop(match next_deadline {
+					Some(next_deadline) => {
+						self.new_sleeper
+							.wait_timeout(sleepers, next_deadline - now)
+							.unwrap()
+							.0
+					}
+					None => self.new_sleeper.wait(sleepers).unwrap(),
+				});
+			} else {
+				// Don't hold the lock while waking, in case a waker registers a new sleep
+				drop(sleepers);
+				for (_, waker) in due {
+					waker.wake();
+				}
+			}
+		}
+	}
+}
+
+/// A clock that never actually waits. Instead, sleeping fast-forwards the clock to the deadline
+/// immediately. Useful for testing code that makes many rate-limited requests
+#[derive(Debug)]
+pub struct InstantClock {
+	start: std::time::Instant,
+	elapsed: std::sync::Mutex<std::time::Duration>,
+}
+
+impl InstantClock {
+	pub fn new() -> Self {
+		Self {
+			start: std::time::Instant::now(),
+			elapsed: std::sync::Mutex::new(std::time::Duration::from_secs(0)),
+		}
+	}
+
+	/// Manually move the clock forward
+	pub fn advance(&self, duration: std::time::Duration) {
+		// UNWRAP: propagate panics
+		*self.elapsed.lock().unwrap() += duration;
+	}
+}
+
+impl Default for InstantClock {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Clock for InstantClock {
+	fn now(&self) -> std::time::Instant {
+		// UNWRAP: propagate panics
+		self.start + *self.elapsed.lock().unwrap()
+	}
+
+	fn sleep_until(&self, deadline: std::time::Instant) -> BoxFuture<()> {
+		// UNWRAP: propagate panics
+		let mut elapsed = self.elapsed.lock().unwrap();
+		if deadline > self.start + *elapsed {
+			*elapsed = deadline - self.start;
+		}
+		Box::pin(std::future::ready(()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_rate_limit_with_instant_clock() {
+		let clock = InstantClock::new();
+		let cooldown = std::time::Duration::from_secs(2);
+		let start = clock.now();
+		let rate_limiter = crate::rate_limit::RateLimiter::new(cooldown, &clock);
+
+		for _ in 0..3 {
+			// InstantClock advances when the sleep is created, no need to poll the future
+			drop(rate_limiter.wait(&clock));
+		}
+		assert_eq!(clock.now() - start, cooldown * 2);
+	}
+
+	#[cfg(not(feature = "tokio"))]
+	#[test]
+	fn test_timer_thread() {
+		struct ThreadWaker(std::thread::Thread);
+		impl std::task::Wake for ThreadWaker {
+			fn wake(self: std::sync::Arc<Self>) {
+				self.0.unpark();
+			}
+		}
+		let waker = std::sync::Arc::new(ThreadWaker(std::thread::current())).into();
+		let mut cx = std::task::Context::from_waker(&waker);
+
+		let start = std::time::Instant::now();
+		let delays = [30, 10, 20].map(std::time::Duration::from_millis);
+		let mut sleeps = delays.map(|delay| RealClock.sleep_until(start + delay));
+		// Sleeps that finish early must not be held up by the ones that were registered first
+		let mut finish_order = Vec::new();
+		loop {
+			for (i, sleep) in sleeps.iter_mut().enumerate() {
+				if !finish_order.contains(&i) && sleep.as_mut().poll(&mut cx).is_ready() {
+					finish_order.push(i);
+				}
+			}
+			if finish_order.len() == sleeps.len() {
+				break;
+			}
+			std::thread::park();
+		}
+		assert_eq!(finish_order, [1, 2, 0]);
+		assert!(start.elapsed() >= delays[0]);
+	}
+}
@@ -1,4 +1,6 @@
 pub mod structs;
+#[cfg(feature = "wire")]
+pub(crate) mod wire;
 use structs::*;
 
 use etterna::*;
@@ -22,6 +24,28 @@ pub(crate) fn note_type_from_eo(note_type: &serde_json::Value) -> Result<etterna
 	}
 }
 
+/// Checks a scorekey passed in by the user before it ends up in a request URL
+pub(crate) fn validate_scorekey(scorekey: &str) -> Result<(), Error> {
+	if etterna::Scorekey::is_valid(scorekey) {
+		Ok(())
+	} else {
+		Err(Error::InvalidScorekey {
+			scorekey: scorekey.to_owned(),
+		})
+	}
+}
+
+/// Checks a chartkey passed in by the user before it ends up in a request URL
+pub(crate) fn validate_chartkey(chartkey: &str) -> Result<(), Error> {
+	if etterna::Chartkey::is_valid(chartkey) {
+		Ok(())
+	} else {
+		Err(Error::InvalidChartkey {
+			chartkey: chartkey.to_owned(),
+		})
+	}
+}
+
 pub(crate) fn skillset_to_eo(skillset: etterna::Skillset7) -> &'static str {
 	match skillset {
 		Skillset7::Stream => "Stream",
@@ -34,18 +58,29 @@ pub(crate) fn skillset_to_eo(skillset: etterna::Skillset7) -> &'static str {
 	}
 }
 
-fn parse_replay_inner(json: &serde_json::Value) -> Result<Option<Replay>, Error> {
-	if json.is_null() {
-		return Ok(None);
+/// Extracts the replay JSON string from the replay field, which is either the string itself or an
+/// array containing the string
+fn replay_str(json: &serde_json::Value) -> Option<&str> {
+	match json {
+		serde_json::Value::Array(values) => values.first()?.as_str(),
+		serde_json::Value::String(string) => Some(string),
+		_ => None,
 	}
+}
 
-	let replay_str = match json {
-		serde_json::Value::Array(values) => match values[0].as_str() {
-			Some(x) => x,
-			None => return Ok(None),
-		},
-		serde_json::Value::String(string) => string,
-		_ => return Ok(None),
+fn parse_raw_replay(json: &serde_json::Value) -> Result<RawReplay, Error> {
+	let rows = json
+		.array()?
+		.iter()
+		.map(|row| row.array()?.iter().map(|x| x.f64_()).collect())
+		.collect::<Result<Vec<Vec<f64>>, Error>>()?;
+	Ok(RawReplay { rows })
+}
+
+fn parse_replay_inner(json: &serde_json::Value, keep_raw: bool) -> Result<Option<Replay>, Error> {
+	let replay_str = match replay_str(json) {
+		Some(x) => x,
+		None => return Ok(None),
 	};
 
 	let json: serde_json::Value = serde_json::from_str(replay_str)?;
@@ -104,16 +139,200 @@ fn parse_replay_inner(json: &serde_json::Value) -> Result<Option<Replay>, Error>
 		return Ok(None);
 	}
 
-	Ok(Some(Replay { notes }))
+	let raw = if keep_raw {
+		Some(parse_raw_replay(&json)?)
+	} else {
+		None
+	};
+
+	Ok(Some(Replay { notes, raw }))
+}
+
+/// Parses a replay in EO's format, for replays obtained elsewhere than through this crate's
+/// sessions, e.g. from archives.
+///
+/// `json` is the replay field of an EO response: either the replay string itself or an array
+/// containing it. Returns None if there is no replay. If the replay is present but can't be
+/// parsed, a warning is logged and the raw replay string is returned as the second tuple element,
+/// so that it can be saved and reported. See [`parse_replay_strict`] for a version that returns
+/// the error instead.
+///
+/// # Example
+/// ```rust
+/// let json = serde_json::json!("[[0.5, -12.0, 0, 1, 96], [1.0, 180.0, 1, 1, 192]]");
+/// let (replay, _) = etternaonline_api::parse_replay(&json);
+/// assert_eq!(replay.unwrap().notes.len(), 2);
+/// ```
+pub fn parse_replay(json: &serde_json::Value) -> (Option<Replay>, Option<String>) {
+	parse_replay_with_settings(json, &crate::ParseSettings::default())
 }
 
-pub(crate) fn parse_replay(json: &serde_json::Value) -> Option<Replay> {
-	match parse_replay_inner(json) {
-		Ok(Some(x)) => Some(x),
-		Ok(None) => None,
+/// Like [`parse_replay`], but returns an error if the replay is present but can't be parsed
+pub fn parse_replay_strict(json: &serde_json::Value) -> Result<Option<Replay>, Error> {
+	parse_replay_inner(json, false)
+}
+
+/// Parses the replay. If the replay is present but can't be parsed, the raw replay string is
+/// returned as the second tuple element, so that users can save and report it
+pub(crate) fn parse_replay_with_settings(
+	json: &serde_json::Value,
+	parse_settings: &crate::ParseSettings,
+) -> (Option<Replay>, Option<String>) {
+	match parse_replay_inner(json, parse_settings.keep_raw_replays) {
+		Ok(replay) => (replay, None),
 		Err(e) => {
-			log::warn!("failed to parse replay: {}", e);
-			None
+			parse_settings.warn("replay", e);
+			(None, replay_str(json).map(|s| s.to_owned()))
 		}
 	}
 }
+
+/// Parses a JSON object response and passes each element of the array at `array_key` to
+/// `on_element`. Returns the other fields of the object.
+///
+/// With the `streaming` feature, the array is never held in memory as a whole: each element is
+/// deserialized, handed out and dropped before the next one is read. Without it, the whole
+/// response is parsed into a [`serde_json::Value`] first.
+pub(crate) fn stream_array_field(
+	json: &str,
+	array_key: &str,
+	mut on_element: impl FnMut(Json<'_>),
+) -> Result<serde_json::Map<String, serde_json::Value>, Error> {
+	#[cfg(feature = "streaming")]
+	let (rest, found_array) = {
+		use serde::de::Deserializer as _;
+
+		let mut visitor = stream::ObjectVisitor {
+			array_key,
+			on_element: &mut on_element,
+			found_array: false,
+		};
+		let mut deserializer = serde_json::Deserializer::from_str(json);
+		let rest = (&mut deserializer).deserialize_map(&mut visitor)?;
+		deserializer.end()?;
+		(rest, visitor.found_array)
+	};
+
+	#[cfg(not(feature = "streaming"))]
+	let (rest, found_array) = {
+		let mut rest = match serde_json::from_str(json)? {
+			serde_json::Value::Object(object) => object,
+			other => {
+				return Err(Error::InvalidDataStructure(format!(
+					"Expected an object at top level, found {}",
+					other
+				)))
+			}
+		};
+		let array = rest.remove(array_key);
+		let found_array = match &array {
+			Some(array @ serde_json::Value::Array(_)) => {
+				Json::new(array, array_key)
+					.elements()?
+					.for_each(&mut on_element);
+				true
+			}
+			_ => false,
+		};
+		(rest, found_array)
+	};
+
+	if !found_array {
+		return Err(Error::InvalidDataStructure(format!(
+			"Expected an array at {}",
+			array_key
+		)));
+	}
+	Ok(rest)
+}
+
+#[cfg(feature = "streaming")]
+mod stream {
+	use serde::de::{DeserializeSeed, MapAccess, SeqAccess, Visitor};
+
+	use crate::extension_traits::Json;
+
+	pub(super) struct ObjectVisitor<'a, F> {
+		pub array_key: &'a str,
+		pub on_element: F,
+		pub found_array: bool,
+	}
+
+	impl<'de, F: FnMut(Json<'_>)> Visitor<'de> for &mut ObjectVisitor<'_, F> {
+		type Value = serde_json::Map<String, serde_json::Value>;
+
+		fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+			write!(f, "a JSON object")
+		}
+
+		fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+			let mut rest = serde_json::Map::new();
+			while let Some(key) = map.next_key::<String>()? {
+				if key == self.array_key {
+					map.next_value_seed(ArraySeed {
+						array_key: self.array_key,
+						on_element: &mut self.on_element,
+					})?;
+					self.found_array = true;
+				} else {
+					rest.insert(key, map.next_value()?);
+				}
+			}
+			Ok(rest)
+		}
+	}
+
+	struct ArraySeed<'a, F> {
+		array_key: &'a str,
+		on_element: &'a mut F,
+	}
+
+	impl<'de, F: FnMut(Json<'_>)> DeserializeSeed<'de> for ArraySeed<'_, F> {
+		type Value = ();
+
+		fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+			deserializer.deserialize_seq(self)
+		}
+	}
+
+	impl<'de, F: FnMut(Json<'_>)> Visitor<'de> for ArraySeed<'_, F> {
+		type Value = ();
+
+		fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+			write!(f, "a JSON array")
+		}
+
+		fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+			let mut index = 0;
+			while let Some(element) = seq.next_element::<serde_json::Value>()? {
+				(self.on_element)(Json::new(
+					&element,
+					&format!("{}[{}]", self.array_key, index),
+				));
+				index += 1;
+			}
+			Ok(())
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_stream_array_field() {
+		let json = r#"{"recordsTotal": 2, "data": [{"a": 1}, {"a": 2}], "draw": null}"#;
+		let mut elements = Vec::new();
+		let rest = stream_array_field(json, "data", |json| {
+			elements.push(json.at("a").u32_().unwrap())
+		})
+		.unwrap();
+		assert_eq!(elements, [1, 2]);
+		assert_eq!(rest.len(), 2);
+		assert_eq!(rest["recordsTotal"], 2);
+
+		assert!(stream_array_field(r#"{"data": null}"#, "data", |_| {}).is_err());
+		assert!(stream_array_field(r#"{"other": []}"#, "data", |_| {}).is_err());
+	}
+}
@@ -19,6 +19,10 @@ use etterna::prelude::*;
 )]
 pub struct Replay {
 	pub notes: Vec<ReplayNote>,
+	/// The replay rows exactly as EO sent them. Only present if raw replay retention was enabled
+	/// with `Session::set_keep_raw_replays`
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub raw: Option<RawReplay>,
 }
 
 impl Replay {
@@ -35,45 +39,80 @@ impl Replay {
 	///
 	/// If this replay doesn't have lane and note_type information, None is returned.
 	pub fn split_into_lanes(&self) -> Option<[NoteAndHitSeconds; 4]> {
-		let mut lanes = [
-			NoteAndHitSeconds {
-				note_seconds: vec![],
-				hit_seconds: vec![],
-			},
-			NoteAndHitSeconds {
-				note_seconds: vec![],
-				hit_seconds: vec![],
-			},
-			NoteAndHitSeconds {
-				note_seconds: vec![],
-				hit_seconds: vec![],
-			},
-			NoteAndHitSeconds {
-				note_seconds: vec![],
-				hit_seconds: vec![],
-			},
-		];
+		let mut lanes = self.split_into_lanes_n(4)?.into_iter();
+		// UNWRAP: split_into_lanes_n returns exactly as many lanes as requested
+		Some([
+			lanes.next().unwrap(),
+			lanes.next().unwrap(),
+			lanes.next().unwrap(),
+			lanes.next().unwrap(),
+		])
+	}
+
+	/// Like [`Self::split_into_lanes`], but for an arbitrary keymode. The returned vector has
+	/// `keymode` entries; notes on lanes outside of that are discarded.
+	///
+	/// If this replay doesn't have lane and note_type information, None is returned.
+	pub fn split_into_lanes_n(&self, keymode: usize) -> Option<Vec<NoteAndHitSeconds>> {
+		let mut lanes = vec![NoteAndHitSeconds::default(); keymode];
+		self.split_into_lanes_reusing(&mut lanes)?;
+		Some(lanes)
+	}
+
+	/// Like [`Self::split_into_lanes_n`], but writes into caller-provided buffers instead of
+	/// allocating new ones, with the keymode being the number of buffers. The buffers are cleared
+	/// first, so they can be reused across many replays without reallocating.
+	///
+	/// If this replay doesn't have lane and note_type information, None is returned and the
+	/// buffers are left in an unspecified state.
+	pub fn split_into_lanes_reusing(&self, lanes: &mut [NoteAndHitSeconds]) -> Option<()> {
+		for lane in lanes.iter_mut() {
+			lane.note_seconds.clear();
+			lane.hit_seconds.clear();
+		}
 
 		for note in self.notes.iter() {
-			if note.lane? >= 4 {
-				continue;
-			}
+			let lane = match lanes.get_mut(note.lane? as usize) {
+				Some(lane) => lane,
+				None => continue,
+			};
 
-			if !(note.note_type? == etterna::NoteType::Tap
-				|| note.note_type? == etterna::NoteType::HoldHead)
-			{
+			if !note.is_tap()? {
 				continue;
 			}
 
-			lanes[note.lane? as usize].note_seconds.push(note.time);
+			lane.note_seconds.push(note.time);
 			if let etterna::Hit::Hit { deviation } = note.hit {
-				lanes[note.lane? as usize]
-					.hit_seconds
-					.push(note.time + deviation);
+				lane.hit_seconds.push(note.time + deviation);
 			}
 		}
 
-		Some(lanes)
+		Some(())
+	}
+
+	/// Iterates over the notes that are relevant for straight tapping, i.e. taps and hold heads,
+	/// without allocating.
+	///
+	/// If this replay doesn't have note_type information, None is returned.
+	pub fn iter_taps(&self) -> Option<impl Iterator<Item = &ReplayNote> + '_> {
+		if let Some(note) = self.notes.first() {
+			note.note_type?;
+		}
+		// The note type is either present on all notes or on none
+		Some(self.notes.iter().filter(|note| note.is_tap() == Some(true)))
+	}
+
+	/// Like [`Self::iter_taps`], but only yields notes on the given lane.
+	///
+	/// If this replay doesn't have lane and note_type information, None is returned.
+	pub fn iter_lane(&self, lane: u8) -> Option<impl Iterator<Item = &ReplayNote> + '_> {
+		if let Some(note) = self.notes.first() {
+			note.lane?;
+		}
+		Some(
+			self.iter_taps()?
+				.filter(move |note| note.lane == Some(lane)),
+		)
 	}
 
 	/// Like [`Self::split_into_lanes`], but it doesn't split by lane. Instead, everything is put
@@ -89,9 +128,7 @@ impl Replay {
 		};
 
 		for note in self.notes.iter() {
-			if !(note.note_type? == etterna::NoteType::Tap
-				|| note.note_type? == etterna::NoteType::HoldHead)
-			{
+			if !note.is_tap()? {
 				continue;
 			}
 
@@ -103,6 +140,208 @@ impl Replay {
 
 		Some(result)
 	}
+
+	/// Notes relevant for accuracy, ordered by time: taps and hold heads if the replay has note
+	/// type information, otherwise all notes
+	fn sorted_taps(&self) -> Vec<&ReplayNote> {
+		let mut taps = match self.iter_taps() {
+			Some(taps) => taps.collect::<Vec<_>>(),
+			None => self.notes.iter().collect(),
+		};
+		taps.sort_by(|a, b| a.time.total_cmp(&b.time));
+		taps
+	}
+
+	/// Computes a smoothed accuracy curve: for each note, the Wife3 accuracy of all notes in the
+	/// `window_seconds` up to and including it. Returns `(note time, accuracy)` pairs in
+	/// chronological order.
+	///
+	/// Unlike the cumulative wifescore, dips in this curve show exactly where a run fell apart.
+	/// Mine hits and hold drops are not included, because they're not part of the replay notes.
+	///
+	/// A negative or NaN `window_seconds` is treated as zero, so every note only covers itself.
+	pub fn rolling_accuracy(
+		&self,
+		window_seconds: f32,
+		judge: &etterna::Judge,
+	) -> Vec<(f32, Wifescore)> {
+		// f32::max returns the other operand if one is NaN
+		let window_seconds = window_seconds.max(0.0);
+		let hits = self.sorted_taps();
+		let points = hits
+			.iter()
+			.map(|note| etterna::wife3(note.hit, judge))
+			.collect::<Vec<_>>();
+
+		let mut curve = Vec::with_capacity(hits.len());
+		let mut window_start = 0;
+		let mut window_sum = 0.0;
+		for (i, note) in hits.iter().enumerate() {
+			let time = note.time;
+			window_sum += points[i];
+			while hits[window_start].time < time - window_seconds {
+				window_sum -= points[window_start];
+				window_start += 1;
+			}
+
+			let accuracy = window_sum / (i + 1 - window_start) as f32;
+			// Wife3 points are at most 1, so this only fails for NaN from a NaN note time
+			if let Some(accuracy) = Wifescore::from_proportion(accuracy) {
+				curve.push((time, accuracy));
+			}
+		}
+		curve
+	}
+
+	/// Finds sections of the replay where combo breakers (goods, bads and misses) cluster, e.g. to
+	/// tell a player "most of your misses are between 1:05 and 1:12".
+	///
+	/// Combo breakers that are at most [`Self::PROBLEM_SECTION_GAP`] seconds apart belong to the
+	/// same section. Sections with fewer than `min_cluster_size` combo breakers are discarded. The
+	/// sections are returned in chronological order.
+	pub fn problem_sections(
+		&self,
+		judge: &etterna::Judge,
+		min_cluster_size: u32,
+	) -> Vec<ProblemSection> {
+		let mut sections = Vec::new();
+		let mut current: Option<ProblemSection> = None;
+		for note in self.sorted_taps() {
+			let (time, hit) = (note.time, note.hit);
+			let is_miss = matches!(hit, etterna::Hit::Miss);
+			let is_combo_breaker = matches!(
+				hit.classify(judge),
+				etterna::TapJudgement::Good
+					| etterna::TapJudgement::Bad
+					| etterna::TapJudgement::Miss
+			);
+			if !is_combo_breaker {
+				continue;
+			}
+
+			match &mut current {
+				Some(section) if time - section.end <= Self::PROBLEM_SECTION_GAP => {
+					section.end = time;
+					section.end_tick = note.tick;
+				}
+				_ => {
+					sections.extend(current.take());
+					current = Some(ProblemSection {
+						start: time,
+						end: time,
+						start_tick: note.tick,
+						end_tick: note.tick,
+						num_combo_breakers: 0,
+						num_misses: 0,
+					});
+				}
+			}
+			// UNWRAP: set right above
+			let section = current.as_mut().unwrap();
+			section.num_combo_breakers += 1;
+			if is_miss {
+				section.num_misses += 1;
+			}
+		}
+		sections.extend(current);
+
+		sections.retain(|section| section.num_combo_breakers >= min_cluster_size);
+		sections
+	}
+
+	/// Maximum number of seconds between two combo breakers of the same problem section, see
+	/// [`Self::problem_sections`]
+	pub const PROBLEM_SECTION_GAP: f32 = 2.0;
+
+	/// Returns a copy of the replay with note times reconstructed from the note ticks, because the
+	/// times EO sends are slightly off.
+	///
+	/// The ticks don't say how long a beat is, so the chart's BPM changes have to be given as
+	/// `(tick, bpm)` pairs, sorted by tick; the first one should be at tick 0. The chart duration is
+	/// divided by `rate`, like in the game. Since the chart offset isn't known either, the new
+	/// times are shifted so that they agree with EO's times on median.
+	///
+	/// Returns None if the replay doesn't have tick information or no BPM is given.
+	pub fn with_times_from_ticks(&self, bpms: &[(u32, f32)], rate: Rate) -> Option<Replay> {
+		let first_bpm = bpms.first()?;
+		let rate = rate.as_f32();
+		// The time at which each BPM segment starts, unscaled by the rate
+		let mut segments = Vec::with_capacity(bpms.len());
+		let mut segment_start = 0.0;
+		let mut previous = (0, first_bpm.1);
+		for &(tick, bpm) in bpms {
+			segment_start += ticks_to_seconds(tick.saturating_sub(previous.0), previous.1);
+			segments.push((tick, bpm, segment_start));
+			previous = (tick, bpm);
+		}
+		let tick_time = |tick: u32| {
+			let &(segment_tick, bpm, segment_start) = segments
+				.iter()
+				.rev()
+				.find(|&&(segment_tick, _, _)| segment_tick <= tick)
+				.unwrap_or(&segments[0]);
+			(segment_start + ticks_to_seconds(tick.saturating_sub(segment_tick), bpm)) / rate
+		};
+
+		let mut times = self
+			.notes
+			.iter()
+			.map(|note| Some(tick_time(note.tick?)))
+			.collect::<Option<Vec<_>>>()?;
+
+		let mut offsets = self
+			.notes
+			.iter()
+			.zip(&times)
+			.map(|(note, time)| note.time - time)
+			.collect::<Vec<_>>();
+		offsets.sort_by(|a, b| a.total_cmp(b));
+		let offset = offsets.get(offsets.len() / 2).copied().unwrap_or(0.0);
+		for time in &mut times {
+			*time += offset;
+		}
+
+		Some(Replay {
+			notes: self
+				.notes
+				.iter()
+				.zip(times)
+				.map(|(note, time)| ReplayNote {
+					time,
+					..note.clone()
+				})
+				.collect(),
+			raw: self.raw.clone(),
+		})
+	}
+}
+
+/// Etterna places 48 ticks on each beat
+fn ticks_to_seconds(ticks: u32, bpm: f32) -> f32 {
+	ticks as f32 / 48.0 * 60.0 / bpm
+}
+
+/// A section of a replay with many combo breakers, see [`Replay::problem_sections`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct ProblemSection {
+	/// Time of the first combo breaker, in seconds
+	pub start: f32,
+	/// Time of the last combo breaker, in seconds
+	pub end: f32,
+	/// Position of the first combo breaker in ticks (192nds), if the replay has tick information
+	pub start_tick: Option<u32>,
+	/// Position of the last combo breaker in ticks (192nds), if the replay has tick information
+	pub end_tick: Option<u32>,
+	/// Number of goods, bads and misses in this section
+	pub num_combo_breakers: u32,
+	/// Number of misses in this section
+	pub num_misses: u32,
 }
 
 impl etterna::SimpleReplay for Replay {
@@ -111,6 +350,157 @@ impl etterna::SimpleReplay for Replay {
 	}
 }
 
+/// Alternative, column-wise layout of a [`Replay`]: one array per field instead of one struct per
+/// note. This takes less memory and is faster to scan when analyzing large numbers of replays.
+///
+/// The optional columns are either as long as [`Self::times`] or absent, following the same
+/// guarantees as [`Replay`].
+///
+/// Convert with `ReplayColumns::from(&replay)` and `Replay::from(columns)`. Analyses from
+/// [`etterna::SimpleReplay`] are available on both representations.
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+pub struct ReplayColumns {
+	/// See [`ReplayNote::time`]
+	pub times: Vec<f32>,
+	/// Hit deviation in seconds, or NaN if the note was missed. See [`Self::hit`]
+	pub deviations: Vec<f32>,
+	/// See [`ReplayNote::lane`]
+	pub lanes: Option<Vec<u8>>,
+	/// See [`ReplayNote::note_type`]
+	pub note_types: Option<Vec<NoteType>>,
+	/// See [`ReplayNote::tick`]
+	pub ticks: Option<Vec<u32>>,
+}
+
+impl ReplayColumns {
+	/// Number of notes
+	pub fn len(&self) -> usize {
+		self.times.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.times.is_empty()
+	}
+
+	/// The hit of the note at the given index
+	///
+	/// Panics if the index is out of bounds
+	pub fn hit(&self, index: usize) -> etterna::Hit {
+		let deviation = self.deviations[index];
+		if deviation.is_nan() {
+			etterna::Hit::Miss
+		} else {
+			etterna::Hit::Hit { deviation }
+		}
+	}
+
+	/// The note at the given index, or None if out of bounds
+	pub fn note(&self, index: usize) -> Option<ReplayNote> {
+		Some(ReplayNote {
+			time: *self.times.get(index)?,
+			hit: self.hit(index),
+			lane: self.lanes.as_ref().map(|lanes| lanes[index]),
+			note_type: self.note_types.as_ref().map(|note_types| note_types[index]),
+			tick: self.ticks.as_ref().map(|ticks| ticks[index]),
+		})
+	}
+
+	/// Same as [`Replay::split_into_lanes_reusing`]
+	pub fn split_into_lanes_reusing(&self, lanes: &mut [NoteAndHitSeconds]) -> Option<()> {
+		for lane in lanes.iter_mut() {
+			lane.note_seconds.clear();
+			lane.hit_seconds.clear();
+		}
+
+		let note_lanes = self.lanes.as_ref()?;
+		let note_types = self.note_types.as_ref()?;
+		for (i, &time) in self.times.iter().enumerate() {
+			let lane = match lanes.get_mut(note_lanes[i] as usize) {
+				Some(lane) => lane,
+				None => continue,
+			};
+			if note_types[i] != NoteType::Tap && note_types[i] != NoteType::HoldHead {
+				continue;
+			}
+
+			lane.note_seconds.push(time);
+			let deviation = self.deviations[i];
+			if !deviation.is_nan() {
+				lane.hit_seconds.push(time + deviation);
+			}
+		}
+
+		Some(())
+	}
+
+	/// Same as [`Replay::split_into_lanes_n`]
+	pub fn split_into_lanes_n(&self, keymode: usize) -> Option<Vec<NoteAndHitSeconds>> {
+		let mut lanes = vec![NoteAndHitSeconds::default(); keymode];
+		self.split_into_lanes_reusing(&mut lanes)?;
+		Some(lanes)
+	}
+}
+
+impl From<&Replay> for ReplayColumns {
+	fn from(replay: &Replay) -> Self {
+		// The optional fields are either present on all notes or on none
+		fn column<T>(
+			notes: &[ReplayNote],
+			field: impl Fn(&ReplayNote) -> Option<T>,
+		) -> Option<Vec<T>> {
+			notes.iter().map(field).collect()
+		}
+
+		Self {
+			times: replay.notes.iter().map(|note| note.time).collect(),
+			deviations: replay
+				.notes
+				.iter()
+				.map(|note| note.hit.deviation().unwrap_or(f32::NAN))
+				.collect(),
+			lanes: column(&replay.notes, |note| note.lane),
+			note_types: column(&replay.notes, |note| note.note_type),
+			ticks: column(&replay.notes, |note| note.tick),
+		}
+	}
+}
+
+impl From<ReplayColumns> for Replay {
+	fn from(columns: ReplayColumns) -> Self {
+		Self {
+			notes: (0..columns.len()).filter_map(|i| columns.note(i)).collect(),
+			raw: None,
+		}
+	}
+}
+
+impl etterna::SimpleReplay for ReplayColumns {
+	fn iter_hits(&self) -> Box<dyn '_ + Iterator<Item = etterna::Hit>> {
+		Box::new((0..self.len()).map(move |i| self.hit(i)))
+	}
+}
+
+/// A replay as EO sent it: one array of numbers per note, without any interpretation.
+///
+/// Normally, a row consists of `[time, deviation in ms, lane, note type, tick]`, where the trailing
+/// entries may be missing (see [`Replay`]). However, some replays have rows with only three
+/// entries, where the third one is the tick instead of the lane. [`ReplayNote`] resolves that
+/// ambiguity heuristically; this type leaves it to you.
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+pub struct RawReplay {
+	pub rows: Vec<Vec<f64>>,
+}
+
 /// A singular note, used inside [`Replay`]
 #[derive(Debug, PartialEq, Clone)]
 #[cfg_attr(
@@ -133,6 +523,139 @@ pub struct ReplayNote {
 	pub tick: Option<u32>,
 }
 
+impl ReplayNote {
+	/// Whether this note is relevant for straight tapping, i.e. a tap or a hold head. None if the
+	/// note type is unknown
+	pub fn is_tap(&self) -> Option<bool> {
+		let note_type = self.note_type?;
+		Some(note_type == etterna::NoteType::Tap || note_type == etterna::NoteType::HoldHead)
+	}
+
+	/// How early or late the note was hit. None if the note was missed
+	pub fn offset(&self) -> Option<Offset> {
+		Offset::from_hit(self.hit)
+	}
+}
+
+/// How early or late a note was hit. Negative offsets are early, positive offsets are late.
+///
+/// [`etterna::Hit`] and most of this crate store deviations as raw seconds; this type makes the
+/// unit explicit. Convert from a hit with [`Offset::from_hit`] or [`ReplayNote::offset`], and back
+/// with `etterna::Hit::from(offset)`.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+pub struct Offset {
+	seconds: f32,
+}
+
+impl Offset {
+	pub fn from_seconds(seconds: f32) -> Self {
+		Self { seconds }
+	}
+
+	pub fn from_millis(millis: f32) -> Self {
+		Self {
+			seconds: millis / 1000.0,
+		}
+	}
+
+	/// The offset of a hit, or None if the note was missed
+	pub fn from_hit(hit: etterna::Hit) -> Option<Self> {
+		hit.deviation().map(Self::from_seconds)
+	}
+
+	pub fn as_seconds(self) -> f32 {
+		self.seconds
+	}
+
+	pub fn as_millis(self) -> f32 {
+		self.seconds * 1000.0
+	}
+
+	pub fn is_early(self) -> bool {
+		self.seconds < 0.0
+	}
+
+	pub fn is_late(self) -> bool {
+		self.seconds > 0.0
+	}
+
+	/// The offset without its direction
+	pub fn abs(self) -> Self {
+		Self::from_seconds(self.seconds.abs())
+	}
+}
+
+impl From<Offset> for etterna::Hit {
+	fn from(offset: Offset) -> Self {
+		Self::Hit {
+			deviation: offset.seconds,
+		}
+	}
+}
+
+impl std::fmt::Display for Offset {
+	/// Formats the offset in milliseconds with sign, e.g. `-12.3ms`
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{:+.1}ms", self.as_millis())
+	}
+}
+
+macro_rules! id_newtype {
+	($(#[$attr:meta])* $name:ident) => {
+		$(#[$attr])*
+		///
+		/// Convert from and to the raw number with `From`/`Into`.
+		#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Default)]
+		#[cfg_attr(
+			feature = "serde",
+			derive(serde::Serialize, serde::Deserialize),
+			serde(crate = "serde_", transparent)
+		)]
+		pub struct $name(pub u32);
+
+		impl From<u32> for $name {
+			fn from(id: u32) -> Self {
+				Self(id)
+			}
+		}
+
+		impl From<$name> for u32 {
+			fn from(id: $name) -> Self {
+				id.0
+			}
+		}
+
+		impl std::fmt::Display for $name {
+			fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+				self.0.fmt(f)
+			}
+		}
+
+		impl std::str::FromStr for $name {
+			type Err = std::num::ParseIntError;
+
+			fn from_str(s: &str) -> Result<Self, Self::Err> {
+				s.parse().map(Self)
+			}
+		}
+	};
+}
+
+id_newtype!(
+	/// EO's numeric id of a song, as seen in `etternaonline.com/song/view/<id>` URLs
+	SongId
+);
+
+id_newtype!(
+	/// EO's numeric id of a pack, as seen in `etternaonline.com/pack/<id>` URLs
+	PackId
+);
+
 /// Represents a file size
 #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Default)]
 #[cfg_attr(
@@ -237,6 +760,136 @@ impl std::str::FromStr for FileSize {
 mod tests {
 	use super::*;
 
+	fn tap(time: f32, hit: etterna::Hit) -> ReplayNote {
+		ReplayNote {
+			time,
+			hit,
+			lane: Some(0),
+			note_type: Some(NoteType::Tap),
+			tick: None,
+		}
+	}
+
+	#[test]
+	fn test_replay_columns() {
+		let replay = Replay {
+			notes: vec![
+				tap(0.0, etterna::Hit::Hit { deviation: 0.01 }),
+				tap(0.5, etterna::Hit::Miss),
+				tap(1.0, etterna::Hit::Hit { deviation: -0.03 }),
+			],
+			raw: None,
+		};
+
+		let columns = ReplayColumns::from(&replay);
+		assert_eq!(columns.len(), 3);
+		assert_eq!(columns.ticks, None);
+		assert_eq!(columns.hit(1), etterna::Hit::Miss);
+		assert_eq!(
+			columns.tap_judgements(etterna::J4),
+			replay.tap_judgements(etterna::J4)
+		);
+		assert_eq!(columns.split_into_lanes_n(4), replay.split_into_lanes_n(4));
+		assert_eq!(Replay::from(columns), replay);
+	}
+
+	#[test]
+	fn test_rolling_accuracy() {
+		let perfect = etterna::Hit::Hit { deviation: 0.0 };
+		let replay = Replay {
+			notes: vec![
+				tap(0.0, perfect),
+				tap(1.0, perfect),
+				tap(2.0, etterna::Hit::Miss),
+				tap(5.0, perfect),
+			],
+			raw: None,
+		};
+
+		let curve = replay
+			.rolling_accuracy(1.5, etterna::J4)
+			.into_iter()
+			.map(|(time, accuracy)| (time, accuracy.as_proportion()))
+			.collect::<Vec<_>>();
+		let miss = etterna::wife3(etterna::Hit::Miss, etterna::J4);
+		assert_eq!(
+			curve,
+			vec![
+				(0.0, 1.0),
+				(1.0, 1.0),
+				// The note at 0.0 has left the window
+				(2.0, (1.0 + miss) / 2.0),
+				(5.0, 1.0),
+			]
+		);
+
+		// Would otherwise shrink the window past the current note
+		let per_note = replay.rolling_accuracy(0.0, etterna::J4);
+		assert_eq!(per_note.len(), 4);
+		assert_eq!(replay.rolling_accuracy(-1.0, etterna::J4), per_note);
+		assert_eq!(replay.rolling_accuracy(f32::NAN, etterna::J4), per_note);
+	}
+
+	#[test]
+	fn test_problem_sections() {
+		let good = etterna::Hit::Hit { deviation: 0.1 };
+		let marvelous = etterna::Hit::Hit { deviation: 0.0 };
+		let replay = Replay {
+			notes: vec![
+				tap(0.0, etterna::Hit::Miss),
+				tap(10.0, etterna::Hit::Miss),
+				tap(11.0, marvelous),
+				tap(11.5, good),
+				tap(13.0, etterna::Hit::Miss),
+				tap(20.0, marvelous),
+			],
+			raw: None,
+		};
+
+		let sections = replay.problem_sections(etterna::J4, 2);
+		assert_eq!(sections.len(), 1);
+		assert_eq!((sections[0].start, sections[0].end), (10.0, 13.0));
+		assert_eq!(sections[0].num_combo_breakers, 3);
+		assert_eq!(sections[0].num_misses, 2);
+	}
+
+	#[test]
+	fn test_times_from_ticks() {
+		let note = |time, tick| ReplayNote {
+			tick: Some(tick),
+			..tap(time, etterna::Hit::Miss)
+		};
+		// 0.01s off, and the last note more so
+		let replay = Replay {
+			notes: vec![
+				note(1.01, 0),
+				note(1.26, 48),
+				note(1.51, 96),
+				note(1.9, 144),
+			],
+			raw: None,
+		};
+
+		// 120 BPM is half a second per beat, at 2.0x a quarter
+		let fixed = replay
+			.with_times_from_ticks(&[(0, 120.0)], Rate::from_f32(2.0).unwrap())
+			.unwrap();
+		let times = fixed.notes.iter().map(|n| n.time).collect::<Vec<_>>();
+		for (time, expected) in times.iter().zip([1.01, 1.26, 1.51, 1.76]) {
+			assert!((time - expected).abs() < 1e-5, "{:?}", times);
+		}
+
+		// Doubling the BPM halfway
+		let fixed = replay
+			.with_times_from_ticks(&[(0, 120.0), (96, 240.0)], Rate::from_f32(1.0).unwrap())
+			.unwrap();
+		let times = fixed.notes.iter().map(|n| n.time).collect::<Vec<_>>();
+		let gaps = times.windows(2).map(|w| w[1] - w[0]).collect::<Vec<_>>();
+		for (gap, expected) in gaps.iter().zip([0.5, 0.5, 0.25]) {
+			assert!((gap - expected).abs() < 1e-5, "{:?}", times);
+		}
+	}
+
 	#[test]
 	fn test_split_replay() {
 		let replay = Replay {
@@ -277,6 +930,7 @@ mod tests {
 					tick: None,
 				},
 			],
+			raw: None,
 		};
 
 		assert_eq!(
@@ -310,7 +964,11 @@ mod tests {
 		);
 
 		assert_eq!(
-			Replay { notes: vec![] }.split_into_notes_and_hits(),
+			Replay {
+				notes: vec![],
+				raw: None,
+			}
+			.split_into_notes_and_hits(),
 			Some(NoteAndHitSeconds {
 				note_seconds: vec![],
 				hit_seconds: vec![],
@@ -318,7 +976,11 @@ mod tests {
 		);
 
 		assert_eq!(
-			Replay { notes: vec![] }.split_into_lanes(),
+			Replay {
+				notes: vec![],
+				raw: None,
+			}
+			.split_into_lanes(),
 			Some([
 				NoteAndHitSeconds {
 					note_seconds: vec![],
@@ -0,0 +1,215 @@
+//! Building blocks for the `wire` feature, which makes the serde representation of the structs
+//! match EO's responses. The structs are converted from and into mirrors of the response JSON,
+//! which live in `v1::wire` and `v2::wire`; the parts that both APIs share are here
+
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+pub(crate) fn invalid(what: &str, value: impl std::fmt::Display) -> Error {
+	Error::InvalidDataStructure(format!("Expected {}, got {}", what, value))
+}
+
+pub(crate) fn wifescore_from_percent(percent: f32) -> Result<etterna::Wifescore, Error> {
+	etterna::Wifescore::from_percent(percent).ok_or_else(|| invalid("wifescore percent", percent))
+}
+
+pub(crate) fn wifescore_from_proportion(proportion: f32) -> Result<etterna::Wifescore, Error> {
+	etterna::Wifescore::from_proportion(proportion)
+		.ok_or_else(|| invalid("wifescore proportion", proportion))
+}
+
+pub(crate) fn rate_from_f32(rate: f32) -> Result<etterna::Rate, Error> {
+	etterna::Rate::from_f32(rate).ok_or_else(|| invalid("rate", rate))
+}
+
+/// For the numbers and keys that v1 sends as strings
+pub(crate) fn parse_str<T: std::str::FromStr>(string: &str) -> Result<T, Error> {
+	string
+		.parse()
+		.map_err(|_| invalid(std::any::type_name::<T>(), format!("{:?}", string)))
+}
+
+/// For the booleans that v1 sends as "0" or "1"
+pub(crate) fn bool_from_int_str(string: &str) -> Result<bool, Error> {
+	match string {
+		"0" => Ok(false),
+		"1" => Ok(true),
+		other => Err(invalid("\"0\" or \"1\"", format!("{:?}", other))),
+	}
+}
+
+pub(crate) fn bool_to_int_str(boolean: bool) -> String {
+	if boolean { "1" } else { "0" }.to_owned()
+}
+
+/// Skillsets as they appear in v2 responses. Some responses leave out `Overall` and send the
+/// overall rating next to the skillsets instead
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_")]
+pub(crate) struct Skillsets {
+	#[serde(rename = "Overall", default, skip_serializing_if = "Option::is_none")]
+	pub overall: Option<f32>,
+	#[serde(rename = "Stream")]
+	pub stream: f32,
+	#[serde(rename = "Jumpstream")]
+	pub jumpstream: f32,
+	#[serde(rename = "Handstream")]
+	pub handstream: f32,
+	#[serde(rename = "Stamina")]
+	pub stamina: f32,
+	#[serde(rename = "JackSpeed")]
+	pub jackspeed: f32,
+	#[serde(rename = "Chordjack")]
+	pub chordjack: f32,
+	#[serde(rename = "Technical")]
+	pub technical: f32,
+}
+
+impl Skillsets {
+	pub fn new(ssr: &etterna::Skillsets8) -> Self {
+		Self {
+			overall: Some(ssr.overall),
+			stream: ssr.stream,
+			jumpstream: ssr.jumpstream,
+			handstream: ssr.handstream,
+			stamina: ssr.stamina,
+			jackspeed: ssr.jackspeed,
+			chordjack: ssr.chordjack,
+			technical: ssr.technical,
+		}
+	}
+
+	/// For responses that send the overall rating outside of the skillsets
+	pub fn without_overall(ssr: &etterna::Skillsets8) -> Self {
+		Self {
+			overall: None,
+			..Self::new(ssr)
+		}
+	}
+
+	pub fn into_skillsets8(self) -> Result<etterna::Skillsets8, Error> {
+		let overall = self
+			.overall
+			.ok_or_else(|| invalid("Overall skillset", "nothing"))?;
+		Ok(self.into_skillsets8_with_overall(overall))
+	}
+
+	pub fn into_skillsets8_with_overall(self, overall: f32) -> etterna::Skillsets8 {
+		etterna::Skillsets8 {
+			overall,
+			stream: self.stream,
+			jumpstream: self.jumpstream,
+			handstream: self.handstream,
+			stamina: self.stamina,
+			jackspeed: self.jackspeed,
+			chordjack: self.chordjack,
+			technical: self.technical,
+		}
+	}
+}
+
+/// Judgements as they appear in v2 responses
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_", rename_all = "camelCase")]
+pub(crate) struct Judgements {
+	marvelous: u32,
+	perfect: u32,
+	great: u32,
+	good: u32,
+	bad: u32,
+	miss: u32,
+	hit_mines: u32,
+	held_hold: u32,
+	let_go_hold: u32,
+	missed_hold: u32,
+}
+
+impl From<etterna::FullJudgements> for Judgements {
+	fn from(judgements: etterna::FullJudgements) -> Self {
+		Self {
+			marvelous: judgements.marvelouses,
+			perfect: judgements.perfects,
+			great: judgements.greats,
+			good: judgements.goods,
+			bad: judgements.bads,
+			miss: judgements.misses,
+			hit_mines: judgements.hit_mines,
+			held_hold: judgements.held_holds,
+			let_go_hold: judgements.let_go_holds,
+			missed_hold: judgements.missed_holds,
+		}
+	}
+}
+
+impl From<Judgements> for etterna::FullJudgements {
+	fn from(judgements: Judgements) -> Self {
+		Self {
+			marvelouses: judgements.marvelous,
+			perfects: judgements.perfect,
+			greats: judgements.great,
+			goods: judgements.good,
+			bads: judgements.bad,
+			misses: judgements.miss,
+			hit_mines: judgements.hit_mines,
+			held_holds: judgements.held_hold,
+			let_go_holds: judgements.let_go_hold,
+			missed_holds: judgements.missed_hold,
+		}
+	}
+}
+
+fn note_type_to_eo(note_type: etterna::NoteType) -> u32 {
+	match note_type {
+		etterna::NoteType::Tap => 1,
+		etterna::NoteType::HoldHead => 2,
+		etterna::NoteType::HoldTail => 3,
+		etterna::NoteType::Mine => 4,
+		etterna::NoteType::Lift => 5,
+		etterna::NoteType::Keysound => 6,
+		etterna::NoteType::Fake => 7,
+	}
+}
+
+/// Inverse of [`crate::parse_replay`]. Replays that EO sent but this crate couldn't parse are
+/// written back unchanged, as are the raw rows if they were kept. Otherwise the rows are rebuilt
+/// from the notes
+pub(crate) fn replay_to_eo(
+	replay: Option<&crate::Replay>,
+	replay_raw: Option<&str>,
+) -> serde_json::Value {
+	if let Some(replay_raw) = replay_raw {
+		return replay_raw.into();
+	}
+	let replay = match replay {
+		Some(replay) => replay,
+		None => return serde_json::Value::Null,
+	};
+	if let Some(raw) = &replay.raw {
+		// UNWRAP: the rows are plain numbers, which always serialize
+		return serde_json::to_string(&raw.rows).unwrap().into();
+	}
+
+	let rows = replay
+		.notes
+		.iter()
+		.map(|note| {
+			let deviation = match note.hit {
+				etterna::Hit::Hit { deviation } => deviation * 1000.0,
+				etterna::Hit::Miss => 180.0,
+			};
+			let mut row = vec![serde_json::json!(note.time), serde_json::json!(deviation)];
+			// Rows come either as [time, deviation, tick] or with lane and note type in between
+			if let Some(note_type) = note.note_type {
+				row.push(match note.lane {
+					Some(lane) => lane.into(),
+					None => (-1).into(),
+				});
+				row.push(note_type_to_eo(note_type).into());
+			}
+			row.extend(note.tick.map(serde_json::Value::from));
+			serde_json::Value::Array(row)
+		})
+		.collect::<Vec<_>>();
+	serde_json::Value::Array(rows).to_string().into()
+}
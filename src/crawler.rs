@@ -0,0 +1,656 @@
+//! Resumable crawling of large datasets, for archiving EO.
+//!
+//! [`Crawler`] walks the web leaderboard from top to bottom and retrieves all scores of every
+//! player on it. Progress is saved to a [`CheckpointStore`] after each page, so that a crawl that
+//! takes days can be interrupted and resumed without fetching everything again. How hard the
+//! crawler may hit EO is controlled with [`Politeness`].
+//!
+//! [`TopScoresCrawl`] is a smaller crawl that only retrieves the best scores of the best players,
+//! e.g. to find the hardest scores in the world.
+
+use crate::web::{
+	LeaderboardEntry, LeaderboardSortBy, Session, SortDirection, UserScore, UserScoresSortBy,
+};
+use crate::Error;
+
+/// How far a crawl has progressed. The default value is the start of a crawl
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+pub struct Checkpoint {
+	/// Leaderboard position (starting at 0) of the player currently being crawled
+	pub leaderboard_position: u32,
+	/// Next page of the current player's scores to retrieve
+	pub score_page: u32,
+	/// The UTC day (days since the Unix epoch) that [`Self::requests_on_budget_day`] refers to
+	pub budget_day: u64,
+	/// Number of requests made on [`Self::budget_day`], for enforcing
+	/// [`Politeness::daily_request_budget`] across restarts
+	pub requests_on_budget_day: u32,
+}
+
+/// A daily time span in UTC during which the crawler pauses, e.g. EO's peak hours
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PauseWindow {
+	start: std::time::Duration,
+	end: std::time::Duration,
+}
+
+impl PauseWindow {
+	/// Creates a pause window from `start` to `end`, given as time since midnight UTC. If `end` is
+	/// before `start`, the window spans midnight.
+	///
+	/// Panics if either time is 24 hours or more
+	pub fn new(start: std::time::Duration, end: std::time::Duration) -> Self {
+		assert!(
+			start < DAY && end < DAY,
+			"pause window times must be within a day"
+		);
+		Self { start, end }
+	}
+
+	/// If the given time since midnight UTC is within the window, returns how long the window
+	/// lasts from then on
+	pub fn remaining(&self, time_of_day: std::time::Duration) -> Option<std::time::Duration> {
+		if self.start <= self.end {
+			if self.start <= time_of_day && time_of_day < self.end {
+				return Some(self.end - time_of_day);
+			}
+		} else if time_of_day >= self.start {
+			return Some(DAY - time_of_day + self.end);
+		} else if time_of_day < self.end {
+			return Some(self.end - time_of_day);
+		}
+		None
+	}
+}
+
+const DAY: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+/// Limits on how hard a [`Crawler`] may hit EO. The default imposes no limits beyond the
+/// session's own rate limit
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct Politeness {
+	/// Maximum number of requests that all crawlers in this process may have in flight at once
+	pub max_concurrent_requests: Option<usize>,
+	/// Extra delay after every page, on top of the session's rate limit
+	pub page_delay: std::time::Duration,
+	/// Times of day during which the crawler doesn't send requests
+	pub pause_windows: Vec<PauseWindow>,
+	/// Maximum number of requests per UTC day. Once reached, the crawl stops with
+	/// [`CrawlStatus::BudgetExhausted`]
+	pub daily_request_budget: Option<u32>,
+}
+
+/// Why [`Crawler::run`] returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrawlStatus {
+	/// The end of the leaderboard was reached
+	Finished,
+	/// The daily request budget is used up. Run again on the next day to continue
+	BudgetExhausted,
+}
+
+/// A semaphore for the crawler requests in flight in this process. Hand-rolled instead of e.g.
+/// tokio's, so that crawling doesn't require a particular executor
+struct InFlight {
+	count: usize,
+	/// Tasks waiting for a free slot
+	waiters: Vec<std::task::Waker>,
+}
+
+static REQUESTS_IN_FLIGHT: std::sync::Mutex<InFlight> = std::sync::Mutex::new(InFlight {
+	count: 0,
+	waiters: Vec::new(),
+});
+
+/// Waits until fewer than `max` crawler requests are in flight and takes a slot
+async fn acquire_in_flight_slot(max: usize) -> InFlightGuard {
+	std::future::poll_fn(|cx| {
+		// UNWRAP: propagate panics
+		let mut in_flight = REQUESTS_IN_FLIGHT.lock().unwrap();
+		if in_flight.count < max {
+			in_flight.count += 1;
+			std::task::Poll::Ready(InFlightGuard)
+		} else {
+			in_flight.waiters.push(cx.waker().clone());
+			std::task::Poll::Pending
+		}
+	})
+	.await
+}
+
+/// Releases a [`REQUESTS_IN_FLIGHT`] slot on drop
+struct InFlightGuard;
+
+impl Drop for InFlightGuard {
+	fn drop(&mut self) {
+		let waiters = {
+			// UNWRAP: propagate panics
+			let mut in_flight = REQUESTS_IN_FLIGHT.lock().unwrap();
+			in_flight.count -= 1;
+			std::mem::take(&mut in_flight.waiters)
+		};
+		// Wake all waiters rather than one, because a waiter may have been dropped meanwhile and
+		// the slot would stay unused
+		for waker in waiters {
+			waker.wake();
+		}
+	}
+}
+
+/// Wall-clock time that advances with the session's clock, so that e.g. waiting out a pause
+/// window under an [`InstantClock`](crate::clock::InstantClock) also moves the time of day along
+struct UtcClock<'a> {
+	clock: &'a dyn crate::clock::Clock,
+	/// Time since the Unix epoch at [`Self::anchor`]
+	anchor_utc: std::time::Duration,
+	anchor: std::time::Instant,
+}
+
+impl<'a> UtcClock<'a> {
+	fn new(clock: &'a dyn crate::clock::Clock) -> Self {
+		Self {
+			clock,
+			anchor_utc: std::time::SystemTime::now()
+				.duration_since(std::time::UNIX_EPOCH)
+				.unwrap_or_default(),
+			anchor: clock.now(),
+		}
+	}
+
+	/// Time since the Unix epoch
+	fn now(&self) -> std::time::Duration {
+		self.anchor_utc + self.clock.now().saturating_duration_since(self.anchor)
+	}
+
+	/// Days since the Unix epoch
+	fn day(&self) -> u64 {
+		self.now().as_secs() / DAY.as_secs()
+	}
+
+	/// Time since midnight UTC
+	fn time_of_day(&self) -> std::time::Duration {
+		std::time::Duration::from_secs(self.now().as_secs() % DAY.as_secs())
+	}
+}
+
+/// Persists the [`Checkpoint`] of a crawl, e.g. to a file or a database.
+///
+/// Saving happens after every page, so it should be cheap. Errors can't be reported and should
+/// be logged or ignored by the implementation
+pub trait CheckpointStore: Send + Sync {
+	/// Returns the previously saved checkpoint, if any
+	fn load(&self) -> Option<Checkpoint>;
+
+	fn save(&self, checkpoint: Checkpoint);
+}
+
+/// A [`CheckpointStore`] that saves the checkpoint as space-separated numbers to a file
+#[derive(Debug, Clone)]
+pub struct FileCheckpointStore {
+	path: std::path::PathBuf,
+}
+
+impl FileCheckpointStore {
+	pub fn new(path: impl Into<std::path::PathBuf>) -> Self {
+		Self { path: path.into() }
+	}
+}
+
+impl CheckpointStore for FileCheckpointStore {
+	fn load(&self) -> Option<Checkpoint> {
+		let contents = std::fs::read_to_string(&self.path).ok()?;
+		let numbers = contents
+			.split_whitespace()
+			.map(|n| n.parse().ok())
+			.collect::<Option<Vec<u64>>>()?;
+		// Checkpoints saved before the daily budget existed only have the first two numbers
+		Some(Checkpoint {
+			leaderboard_position: *numbers.first()? as u32,
+			score_page: *numbers.get(1)? as u32,
+			budget_day: numbers.get(2).copied().unwrap_or(0),
+			requests_on_budget_day: numbers.get(3).copied().unwrap_or(0) as u32,
+		})
+	}
+
+	fn save(&self, checkpoint: Checkpoint) {
+		let contents = format!(
+			"{} {} {} {}",
+			checkpoint.leaderboard_position,
+			checkpoint.score_page,
+			checkpoint.budget_day,
+			checkpoint.requests_on_budget_day,
+		);
+		if let Err(e) = std::fs::write(&self.path, contents) {
+			log::warn!("failed to save crawl checkpoint to {:?}: {}", self.path, e);
+		}
+	}
+}
+
+/// Data found by the [`Crawler`]
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum CrawlItem {
+	/// A player whose scores are about to be crawled
+	User {
+		entry: LeaderboardEntry,
+		user_id: u32,
+	},
+	/// A page of a player's scores
+	Scores {
+		username: String,
+		user_id: u32,
+		scores: Vec<UserScore>,
+	},
+}
+
+/// Crawls all players on the web leaderboard and all of their scores, including invalid ones.
+///
+/// Every item is passed to the handler before the checkpoint is advanced past it, so after a
+/// crash, the page that was being processed may be handed out a second time, but nothing is
+/// skipped. The leaderboard can shift while a crawl is running, which may cause players around
+/// the current position to be crawled twice or not at all.
+///
+/// # Example
+/// ```rust,no_run
+/// # async fn run() -> Result<(), etternaonline_api::Error> {
+/// use etternaonline_api::crawler::*;
+/// # let session: etternaonline_api::web::Session = unimplemented!();
+///
+/// let mut crawler = Crawler::new(&session, FileCheckpointStore::new("crawl.checkpoint"));
+/// crawler
+/// 	.run(|item| {
+/// 		if let CrawlItem::Scores { username, scores, .. } = item {
+/// 			println!("{}: {} more scores", username, scores.len());
+/// 		}
+/// 	})
+/// 	.await?;
+/// # Ok(()) }
+/// ```
+pub struct Crawler<'a> {
+	session: &'a Session,
+	utc: UtcClock<'a>,
+	store: Box<dyn CheckpointStore>,
+	page_size: u32,
+	politeness: Politeness,
+}
+
+impl<'a> Crawler<'a> {
+	pub fn new(session: &'a Session, store: impl CheckpointStore + 'static) -> Self {
+		Self {
+			session,
+			utc: UtcClock::new(session.clock()),
+			store: Box::new(store),
+			page_size: 100,
+			politeness: Politeness::default(),
+		}
+	}
+
+	pub fn set_politeness(&mut self, politeness: Politeness) {
+		self.politeness = politeness;
+	}
+
+	/// Sets how many leaderboard entries or scores are requested at once. Defaults to 100
+	///
+	/// Panics if the page size is zero
+	pub fn set_page_size(&mut self, page_size: u32) {
+		assert!(page_size > 0, "page size must not be zero");
+		self.page_size = page_size;
+	}
+
+	/// Returns the checkpoint from which [`Self::run`] would continue
+	pub fn checkpoint(&self) -> Checkpoint {
+		self.store.load().unwrap_or_default()
+	}
+
+	/// Crawls from the saved checkpoint until the end of the leaderboard or until the daily
+	/// request budget is used up, passing everything found to the handler.
+	///
+	/// If a request fails, the error is returned and the checkpoint stays at the failed page, so
+	/// calling this again retries it
+	pub async fn run(&mut self, mut handle: impl FnMut(CrawlItem)) -> Result<CrawlStatus, Error> {
+		let mut checkpoint = self.checkpoint();
+
+		loop {
+			let page_start = checkpoint.leaderboard_position / self.page_size * self.page_size;
+			let leaderboard = self
+				.politely(&mut checkpoint, || {
+					self.session.leaderboard(
+						page_start..page_start + self.page_size,
+						LeaderboardSortBy::Rating(etterna::Skillset8::Overall),
+						SortDirection::Descending,
+					)
+				})
+				.await?;
+			let leaderboard = match leaderboard {
+				Some(leaderboard) => leaderboard,
+				None => return Ok(CrawlStatus::BudgetExhausted),
+			};
+			let leaderboard_len = leaderboard.len() as u32;
+
+			let already_crawled = (checkpoint.leaderboard_position - page_start) as usize;
+			for entry in leaderboard.into_iter().skip(already_crawled) {
+				if let CrawlStatus::BudgetExhausted =
+					self.crawl_user(entry, &mut checkpoint, &mut handle).await?
+				{
+					return Ok(CrawlStatus::BudgetExhausted);
+				}
+				checkpoint.leaderboard_position += 1;
+				checkpoint.score_page = 0;
+				self.store.save(checkpoint);
+			}
+
+			if leaderboard_len < self.page_size {
+				return Ok(CrawlStatus::Finished);
+			}
+		}
+	}
+
+	/// Sends the request made by `request` while respecting the politeness settings. Returns
+	/// `None` without sending anything if the daily budget is used up.
+	///
+	/// `request` must make exactly one request, because that's what is charged to the budget
+	async fn politely<T, F: std::future::Future<Output = Result<T, Error>>>(
+		&self,
+		checkpoint: &mut Checkpoint,
+		request: impl FnOnce() -> F,
+	) -> Result<Option<T>, Error> {
+		let clock = self.session.clock();
+
+		while let Some(remaining) = self
+			.politeness
+			.pause_windows
+			.iter()
+			.find_map(|window| window.remaining(self.utc.time_of_day()))
+		{
+			clock.sleep_until(clock.now() + remaining).await;
+		}
+
+		let today = self.utc.day();
+		if checkpoint.budget_day != today {
+			checkpoint.budget_day = today;
+			checkpoint.requests_on_budget_day = 0;
+		}
+		if let Some(budget) = self.politeness.daily_request_budget {
+			if checkpoint.requests_on_budget_day >= budget {
+				return Ok(None);
+			}
+		}
+
+		let in_flight = match self.politeness.max_concurrent_requests {
+			Some(max) => Some(acquire_in_flight_slot(max).await),
+			None => None,
+		};
+
+		let result = request().await;
+		drop(in_flight);
+		checkpoint.requests_on_budget_day += 1;
+		self.store.save(*checkpoint);
+
+		clock
+			.sleep_until(clock.now() + self.politeness.page_delay)
+			.await;
+		result.map(Some)
+	}
+
+	async fn crawl_user(
+		&self,
+		entry: LeaderboardEntry,
+		checkpoint: &mut Checkpoint,
+		handle: &mut impl FnMut(CrawlItem),
+	) -> Result<CrawlStatus, Error> {
+		let username = entry.username.clone();
+		// Only go through politely() if the lookup actually makes a request
+		let user_id = match self.session.cached_user_id(&username) {
+			Some(user_id) => user_id,
+			None => match self
+				.politely(checkpoint, || self.session.user_id(&username))
+				.await?
+			{
+				Some(user_id) => user_id,
+				None => return Ok(CrawlStatus::BudgetExhausted),
+			},
+		};
+		if checkpoint.score_page == 0 {
+			handle(CrawlItem::User { entry, user_id });
+		}
+
+		loop {
+			let page_start = checkpoint.score_page * self.page_size;
+			let scores = self
+				.politely(checkpoint, || {
+					self.session.user_scores(
+						user_id,
+						page_start..page_start + self.page_size,
+						None,
+						UserScoresSortBy::Date,
+						SortDirection::Ascending,
+						true,
+					)
+				})
+				.await?;
+			let scores = match scores {
+				Some(scores) => scores.scores,
+				None => return Ok(CrawlStatus::BudgetExhausted),
+			};
+			let is_last_page = (scores.len() as u32) < self.page_size;
+
+			if !scores.is_empty() {
+				handle(CrawlItem::Scores {
+					username: username.clone(),
+					user_id,
+					scores,
+				});
+			}
+			checkpoint.score_page += 1;
+			self.store.save(*checkpoint);
+
+			if is_last_page {
+				return Ok(CrawlStatus::Finished);
+			}
+		}
+	}
+}
+
+/// The best scores of one player, found by [`TopScoresCrawl`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct TopPlayerScores {
+	pub entry: LeaderboardEntry,
+	pub user_id: u32,
+	/// The player's best valid scores by overall SSR, best first
+	pub scores: Vec<UserScore>,
+}
+
+/// Retrieves the top players of the web leaderboard and each of their best scores.
+///
+/// The crawl keeps everything it has fetched so far. If [`Self::run`] fails, e.g. because EO went
+/// down, calling it again continues with the player it failed on. With the `serde` feature, the
+/// crawl can be serialized to continue it after a restart.
+///
+/// # Example
+/// ```rust,no_run
+/// # async fn run() -> Result<(), etternaonline_api::Error> {
+/// use etternaonline_api::crawler::*;
+/// # let session: etternaonline_api::web::Session = unimplemented!();
+///
+/// let mut crawl = TopScoresCrawl::new(100, 10);
+/// crawl
+/// 	.run(&session, |done, total| println!("{}/{} players", done, total))
+/// 	.await?;
+/// for (player, score) in crawl.merged_scores().iter().take(10) {
+/// 	println!("{}: {}", player.entry.username, score.song_name);
+/// }
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+pub struct TopScoresCrawl {
+	num_players: u32,
+	num_scores: u32,
+	/// None until the leaderboard has been fetched
+	leaderboard: Option<Vec<LeaderboardEntry>>,
+	players: Vec<TopPlayerScores>,
+}
+
+impl TopScoresCrawl {
+	/// Prepares a crawl of the top `num_players` players and the top `num_scores` scores of each
+	///
+	/// Panics if either number is zero
+	pub fn new(num_players: u32, num_scores: u32) -> Self {
+		assert!(
+			num_players > 0 && num_scores > 0,
+			"number of players and scores must not be zero"
+		);
+		Self {
+			num_players,
+			num_scores,
+			leaderboard: None,
+			players: Vec::new(),
+		}
+	}
+
+	/// The players crawled so far, in leaderboard order
+	pub fn players(&self) -> &[TopPlayerScores] {
+		&self.players
+	}
+
+	pub fn into_players(self) -> Vec<TopPlayerScores> {
+		self.players
+	}
+
+	/// Whether every player has been crawled
+	pub fn is_finished(&self) -> bool {
+		self.leaderboard
+			.as_ref()
+			.is_some_and(|leaderboard| self.players.len() == leaderboard.len())
+	}
+
+	/// All scores crawled so far together with their player, sorted by overall SSR, best first
+	pub fn merged_scores(&self) -> Vec<(&TopPlayerScores, &UserScore)> {
+		let ssr = |score: &UserScore| {
+			score
+				.validity_dependant
+				.as_ref()
+				.map_or(0.0, |valid| valid.ssr.overall)
+		};
+
+		let mut scores = self
+			.players
+			.iter()
+			.flat_map(|player| player.scores.iter().map(move |score| (player, score)))
+			.collect::<Vec<_>>();
+		scores.sort_by(|(_, a), (_, b)| ssr(b).total_cmp(&ssr(a)));
+		scores
+	}
+
+	/// Crawls the remaining players. After each player, `on_progress` is called with the number
+	/// of players crawled so far and the total number of players
+	pub async fn run(
+		&mut self,
+		session: &Session,
+		mut on_progress: impl FnMut(usize, usize),
+	) -> Result<(), Error> {
+		let leaderboard = match &self.leaderboard {
+			Some(leaderboard) => leaderboard.clone(),
+			None => {
+				let leaderboard = session
+					.leaderboard(
+						0..self.num_players,
+						LeaderboardSortBy::Rating(etterna::Skillset8::Overall),
+						SortDirection::Descending,
+					)
+					.await?;
+				self.leaderboard = Some(leaderboard.clone());
+				leaderboard
+			}
+		};
+
+		for entry in leaderboard.into_iter().skip(self.players.len()) {
+			let user_id = session.user_id(&entry.username).await?;
+			let query = crate::web::UserScoresQuery::new(user_id)
+				.range(0..self.num_scores)
+				.sort_by(UserScoresSortBy::SsrOverall, SortDirection::Descending);
+			let scores = session.user_scores_query(&query).await?.scores;
+
+			self.players.push(TopPlayerScores {
+				entry,
+				user_id,
+				scores,
+			});
+			on_progress(self.players.len(), self.num_players_total());
+		}
+		Ok(())
+	}
+
+	fn num_players_total(&self) -> usize {
+		self.leaderboard
+			.as_ref()
+			.map_or(0, |leaderboard| leaderboard.len())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::future::Future;
+
+	use crate::clock::Clock;
+
+	use super::*;
+
+	#[test]
+	fn test_pause_window() {
+		let hours = |h: u64| std::time::Duration::from_secs(h * 60 * 60);
+
+		let daytime = PauseWindow::new(hours(8), hours(20));
+		assert_eq!(daytime.remaining(hours(7)), None);
+		assert_eq!(daytime.remaining(hours(8)), Some(hours(12)));
+		assert_eq!(daytime.remaining(hours(20)), None);
+
+		let nightly = PauseWindow::new(hours(22), hours(6));
+		assert_eq!(nightly.remaining(hours(23)), Some(hours(7)));
+		assert_eq!(nightly.remaining(hours(2)), Some(hours(4)));
+		assert_eq!(nightly.remaining(hours(12)), None);
+	}
+
+	#[test]
+	fn test_utc_clock_follows_session_clock() {
+		let clock = crate::clock::InstantClock::new();
+		let utc = UtcClock::new(&clock);
+		let start = utc.now();
+
+		// InstantClock advances when the sleep is created, no need to poll the future
+		drop(clock.sleep_until(clock.now() + DAY));
+		assert_eq!(utc.now() - start, DAY);
+		assert_eq!(utc.day(), start.as_secs() / DAY.as_secs() + 1);
+	}
+
+	#[test]
+	fn test_in_flight_slots() {
+		let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+		let mut first = Box::pin(acquire_in_flight_slot(1));
+		let mut second = Box::pin(acquire_in_flight_slot(1));
+
+		let first_guard = match first.as_mut().poll(&mut cx) {
+			std::task::Poll::Ready(guard) => guard,
+			std::task::Poll::Pending => panic!("first slot should be free"),
+		};
+		assert!(second.as_mut().poll(&mut cx).is_pending());
+		drop(first_guard);
+		assert!(second.as_mut().poll(&mut cx).is_ready());
+	}
+}
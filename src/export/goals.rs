@@ -0,0 +1,131 @@
+//! Backing up score goals to JSON or CSV files and reading them back, e.g. to restore them or to
+//! migrate them to another account with [`crate::v2::Session::import_user_goals`].
+//!
+//! The CSV format has a header line and the columns `chartkey`, `rate`, `wifescore` (as a
+//! proportion, like EO uses it), `time_assigned` and `time_achieved` (empty if the goal wasn't
+//! achieved yet).
+//!
+//! # Example
+//! ```rust,no_run
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! # use etternaonline_api::{export::goals, v2::*};
+//! # let session: Session = unimplemented!();
+//! let goals = session.user_goals("kangalioo").await?;
+//! goals::write_csv(&goals, std::fs::File::create("goals.csv")?)?;
+//!
+//! let goals = goals::read_csv(std::io::BufReader::new(std::fs::File::open("goals.csv")?))?;
+//! session.import_user_goals("kangalioo_alt", &goals).await?;
+//! # Ok(()) }
+//! ```
+
+use std::io::{BufRead, Write};
+
+use crate::v2::ScoreGoal;
+
+const CSV_HEADER: &str = "chartkey,rate,wifescore,time_assigned,time_achieved";
+
+/// Writes the goals as a pretty-printed JSON array
+pub fn write_json(goals: &[ScoreGoal], writer: impl Write) -> std::io::Result<()> {
+	serde_json::to_writer_pretty(writer, goals)?;
+	Ok(())
+}
+
+/// Reads goals written by [`write_json`]
+pub fn read_json(reader: impl std::io::Read) -> std::io::Result<Vec<ScoreGoal>> {
+	Ok(serde_json::from_reader(reader)?)
+}
+
+/// Writes the goals as CSV, one goal per line
+pub fn write_csv(goals: &[ScoreGoal], mut writer: impl Write) -> std::io::Result<()> {
+	writeln!(writer, "{}", CSV_HEADER)?;
+	for goal in goals {
+		writeln!(
+			writer,
+			"{},{},{},{},{}",
+			goal.chartkey,
+			goal.rate.as_f32(),
+			goal.wifescore.as_proportion(),
+			goal.time_assigned,
+			goal.time_achieved.as_deref().unwrap_or(""),
+		)?;
+	}
+	Ok(())
+}
+
+/// Reads goals written by [`write_csv`]. Empty lines are skipped
+///
+/// # Errors
+/// Besides I/O errors, an error of kind [`std::io::ErrorKind::InvalidData`] is returned if the
+/// header or a line is malformed
+pub fn read_csv(reader: impl BufRead) -> std::io::Result<Vec<ScoreGoal>> {
+	let mut lines = reader.lines();
+	match lines.next().transpose()? {
+		Some(header) if header.trim() == CSV_HEADER => {}
+		_ => return Err(invalid_data("missing or unexpected CSV header".to_owned())),
+	}
+
+	let mut goals = Vec::new();
+	for (i, line) in lines.enumerate() {
+		let line = line?;
+		if line.trim().is_empty() {
+			continue;
+		}
+		// The header is line 1
+		let goal = parse_csv_line(line.trim())
+			.ok_or_else(|| invalid_data(format!("malformed goal in line {}", i + 2)))?;
+		goals.push(goal);
+	}
+	Ok(goals)
+}
+
+fn parse_csv_line(line: &str) -> Option<ScoreGoal> {
+	let mut columns = line.split(',');
+	let chartkey = columns.next()?.parse().ok()?;
+	let rate = etterna::Rate::from_f32(columns.next()?.parse().ok()?)?;
+	let wifescore = etterna::Wifescore::from_proportion(columns.next()?.parse().ok()?)?;
+	let time_assigned = columns.next()?.to_owned();
+	let time_achieved = match columns.next()? {
+		"" => None,
+		time_achieved => Some(time_achieved.to_owned()),
+	};
+	if columns.next().is_some() {
+		return None;
+	}
+
+	let mut goal = ScoreGoal::new(chartkey, rate, wifescore, time_assigned);
+	goal.time_achieved = time_achieved;
+	Some(goal)
+}
+
+fn invalid_data(message: String) -> std::io::Error {
+	std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_csv_roundtrip() {
+		let mut achieved = ScoreGoal::new(
+			"X4a15f62b66a80b62ec64521704f98c6c03d98e03".parse().unwrap(),
+			etterna::Rate::from_f32(1.1).unwrap(),
+			etterna::Wifescore::from_proportion(0.93).unwrap(),
+			"2020-07-13 22:48:26".to_owned(),
+		);
+		achieved.time_achieved = Some("2020-07-20 18:00:00".to_owned());
+		let goals = vec![
+			achieved.clone(),
+			ScoreGoal {
+				time_achieved: None,
+				..achieved
+			},
+		];
+
+		let mut output = Vec::new();
+		write_csv(&goals, &mut output).unwrap();
+		assert_eq!(read_csv(&output[..]).unwrap(), goals);
+
+		assert!(read_csv("chartkey,rate\n".as_bytes()).is_err());
+	}
+}
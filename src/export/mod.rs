@@ -0,0 +1,6 @@
+//! Writing API results to files for archival
+//!
+//! Requires the `serde` feature.
+
+pub mod goals;
+pub mod ndjson;
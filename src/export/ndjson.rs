@@ -0,0 +1,117 @@
+//! Newline-delimited JSON (one JSON object per line) writers.
+//!
+//! Items are written as soon as they are passed in, so large datasets can be archived while
+//! they're being fetched without holding everything in memory.
+//!
+//! # Example
+//! ```rust,no_run
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! # use etternaonline_api::web::*;
+//! # let session: Session = unimplemented!();
+//! let file = std::fs::File::create("leaderboard.ndjson")?;
+//! let mut writer = etternaonline_api::export::ndjson::Writer::new(std::io::BufWriter::new(file));
+//! for page in 0..10 {
+//! 	let entries = session
+//! 		.leaderboard(
+//! 			page * 100..(page + 1) * 100,
+//! 			LeaderboardSortBy::Rating(etterna::Skillset8::Overall),
+//! 			SortDirection::Descending,
+//! 		)
+//! 		.await?;
+//! 	writer.write_all(&entries)?;
+//! }
+//! writer.flush()?;
+//! # Ok(()) }
+//! ```
+
+use std::io::Write;
+
+/// Writes serializable items as newline-delimited JSON to an [`std::io::Write`]
+#[derive(Debug)]
+pub struct Writer<W: Write> {
+	inner: W,
+	num_written: u64,
+}
+
+impl<W: Write> Writer<W> {
+	pub fn new(inner: W) -> Self {
+		Self {
+			inner,
+			num_written: 0,
+		}
+	}
+
+	/// Writes a single item as one line
+	pub fn write<T: serde::Serialize + ?Sized>(&mut self, item: &T) -> std::io::Result<()> {
+		serde_json::to_writer(&mut self.inner, item)?;
+		self.inner.write_all(b"\n")?;
+		self.num_written += 1;
+		Ok(())
+	}
+
+	/// Writes every item of the iterator as its own line
+	pub fn write_all<T: serde::Serialize>(
+		&mut self,
+		items: impl IntoIterator<Item = T>,
+	) -> std::io::Result<()> {
+		for item in items {
+			self.write(&item)?;
+		}
+		Ok(())
+	}
+
+	/// Number of items written so far
+	pub fn num_written(&self) -> u64 {
+		self.num_written
+	}
+
+	pub fn flush(&mut self) -> std::io::Result<()> {
+		self.inner.flush()
+	}
+
+	/// Returns the underlying writer. It is not flushed
+	pub fn into_inner(self) -> W {
+		self.inner
+	}
+}
+
+/// Reads back items written by [`Writer`], one per line. Empty lines are skipped
+pub fn read<T: serde::de::DeserializeOwned>(
+	reader: impl std::io::BufRead,
+) -> impl Iterator<Item = std::io::Result<T>> {
+	reader.lines().filter_map(|line| match line {
+		Ok(line) if line.trim().is_empty() => None,
+		Ok(line) => Some(serde_json::from_str(&line).map_err(std::io::Error::from)),
+		Err(e) => Some(Err(e)),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_roundtrip() {
+		let entries = vec![
+			crate::web::Country {
+				code: "DE".to_owned(),
+				name: "Germany".to_owned(),
+			},
+			crate::web::Country {
+				code: "JP".to_owned(),
+				name: "Japan".to_owned(),
+			},
+		];
+
+		let mut writer = Writer::new(Vec::new());
+		writer.write_all(&entries).unwrap();
+		assert_eq!(writer.num_written(), 2);
+		let output = writer.into_inner();
+		assert_eq!(output.iter().filter(|&&b| b == b'\n').count(), 2);
+
+		let read_back = read(&output[..])
+			.collect::<std::io::Result<Vec<crate::web::Country>>>()
+			.unwrap();
+		assert_eq!(read_back, entries);
+	}
+}
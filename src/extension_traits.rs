@@ -16,6 +16,12 @@ impl ExtractStr for &str {
 pub(crate) trait JsonValueExt: Sized {
 	fn get(&self) -> &serde_json::Value;
 
+	/// Where in the server response this value is located, if known. Only called to build error
+	/// messages, so it's fine for this to be expensive
+	fn path(&self) -> Option<String> {
+		None
+	}
+
 	fn attempt_get<'val, 'content: 'val, T: 'content>(
 		&'val self,
 		what_is_expected: &str,
@@ -24,7 +30,15 @@ pub(crate) trait JsonValueExt: Sized {
 		match action(self.get()) {
 			Some(result) => Ok(result),
 			None => Err(Error::InvalidDataStructure({
-				let mut msg = format!("Expected {}, found {}", what_is_expected, self.get());
+				let mut msg = match self.path() {
+					Some(path) => format!(
+						"Expected {} at {}, found {}",
+						what_is_expected,
+						path,
+						self.get()
+					),
+					None => format!("Expected {}, found {}", what_is_expected, self.get()),
+				};
 				if msg.len() > 500 {
 					msg.truncate(500);
 					msg += "...";
@@ -95,18 +109,12 @@ pub(crate) trait JsonValueExt: Sized {
 		self.attempt_get("u32", |j| Some(j.as_u64()?.try_into().ok()?))
 	}
 
-	fn f32_(&self) -> Result<f32, Error> {
-		self.attempt_get("f32", |j| Some(j.as_f64()? as f32))
+	fn f64_(&self) -> Result<f64, Error> {
+		self.attempt_get("f64", |j| j.as_f64())
 	}
 
-	fn singular_array_item(&self) -> Result<&serde_json::Value, Error> {
-		self.attempt_get("array with a single item", |j| {
-			let arr = j.as_array()?;
-			match arr.len() {
-				1 => Some(&arr[0]),
-				_ => None,
-			}
-		})
+	fn f32_(&self) -> Result<f32, Error> {
+		self.attempt_get("f32", |j| Some(j.as_f64()? as f32))
 	}
 
 	fn rate_float(&self) -> Result<etterna::Rate, Error> {
@@ -140,3 +148,165 @@ impl JsonValueExt for serde_json::Value {
 		self
 	} // self intensifies
 }
+
+/// A JSON value that remembers the path at which it was found inside the server response (e.g.
+/// `data[3].attributes.skillsets.Stream`), so that parse errors can point to the offending value
+#[derive(Clone, Copy)]
+pub(crate) struct Json<'a> {
+	value: &'a serde_json::Value,
+	path: JsonPath<'a>,
+}
+
+/// Where a [`Json`] value was found. Child values borrow their parent instead of storing the full
+/// path, which is only rendered when an error message needs it, because nearly all lookups succeed
+#[derive(Clone, Copy)]
+enum JsonPath<'a> {
+	Root(&'a str),
+	Key(&'a Json<'a>, &'a str),
+	Index(&'a Json<'a>, usize),
+}
+
+impl std::fmt::Display for JsonPath<'_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match *self {
+			Self::Root(name) => f.write_str(name),
+			Self::Key(parent, key) => match parent.path {
+				Self::Root("") => f.write_str(key),
+				parent => write!(f, "{}.{}", parent, key),
+			},
+			Self::Index(parent, index) => write!(f, "{}[{}]", parent.path, index),
+		}
+	}
+}
+
+impl<'a> Json<'a> {
+	/// `path` is the name of the root value, may be empty
+	pub fn new(value: &'a serde_json::Value, path: &'a str) -> Self {
+		Self {
+			value,
+			path: JsonPath::Root(path),
+		}
+	}
+
+	/// Retrieve an object field. Like indexing a [`serde_json::Value`], this yields null if the
+	/// field doesn't exist
+	pub fn at<'s>(&'s self, key: &'s str) -> Json<'s> {
+		Json {
+			value: &self.value[key],
+			path: JsonPath::Key(self, key),
+		}
+	}
+
+	/// Retrieve an array element. Like indexing a [`serde_json::Value`], this yields null if the
+	/// element doesn't exist
+	pub fn at_index(&self, index: usize) -> Json<'_> {
+		Json {
+			value: &self.value[index],
+			path: JsonPath::Index(self, index),
+		}
+	}
+
+	/// Iterate over the elements of this JSON array
+	pub fn elements(&self) -> Result<JsonElements<'_>, Error> {
+		Ok(JsonElements {
+			len: self.value.array()?.len(),
+			array: self,
+			index: 0,
+		})
+	}
+
+	pub fn singular_array_item(&self) -> Result<Json<'_>, Error> {
+		self.attempt_get("array with a single item", |j| match j.as_array()?.len() {
+			1 => Some(()),
+			_ => None,
+		})?;
+		Ok(self.at_index(0))
+	}
+
+	/// Collect all fields of this JSON object whose names are not in `known`. Yields an empty map
+	/// if this isn't an object
+	#[cfg(feature = "extra-fields")]
+	pub fn unknown_fields(
+		&self,
+		known: &[&str],
+	) -> std::collections::HashMap<String, serde_json::Value> {
+		match self.value.as_object() {
+			Some(object) => object
+				.iter()
+				.filter(|(key, _)| !known.contains(&key.as_str()))
+				.map(|(key, value)| (key.clone(), value.clone()))
+				.collect(),
+			None => std::collections::HashMap::new(),
+		}
+	}
+}
+
+impl std::ops::Deref for Json<'_> {
+	type Target = serde_json::Value;
+
+	fn deref(&self) -> &Self::Target {
+		self.value
+	}
+}
+
+impl JsonValueExt for Json<'_> {
+	fn get(&self) -> &serde_json::Value {
+		self.value
+	}
+
+	fn path(&self) -> Option<String> {
+		let path = self.path.to_string();
+		Some(if path.is_empty() {
+			"top level".to_owned()
+		} else {
+			path
+		})
+	}
+}
+
+/// Iterator over the elements of a JSON array, see [`Json::elements`]
+pub(crate) struct JsonElements<'a> {
+	array: &'a Json<'a>,
+	index: usize,
+	len: usize,
+}
+
+impl<'a> Iterator for JsonElements<'a> {
+	type Item = Json<'a>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.index >= self.len {
+			return None;
+		}
+		self.index += 1;
+		Some(self.array.at_index(self.index - 1))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_json_path() {
+		let value = serde_json::json!({ "data": [{ "attributes": { "rate": "fast" } }] });
+
+		let error = Json::new(&value, "")
+			.at("data")
+			.at_index(0)
+			.at("attributes")
+			.at("rate")
+			.f32_()
+			.unwrap_err();
+		assert!(error.to_string().contains("at data[0].attributes.rate,"));
+
+		let error = Json::new(&value["data"], "data")
+			.at_index(1)
+			.str_()
+			.unwrap_err();
+		assert!(error.to_string().contains("at data[1],"));
+
+		let error = Json::new(&value, "").str_().unwrap_err();
+		assert!(error.to_string().contains("at top level,"));
+	}
+}
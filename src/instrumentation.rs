@@ -0,0 +1,55 @@
+//! Reports requests to the [`metrics`](https://docs.rs/metrics) facade, if the `metrics` feature
+//! is enabled. Without it, all functions here do nothing.
+//!
+//! Metrics:
+//! - `etternaonline_api_requests_total` (counter), labeled with `endpoint` and `status`, which is
+//!   either `ok` or the [`Error`](crate::Error) variant name
+//! - `etternaonline_api_request_duration_seconds` (histogram), labeled with `endpoint`
+//! - `etternaonline_api_retries_total` (counter), labeled with `reason`
+//! - `etternaonline_api_cache_lookups_total` (counter), labeled with `cache` and `result`, which is
+//!   either `hit` or `miss`
+
+#[cfg(feature = "metrics")]
+pub(crate) fn request(endpoint: &str, error: Option<&crate::Error>, latency: std::time::Duration) {
+	let status = error.map_or("ok", |error| error.variant_name());
+	metrics::counter!(
+		"etternaonline_api_requests_total",
+		"endpoint" => endpoint.to_owned(),
+		"status" => status,
+	)
+	.increment(1);
+	metrics::histogram!(
+		"etternaonline_api_request_duration_seconds",
+		"endpoint" => endpoint.to_owned(),
+	)
+	.record(latency.as_secs_f64());
+}
+
+#[cfg(not(feature = "metrics"))]
+pub(crate) fn request(
+	_endpoint: &str,
+	_error: Option<&crate::Error>,
+	_latency: std::time::Duration,
+) {
+}
+
+#[cfg(feature = "metrics")]
+pub(crate) fn retry(reason: &'static str) {
+	metrics::counter!("etternaonline_api_retries_total", "reason" => reason).increment(1);
+}
+
+#[cfg(not(feature = "metrics"))]
+pub(crate) fn retry(_reason: &'static str) {}
+
+#[cfg(feature = "metrics")]
+pub(crate) fn cache_lookup(cache: &'static str, hit: bool) {
+	metrics::counter!(
+		"etternaonline_api_cache_lookups_total",
+		"cache" => cache,
+		"result" => if hit { "hit" } else { "miss" },
+	)
+	.increment(1);
+}
+
+#[cfg(not(feature = "metrics"))]
+pub(crate) fn cache_lookup(_cache: &'static str, _hit: bool) {}
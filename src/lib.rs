@@ -6,6 +6,11 @@
 	clippy::too_many_arguments,
 	clippy::needless_question_mark, // thats just what we gotta do for lack of try blocks
 )]
+// Helpers shared between the API modules are partly unused if some of the APIs are disabled
+#![cfg_attr(
+	not(all(feature = "v1", feature = "v2", feature = "web")),
+	allow(dead_code)
+)]
 
 /*!
 This crate provides an ergonomic wrapper around the v1, v2 and web API of
@@ -21,18 +26,58 @@ Etterna terminology:
 
 # Usage
 For detailed usage documentation, see [`v1::Session`] and [`v2::Session`]
+
+# Features
+Each API's `Session` lives behind a cargo feature of the same name: `v1`, `v2` and `web`. All of
+them are enabled by default; if you only need one, disable the default features to save compile
+time. The structs are always available. With only the `parsing-only` feature, neither reqwest
+nor tokio are compiled, which suits tools that work with archived EO data offline.
+
+With the `metrics` feature, request counts, latencies, retries and cache hits are reported to the
+[`metrics`](https://docs.rs/metrics) facade, so any installed recorder (e.g. a Prometheus
+exporter) picks them up.
+
+The `blocking` feature adds blocking wrappers around the v1 and web sessions, in `v1::blocking`
+and `web::blocking`, for programs that don't run an async runtime.
 */
 
-#[cfg(feature = "serde")]
+#[cfg(any(feature = "serde", feature = "streaming"))]
 extern crate serde_ as serde;
 
+pub mod analysis;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(all(feature = "blocking", any(feature = "v1", feature = "web")))]
+#[macro_use]
+mod blocking;
+pub mod clock;
+#[cfg(feature = "web")]
+pub mod crawler;
+#[cfg(feature = "serde")]
+pub mod export;
 mod extension_traits;
+mod instrumentation;
 #[macro_use]
 mod common;
 pub use common::structs::*;
+pub use common::{parse_replay, parse_replay_strict};
+#[cfg(feature = "plot")]
+pub mod plot;
+pub mod pool;
+#[cfg(feature = "python")]
+mod python;
+pub mod rate_limit;
+pub mod rating;
+pub mod scores;
+pub mod session;
+#[cfg(feature = "v2")]
+pub mod tournament;
 pub mod v1;
 pub mod v2;
 pub mod web;
+pub mod wife;
+#[cfg(feature = "worker")]
+pub mod worker;
 
 #[doc(hidden)]
 #[macro_export]
@@ -55,8 +100,11 @@ fn _assert_send_future() {
 	}
 	fn assert_send<T: Send>(_: T) {}
 
+	#[cfg(feature = "v1")]
 	assert_send(v1::Session::user_data(dummy(), dummy()));
+	#[cfg(feature = "v2")]
 	assert_send(v2::Session::user_details(dummy(), dummy()));
+	#[cfg(feature = "web")]
 	assert_send(web::Session::user_details(dummy(), dummy()));
 }
 
@@ -64,28 +112,116 @@ fn _assert_send_future() {
 #[non_exhaustive]
 pub enum Error {
 	// Client errors
-	UserNotFound { name: Option<String> },
+	UserNotFound {
+		name: Option<String>,
+	},
 	InvalidLogin,
+	CredentialsRejected {
+		attempts: u32,
+	},
 	ScoreNotFound,
+	InvalidScorekey {
+		scorekey: String,
+	},
 	SongNotFound,
 	ChartNotTracked,
+	InvalidChartkey {
+		chartkey: String,
+	},
 	ChartAlreadyFavorited,
 	DatabaseError,
 	GoalAlreadyExists,
 	ChartAlreadyAdded,
 	InvalidXml,
 	NoUsersFound,
+	NoSessionsLeft,
+	WorkerStopped,
+	/// The API behind this session doesn't provide the requested data, see
+	/// [`session::EoSession`]
+	Unsupported {
+		operation: &'static str,
+	},
 
 	// External errors
+	#[cfg(feature = "reqwest")]
 	Http(reqwest::Error),
 	NetworkError(std::io::Error),
-	InternalServerError { status_code: u16 },
+	InternalServerError {
+		status_code: u16,
+	},
+	CloudflareChallenge {
+		status_code: u16,
+	},
 	InvalidJson(serde_json::Error),
 	UnknownApiError(String),
 	InvalidDataStructure(String),
 	EmptyServerResponse,
 }
 
+impl Error {
+	/// Name of the enum variant, used for statistics and serialization
+	fn variant_name(&self) -> &'static str {
+		match self {
+			Self::UserNotFound { .. } => "UserNotFound",
+			Self::InvalidLogin => "InvalidLogin",
+			Self::CredentialsRejected { .. } => "CredentialsRejected",
+			Self::ScoreNotFound => "ScoreNotFound",
+			Self::InvalidScorekey { .. } => "InvalidScorekey",
+			Self::SongNotFound => "SongNotFound",
+			Self::ChartNotTracked => "ChartNotTracked",
+			Self::InvalidChartkey { .. } => "InvalidChartkey",
+			Self::ChartAlreadyFavorited => "ChartAlreadyFavorited",
+			Self::DatabaseError => "DatabaseError",
+			Self::GoalAlreadyExists => "GoalAlreadyExists",
+			Self::ChartAlreadyAdded => "ChartAlreadyAdded",
+			Self::InvalidXml => "InvalidXml",
+			Self::NoUsersFound => "NoUsersFound",
+			Self::NoSessionsLeft => "NoSessionsLeft",
+			Self::WorkerStopped => "WorkerStopped",
+			Self::Unsupported { .. } => "Unsupported",
+			#[cfg(feature = "reqwest")]
+			Self::Http(_) => "Http",
+			Self::NetworkError(_) => "NetworkError",
+			Self::InternalServerError { .. } => "InternalServerError",
+			Self::CloudflareChallenge { .. } => "CloudflareChallenge",
+			Self::InvalidJson(_) => "InvalidJson",
+			Self::UnknownApiError(_) => "UnknownApiError",
+			Self::InvalidDataStructure(_) => "InvalidDataStructure",
+			Self::EmptyServerResponse => "EmptyServerResponse",
+		}
+	}
+
+	/// A hint on how to resolve the error, for showing to end users of tools built on this crate
+	pub fn help(&self) -> Option<&'static str> {
+		Some(match self {
+			Self::InvalidLogin => "Check the username and password",
+			Self::CredentialsRejected { .. } => {
+				"The password was probably changed. Update the credentials and call \
+				Session::reset_login_failures"
+			}
+			Self::InternalServerError { .. } | Self::EmptyServerResponse => {
+				"EtternaOnline may be having problems, try again later"
+			}
+			Self::CloudflareChallenge { .. } => {
+				"EtternaOnline is blocking automated requests right now, try again later or \
+				increase the rate limit cooldown"
+			}
+			Self::InvalidJson(_) | Self::UnknownApiError(_) | Self::InvalidDataStructure(_) => {
+				"EtternaOnline's API may have changed, consider updating this crate"
+			}
+			Self::NoSessionsLeft => "Add sessions with working credentials to the pool",
+			Self::Unsupported { .. } => "Use a session of another EO API for this request",
+			Self::InvalidScorekey { .. } => {
+				"Scorekeys consist of an S followed by 40 lowercase hexadecimal digits"
+			}
+			Self::InvalidChartkey { .. } => {
+				"Chartkeys consist of an X followed by 40 lowercase hexadecimal digits"
+			}
+			_ => return None,
+		})
+	}
+}
+
 impl std::fmt::Display for Error {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
@@ -93,17 +229,34 @@ impl std::fmt::Display for Error {
 			Self::UserNotFound { name: Some(name) } => write!(f, "User '{}' not found", name),
 			Self::UserNotFound { name: None } => write!(f, "User not found"),
 			Self::InvalidLogin => write!(f, "Username and password combination not found"),
+			Self::CredentialsRejected { attempts } => write!(
+				f,
+				"Login was rejected {} times in a row, not trying again",
+				attempts
+			),
 			Self::ScoreNotFound => write!(f, "Score not found"),
+			Self::InvalidScorekey { scorekey } => {
+				write!(f, "'{}' is not a valid scorekey", scorekey)
+			}
 			Self::SongNotFound => write!(f, "Song not found"),
 			Self::ChartNotTracked => write!(f, "Chart not tracked"),
+			Self::InvalidChartkey { chartkey } => {
+				write!(f, "'{}' is not a valid chartkey", chartkey)
+			}
 			Self::ChartAlreadyFavorited => write!(f, "Favorite already exists"),
 			Self::DatabaseError => write!(f, "Database error"),
 			Self::GoalAlreadyExists => write!(f, "Goal already exists"),
 			Self::ChartAlreadyAdded => write!(f, "Chart already exists"),
 			Self::InvalidXml => write!(f, "The uploaded file is not a valid XML file"),
 			Self::NoUsersFound => write!(f, "No users registered"),
+			Self::NoSessionsLeft => write!(f, "All sessions in the pool have been retired"),
+			Self::WorkerStopped => write!(f, "The worker task has stopped"),
+			Self::Unsupported { operation } => {
+				write!(f, "This API doesn't support {}", operation)
+			}
 
 			// External errors
+			#[cfg(feature = "reqwest")]
 			Self::Http(e) => write!(f, "HTTP error: {}", e),
 			Self::NetworkError(e) => write!(f, "General network error: {}", e),
 			Self::InternalServerError { status_code } => write!(
@@ -111,6 +264,11 @@ impl std::fmt::Display for Error {
 				"Internal EtternaOnline server error (HTTP {})",
 				status_code
 			),
+			Self::CloudflareChallenge { status_code } => write!(
+				f,
+				"EtternaOnline responded with a Cloudflare challenge page (HTTP {})",
+				status_code
+			),
 			Self::InvalidJson(e) => {
 				write!(f, "Error while parsing the json sent by the server ({})", e)
 			}
@@ -129,6 +287,7 @@ impl std::fmt::Display for Error {
 	}
 }
 
+#[cfg(feature = "reqwest")]
 impl From<reqwest::Error> for Error {
 	fn from(mut e: reqwest::Error) -> Self {
 		e.delete_url(); // let's not leak API keys
@@ -149,11 +308,12 @@ macro_rules! error_from {
 }
 
 macro_rules! error_source {
-	($($variant:ident),* $(,)?) => {
+	($($(#[$attr:meta])* $variant:ident),* $(,)?) => {
 		impl std::error::Error for Error {
 			fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
 				match self {
 					$(
+						$(#[$attr])*
 						Self::$variant(e) => Some(e),
 					)*
 					_ => None,
@@ -168,11 +328,212 @@ error_from! {
 	InvalidJson(serde_json::Error),
 }
 error_source! {
+	#[cfg(feature = "reqwest")]
 	Http,
 	NetworkError,
 	InvalidJson,
 }
 
+#[cfg(feature = "diagnostics")]
+impl miette::Diagnostic for Error {
+	fn code<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+		Some(Box::new(format!(
+			"etternaonline_api::{}",
+			self.variant_name()
+		)))
+	}
+
+	fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+		Some(Box::new(Error::help(self)?))
+	}
+}
+
+/// Serializes the error for structured logging, as an object with the following fields:
+/// - `kind`: the name of the enum variant, e.g. `"UserNotFound"`. This is stable across crate
+///   versions, so it can be used to aggregate errors
+/// - `message`: the human-readable error message
+/// - `details`: the variant's fields as an object (e.g. `{"status_code": 503}`), or null
+#[cfg(feature = "serde")]
+impl serde::Serialize for Error {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		use serde::ser::SerializeStruct as _;
+
+		let details = match self {
+			Self::UserNotFound { name } => Some(serde_json::json!({ "name": name })),
+			Self::InvalidScorekey { scorekey } => Some(serde_json::json!({ "scorekey": scorekey })),
+			Self::InvalidChartkey { chartkey } => Some(serde_json::json!({ "chartkey": chartkey })),
+			Self::Unsupported { operation } => Some(serde_json::json!({ "operation": operation })),
+			Self::CredentialsRejected { attempts } => {
+				Some(serde_json::json!({ "attempts": attempts }))
+			}
+			Self::InternalServerError { status_code }
+			| Self::CloudflareChallenge { status_code } => {
+				Some(serde_json::json!({ "status_code": status_code }))
+			}
+			_ => None,
+		};
+
+		let mut state = serializer.serialize_struct("Error", 3)?;
+		state.serialize_field("kind", self.variant_name())?;
+		state.serialize_field("message", &self.to_string())?;
+		state.serialize_field("details", &details)?;
+		state.end()
+	}
+}
+
+/// A non-fatal problem that was encountered while parsing a server response, for example a
+/// malformed replay or - in lenient mode - a list entry that had to be skipped.
+///
+/// See `Session::set_parse_warning_callback` on the respective session types
+#[derive(Debug)]
+pub struct ParseWarning {
+	/// The endpoint or data kind that was being parsed
+	pub endpoint: String,
+	/// The error that would have been returned if parsing wasn't lenient
+	pub error: Error,
+}
+
+impl std::fmt::Display for ParseWarning {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "Failed to parse {}: {}", self.endpoint, self.error)
+	}
+}
+
+/// Decides how strictly server responses are parsed and where parse warnings go. Every session
+/// type has one of these
+#[derive(Default)]
+struct ParseSettings {
+	lenient: bool,
+	keep_raw_replays: bool,
+	warning_callback: Option<Box<dyn Fn(ParseWarning) + Send + Sync>>,
+}
+
+impl ParseSettings {
+	fn warn(&self, endpoint: &str, error: Error) {
+		log::warn!("failed to parse {}: {}", endpoint, error);
+		if let Some(warning_callback) = &self.warning_callback {
+			warning_callback(ParseWarning {
+				endpoint: endpoint.to_owned(),
+				error,
+			});
+		}
+	}
+
+	/// Collects a list of parsed entries. In lenient mode, entries that failed to parse are
+	/// skipped and reported as warnings; otherwise the first error is returned
+	fn collect<T>(
+		&self,
+		endpoint: &str,
+		entries: impl IntoIterator<Item = Result<T, Error>>,
+	) -> Result<Vec<T>, Error> {
+		if !self.lenient {
+			return entries.into_iter().collect();
+		}
+
+		Ok(entries
+			.into_iter()
+			.filter_map(|entry| match entry {
+				Ok(entry) => Some(entry),
+				Err(e) => {
+					self.warn(endpoint, e);
+					None
+				}
+			})
+			.collect())
+	}
+}
+
+/// Request statistics of a session, see e.g. [`v2::Session::stats`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestStats {
+	/// Number of requests sent to EO, including failed ones
+	pub total_requests: u64,
+	/// Number of requests per endpoint. Variable path segments like usernames or chartkeys are
+	/// replaced by placeholders, e.g. `user/{user}/latest`
+	pub requests_per_endpoint: std::collections::HashMap<String, u64>,
+	/// Number of failed requests per [`Error`] variant name, e.g. `UserNotFound`
+	pub errors_per_variant: std::collections::HashMap<String, u64>,
+	/// Summed up latency of all requests, excluding time spent waiting for the rate limit
+	pub total_latency: std::time::Duration,
+	/// When the last successful request finished
+	pub last_success: Option<std::time::Instant>,
+}
+
+impl RequestStats {
+	/// Average latency of all requests so far, or None if there were no requests yet
+	pub fn average_latency(&self) -> Option<std::time::Duration> {
+		if self.total_requests == 0 {
+			return None;
+		}
+		Some(self.total_latency / self.total_requests as u32)
+	}
+}
+
+/// Collects [`RequestStats`]. Every session type has one of these
+#[derive(Default)]
+struct StatsRecorder {
+	stats: std::sync::Mutex<RequestStats>,
+}
+
+impl StatsRecorder {
+	/// Turns a request path into the endpoint it belongs to, e.g. `user/kangalioo/top/Stream/10`
+	/// into `user/{user}/top/Stream/{id}`. Query parameters like pagination are dropped
+	fn endpoint(path: &str, user: Option<&str>) -> String {
+		let path = match path.find('?') {
+			Some(query_start) => &path[..query_start],
+			None => path,
+		};
+		path.split('/')
+			.map(|segment| {
+				if Some(segment) == user {
+					"{user}"
+				} else if etterna::Chartkey::is_valid(segment) {
+					"{chartkey}"
+				} else if etterna::Scorekey::is_valid(segment) {
+					"{scorekey}"
+				} else if segment.parse::<u64>().is_ok() {
+					"{id}"
+				} else {
+					segment
+				}
+			})
+			.collect::<Vec<_>>()
+			.join("/")
+	}
+
+	fn record(
+		&self,
+		path: &str,
+		user: Option<&str>,
+		latency: std::time::Duration,
+		error: Option<&Error>,
+	) {
+		let endpoint = Self::endpoint(path, user);
+
+		instrumentation::request(&endpoint, error, latency);
+
+		// UNWRAP: propagate panics
+		let mut stats = self.stats.lock().unwrap();
+		stats.total_requests += 1;
+		*stats.requests_per_endpoint.entry(endpoint).or_insert(0) += 1;
+		stats.total_latency += latency;
+		match error {
+			Some(error) => {
+				*stats
+					.errors_per_variant
+					.entry(error.variant_name().to_owned())
+					.or_insert(0) += 1
+			}
+			None => stats.last_success = Some(std::time::Instant::now()),
+		}
+	}
+
+	fn get(&self) -> RequestStats {
+		// UNWRAP: propagate panics
+		self.stats.lock().unwrap().clone()
+	}
+}
+
 /// Contains context about the request which is used in error messages
 #[derive(Default, Debug)]
 struct RequestContext<'a> {
@@ -180,17 +541,28 @@ struct RequestContext<'a> {
 	// TODO: add chartkey, scorekey, maybe country code? (if the need for better error messages arises)
 }
 
-fn rate_limit(
-	mut last_request: std::sync::MutexGuard<'_, std::time::Instant>,
-	request_cooldown: std::time::Duration,
-) -> impl std::future::Future<Output = ()> + Send + Sync {
-	let earliest_allowed_next_request = *last_request + request_cooldown;
-	let wake_up_time = Ord::max(std::time::Instant::now(), earliest_allowed_next_request);
+/// Cloudflare sometimes intercepts requests to EO and serves a JavaScript challenge page instead
+/// of the actual response. Those pages come with a 403 or 503 status code
+#[cfg(feature = "reqwest")]
+fn is_cloudflare_challenge(
+	status: reqwest::StatusCode,
+	headers: &reqwest::header::HeaderMap,
+	body: &str,
+) -> bool {
+	if status != 403 && status != 503 {
+		return false;
+	}
 
-	// Assign the "last" request time before sleeping so that incoming requests while we're sleeping
-	// incorporate our soon-to-be request into their rate limiting
-	*last_request = wake_up_time;
-	tokio::time::sleep_until(wake_up_time.into())
+	let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+
+	if header("cf-mitigated") == Some("challenge") {
+		return true;
+	}
+
+	header("server") == Some("cloudflare")
+		&& ["cf-chl", "challenge-platform", "Just a moment..."]
+			.iter()
+			.any(|marker| body.contains(marker))
 }
 
 /// This only works with 4k replays at the moment! All notes beyond the first four columns are
@@ -228,3 +600,217 @@ where
 		judge,
 	))
 }
+
+/// One of Etterna's nine standard judges, e.g. to offer "rescore to J5" without dealing with
+/// timing windows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+pub enum JudgePreset {
+	J1,
+	J2,
+	J3,
+	J4,
+	J5,
+	J6,
+	J7,
+	J8,
+	J9,
+}
+
+impl JudgePreset {
+	/// Looks up the judge by its number, e.g. `5` for J5. Returns None if not in 1..=9
+	pub fn from_number(number: u8) -> Option<Self> {
+		Some(match number {
+			1 => Self::J1,
+			2 => Self::J2,
+			3 => Self::J3,
+			4 => Self::J4,
+			5 => Self::J5,
+			6 => Self::J6,
+			7 => Self::J7,
+			8 => Self::J8,
+			9 => Self::J9,
+			_ => return None,
+		})
+	}
+
+	/// The judge number, e.g. `5` for J5
+	pub fn number(self) -> u8 {
+		self as u8 + 1
+	}
+
+	/// The timing windows of this judge
+	pub fn judge(self) -> &'static etterna::Judge {
+		match self {
+			Self::J1 => etterna::J1,
+			Self::J2 => etterna::J2,
+			Self::J3 => etterna::J3,
+			Self::J4 => etterna::J4,
+			Self::J5 => etterna::J5,
+			Self::J6 => etterna::J6,
+			Self::J7 => etterna::J7,
+			Self::J8 => etterna::J8,
+			Self::J9 => etterna::J9,
+		}
+	}
+}
+
+impl From<JudgePreset> for &'static etterna::Judge {
+	fn from(preset: JudgePreset) -> Self {
+		preset.judge()
+	}
+}
+
+impl std::fmt::Display for JudgePreset {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "J{}", self.number())
+	}
+}
+
+/// Like [`rescore`], but with one of the standard judges
+///
+/// # Example
+/// ```rust,no_run
+/// # let replay: etternaonline_api::Replay = unimplemented!();
+/// use etternaonline_api::{rescore_with_judge_preset, JudgePreset};
+///
+/// let wifescore = rescore_with_judge_preset::<etterna::MatchingScorer, etterna::Wife3>(
+/// 	&replay,
+/// 	0,
+/// 	0,
+/// 	JudgePreset::J5,
+/// );
+/// ```
+pub fn rescore_with_judge_preset<S, W>(
+	replay: &Replay,
+	num_hit_mines: u32,
+	num_dropped_holds: u32,
+	preset: JudgePreset,
+) -> Option<etterna::Wifescore>
+where
+	S: etterna::ScoringSystem,
+	W: etterna::Wife,
+{
+	rescore::<S, W>(replay, num_hit_mines, num_dropped_holds, preset.judge())
+}
+
+/// Rescores many replays at once. Each entry consists of the replay, the number of hit mines and
+/// the number of dropped holds, like the parameters to [`rescore`].
+///
+/// With the `parallel` feature, the replays are spread over all available CPU cores. Otherwise,
+/// they're rescored one after another on the calling thread.
+///
+/// The results are returned in the same order as the input.
+///
+/// Panics if any replay contains NaN
+pub fn rescore_many<S, W>(
+	scores: &[(&Replay, u32, u32)],
+	judge: &etterna::Judge,
+) -> Vec<Option<etterna::Wifescore>>
+where
+	S: etterna::ScoringSystem,
+	W: etterna::Wife,
+{
+	let rescore_chunk = |chunk: &[(&Replay, u32, u32)]| {
+		chunk
+			.iter()
+			.map(|&(replay, num_hit_mines, num_dropped_holds)| {
+				rescore::<S, W>(replay, num_hit_mines, num_dropped_holds, judge)
+			})
+			.collect::<Vec<_>>()
+	};
+
+	let num_threads = if cfg!(feature = "parallel") {
+		std::thread::available_parallelism().map_or(1, |n| n.get())
+	} else {
+		1
+	};
+	if num_threads == 1 || scores.len() <= 1 {
+		return rescore_chunk(scores);
+	}
+	let chunk_size = scores.len().div_ceil(num_threads);
+
+	std::thread::scope(|scope| {
+		let threads = scores
+			.chunks(chunk_size)
+			.map(|chunk| scope.spawn(move || rescore_chunk(chunk)))
+			.collect::<Vec<_>>();
+
+		threads
+			.into_iter()
+			// UNWRAP: propagate panics
+			.flat_map(|thread| thread.join().unwrap())
+			.collect()
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_stats_endpoint() {
+		assert_eq!(
+			StatsRecorder::endpoint("user/kangalioo/top/Stream/10", Some("kangalioo")),
+			"user/{user}/top/Stream/{id}",
+		);
+		assert_eq!(
+			StatsRecorder::endpoint("score/S65565b5bc377c6d78b60c0aecfd9e05955b4cf63", None),
+			"score/{scorekey}",
+		);
+		assert_eq!(
+			StatsRecorder::endpoint("leaderboard/DE?page[offset]=100&page[limit]=100", None),
+			"leaderboard/DE",
+		);
+		assert_eq!(
+			StatsRecorder::endpoint("user/kangalioo?username=kangalioo", Some("kangalioo")),
+			"user/{user}",
+		);
+	}
+
+	#[test]
+	fn test_rescore_many_keeps_order() {
+		let note = |hit| ReplayNote {
+			time: 0.0,
+			hit,
+			lane: Some(0),
+			note_type: Some(etterna::NoteType::Tap),
+			tick: None,
+		};
+		// Replay i has i misses, so every replay rescores differently
+		let replays = (0..16)
+			.map(|num_misses| Replay {
+				notes: (0..16)
+					.map(|i| {
+						if i < num_misses {
+							note(etterna::Hit::Miss)
+						} else {
+							note(etterna::Hit::Hit { deviation: 0.0 })
+						}
+					})
+					.collect(),
+				raw: None,
+			})
+			.collect::<Vec<_>>();
+		let scores = replays
+			.iter()
+			.map(|replay| (replay, 0, 0))
+			.collect::<Vec<_>>();
+
+		let expected = replays
+			.iter()
+			.map(|replay| {
+				rescore::<etterna::MatchingScorer, etterna::Wife3>(replay, 0, 0, etterna::J4)
+			})
+			.collect::<Vec<_>>();
+		assert!(expected.windows(2).all(|pair| pair[0] > pair[1]));
+		assert_eq!(
+			rescore_many::<etterna::MatchingScorer, etterna::Wife3>(&scores, etterna::J4),
+			expected
+		);
+	}
+}
@@ -0,0 +1,155 @@
+//! Rendering replays as SVG offset plots, like the one on Etterna's evaluation screen.
+//!
+//! Requires the `plot` feature. The output is a standalone SVG document, which can be attached
+//! to a message directly or rasterized with any SVG renderer.
+//!
+//! # Example
+//! ```rust,no_run
+//! # let replay: etternaonline_api::Replay = unimplemented!();
+//! use etternaonline_api::plot::OffsetPlot;
+//!
+//! let svg = OffsetPlot::from_replay(&replay).size(800, 300).render();
+//! std::fs::write("plot.svg", svg).unwrap();
+//! ```
+
+use std::fmt::Write as _;
+
+use crate::Replay;
+
+const BACKGROUND_COLOR: &str = "#111111";
+const WINDOW_LINE_COLOR: &str = "#444444";
+const CENTER_LINE_COLOR: &str = "#888888";
+
+/// Builder for an SVG offset plot: one dot per note, placed horizontally by time and vertically
+/// by hit deviation, colored by judgement. Misses are drawn as faint vertical lines.
+#[derive(Clone)]
+pub struct OffsetPlot<'a> {
+	hits: Vec<(f32, etterna::Hit)>,
+	judge: &'a etterna::Judge,
+	width: u32,
+	height: u32,
+}
+
+impl<'a> OffsetPlot<'a> {
+	/// Creates a plot from `(note time in seconds, hit)` pairs
+	pub fn new(hits: impl IntoIterator<Item = (f32, etterna::Hit)>) -> Self {
+		Self {
+			hits: hits.into_iter().collect(),
+			judge: etterna::J4,
+			width: 600,
+			height: 250,
+		}
+	}
+
+	/// Creates a plot of the replay's taps and hold heads. If the replay has no note type
+	/// information, all notes are plotted
+	pub fn from_replay(replay: &Replay) -> Self {
+		let taps = replay
+			.iter_taps()
+			.map(|taps| taps.collect::<Vec<_>>())
+			.unwrap_or_else(|| replay.notes.iter().collect());
+		Self::new(taps.into_iter().map(|note| (note.time, note.hit)))
+	}
+
+	/// The judge used to color the dots and draw the judgement windows. Defaults to J4
+	pub fn judge(mut self, judge: &'a etterna::Judge) -> Self {
+		self.judge = judge;
+		self
+	}
+
+	/// Size of the image in pixels. Defaults to 600x250
+	pub fn size(mut self, width: u32, height: u32) -> Self {
+		self.width = width;
+		self.height = height;
+		self
+	}
+
+	/// Renders the plot as an SVG document
+	pub fn render(&self) -> String {
+		let (width, height) = (self.width as f32, self.height as f32);
+		let max_deviation = self.judge.bad_window;
+		let (start, end) = self.hits.iter().fold(
+			(f32::INFINITY, f32::NEG_INFINITY),
+			|(start, end), &(time, _)| (start.min(time), end.max(time)),
+		);
+		let duration = (end - start).max(f32::EPSILON);
+
+		let x = |time: f32| (time - start) / duration * width;
+		// Late hits are at the top, like in-game
+		let y = |deviation: f32| {
+			let deviation = deviation.clamp(-max_deviation, max_deviation);
+			height / 2.0 - deviation / max_deviation * (height / 2.0)
+		};
+
+		let mut svg = String::new();
+		// UNWRAP: writing into a String can't fail
+		write!(
+			svg,
+			r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+			w = self.width,
+			h = self.height,
+		)
+		.unwrap();
+		write!(
+			svg,
+			r#"<rect width="100%" height="100%" fill="{}"/>"#,
+			BACKGROUND_COLOR
+		)
+		.unwrap();
+
+		for window in [
+			self.judge.marvelous_window,
+			self.judge.perfect_window,
+			self.judge.great_window,
+			self.judge.good_window,
+		] {
+			for deviation in [-window, window] {
+				write!(
+					svg,
+					r#"<line x1="0" x2="{}" y1="{y}" y2="{y}" stroke="{}" stroke-width="1"/>"#,
+					width,
+					WINDOW_LINE_COLOR,
+					y = y(deviation),
+				)
+				.unwrap();
+			}
+		}
+		write!(
+			svg,
+			r#"<line x1="0" x2="{}" y1="{y}" y2="{y}" stroke="{}" stroke-width="1"/>"#,
+			width,
+			CENTER_LINE_COLOR,
+			y = height / 2.0,
+		)
+		.unwrap();
+
+		let miss_color = color_hex(etterna::TapJudgement::Miss);
+		for &(time, hit) in &self.hits {
+			match hit {
+				etterna::Hit::Hit { deviation } => write!(
+					svg,
+					r#"<circle cx="{:.1}" cy="{:.1}" r="1.5" fill="{}"/>"#,
+					x(time),
+					y(deviation),
+					color_hex(self.judge.classify(deviation)),
+				),
+				etterna::Hit::Miss => write!(
+					svg,
+					r#"<line x1="{x:.1}" x2="{x:.1}" y1="0" y2="{}" stroke="{}" stroke-opacity="0.4" stroke-width="1"/>"#,
+					height,
+					miss_color,
+					x = x(time),
+				),
+			}
+			.unwrap();
+		}
+
+		svg.push_str("</svg>");
+		svg
+	}
+}
+
+fn color_hex(judgement: etterna::TapJudgement) -> String {
+	let (r, g, b) = judgement.color();
+	format!("#{:02X}{:02X}{:02X}", r, g, b)
+}
@@ -0,0 +1,166 @@
+//! Spreading requests over multiple sessions, for large-scale data collection with several API
+//! keys or accounts
+
+use crate::Error;
+
+/// A session type that can be used in a [`SessionPool`]
+pub trait PooledSession: Send + Sync {
+	/// How long a request sent now would have to wait for the session's rate limit
+	fn time_until_next_request(&self) -> std::time::Duration;
+}
+
+/// Holds several sessions, e.g. with different API keys or accounts, and routes each request to
+/// the session whose rate limit allows it to send the request soonest.
+///
+/// If a request fails because the session's login was rejected, the session is retired from the
+/// pool and the request is retried with another session. It's not known how EO responds to a
+/// revoked v1 API key, so v1 sessions are never retired.
+///
+/// # Example
+/// ```rust,no_run
+/// # async fn f() -> Result<(), etternaonline_api::Error> {
+/// # use etternaonline_api::{pool::SessionPool, v1};
+/// let pool = SessionPool::new(vec![
+/// 	v1::Session::new("first key".into(), std::time::Duration::from_secs(1), None),
+/// 	v1::Session::new("second key".into(), std::time::Duration::from_secs(1), None),
+/// ]);
+///
+/// let user = pool.run(|session| async move { session.user_data("kangalioo").await }).await?;
+/// # Ok(()) }
+/// ```
+pub struct SessionPool<S> {
+	sessions: std::sync::Mutex<Vec<std::sync::Arc<S>>>,
+}
+
+impl<S: PooledSession> SessionPool<S> {
+	pub fn new(sessions: Vec<S>) -> Self {
+		Self {
+			sessions: std::sync::Mutex::new(
+				sessions.into_iter().map(std::sync::Arc::new).collect(),
+			),
+		}
+	}
+
+	/// Number of sessions that haven't been retired yet
+	pub fn len(&self) -> usize {
+		// UNWRAP: propagate panics
+		self.sessions.lock().unwrap().len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Runs the request on the session that can send it soonest. If the session's login is
+	/// rejected, it is retired and the request is retried on the next session.
+	///
+	/// # Errors
+	/// - [`Error::NoSessionsLeft`] if all sessions have been retired
+	/// - any error returned by `request`, except for rejected credentials
+	pub async fn run<T, F, Fut>(&self, mut request: F) -> Result<T, Error>
+	where
+		F: FnMut(std::sync::Arc<S>) -> Fut,
+		Fut: std::future::Future<Output = Result<T, Error>>,
+	{
+		loop {
+			let session = {
+				// UNWRAP: propagate panics
+				let sessions = self.sessions.lock().unwrap();
+				match sessions
+					.iter()
+					.min_by_key(|session| session.time_until_next_request())
+				{
+					Some(session) => std::sync::Arc::clone(session),
+					None => return Err(Error::NoSessionsLeft),
+				}
+			};
+
+			match request(std::sync::Arc::clone(&session)).await {
+				Err(e @ Error::InvalidLogin | e @ Error::CredentialsRejected { .. }) => {
+					log::warn!("retiring session from pool: {}", e);
+					// UNWRAP: propagate panics
+					self.sessions
+						.lock()
+						.unwrap()
+						.retain(|other| !std::sync::Arc::ptr_eq(other, &session));
+				}
+				result => return result,
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::future::Future;
+
+	use super::*;
+
+	struct StubSession {
+		name: &'static str,
+		time_until_next_request: std::time::Duration,
+		has_valid_login: bool,
+	}
+
+	impl PooledSession for StubSession {
+		fn time_until_next_request(&self) -> std::time::Duration {
+			self.time_until_next_request
+		}
+	}
+
+	fn stub(name: &'static str, wait_secs: u64, has_valid_login: bool) -> StubSession {
+		StubSession {
+			name,
+			time_until_next_request: std::time::Duration::from_secs(wait_secs),
+			has_valid_login,
+		}
+	}
+
+	/// Runs the request on the pool, recording which sessions it was sent to. The stub requests
+	/// never wait, so polling once is enough
+	fn run(pool: &SessionPool<StubSession>) -> (Result<&'static str, Error>, Vec<&'static str>) {
+		let mut attempts = Vec::new();
+		let future = pool.run(|session| {
+			attempts.push(session.name);
+			async move {
+				if session.has_valid_login {
+					Ok(session.name)
+				} else {
+					Err(Error::InvalidLogin)
+				}
+			}
+		});
+
+		let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+		let result = match std::pin::pin!(future).poll(&mut cx) {
+			std::task::Poll::Ready(result) => result,
+			std::task::Poll::Pending => panic!("stub requests never wait"),
+		};
+		(result, attempts)
+	}
+
+	#[test]
+	fn test_retry_on_other_session() {
+		let pool = SessionPool::new(vec![stub("slow", 5, true), stub("fast", 0, false)]);
+
+		let (result, attempts) = run(&pool);
+		assert_eq!(result.unwrap(), "slow");
+		assert_eq!(attempts, ["fast", "slow"]);
+		assert_eq!(pool.len(), 1);
+
+		// The retired session isn't tried again
+		let (result, attempts) = run(&pool);
+		assert_eq!(result.unwrap(), "slow");
+		assert_eq!(attempts, ["slow"]);
+	}
+
+	#[test]
+	fn test_no_sessions_left() {
+		let pool = SessionPool::new(vec![stub("first", 0, false), stub("second", 1, false)]);
+
+		let (result, attempts) = run(&pool);
+		assert!(matches!(result, Err(Error::NoSessionsLeft)));
+		assert_eq!(attempts, ["first", "second"]);
+		assert!(pool.is_empty());
+	}
+}
@@ -0,0 +1,188 @@
+//! Python bindings, enabled with the `python` feature. To build an importable extension module,
+//! use maturin with the `python-extension` feature.
+//!
+//! Sessions are exposed as `V1Session` and `V2Session`. Their methods block until the request is
+//! done and return the results as `types.SimpleNamespace` objects with the same fields as the Rust
+//! structs. Errors are raised as `EtternaOnlineError`.
+
+use pyo3::prelude::*;
+
+pyo3::create_exception!(
+	etternaonline_api,
+	EtternaOnlineError,
+	pyo3::exceptions::PyException
+);
+
+fn runtime() -> &'static tokio::runtime::Runtime {
+	static RUNTIME: std::sync::OnceLock<tokio::runtime::Runtime> = std::sync::OnceLock::new();
+	RUNTIME.get_or_init(|| {
+		// UNWRAP: only fails if the OS refuses to set up the runtime's resources
+		tokio::runtime::Builder::new_current_thread()
+			.enable_all()
+			.build()
+			.unwrap()
+	})
+}
+
+fn to_python<T: serde::Serialize>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+	let json =
+		serde_json::to_string(value).map_err(|e| EtternaOnlineError::new_err(e.to_string()))?;
+	let object_hook = py.eval(
+		pyo3::ffi::c_str!("lambda d: __import__('types').SimpleNamespace(**d)"),
+		None,
+		None,
+	)?;
+	let kwargs = pyo3::types::PyDict::new(py);
+	kwargs.set_item("object_hook", object_hook)?;
+	Ok(py
+		.import("json")?
+		.call_method("loads", (json,), Some(&kwargs))?
+		.unbind())
+}
+
+/// Runs the request to completion without holding the GIL, and converts the result
+fn block_on<T: serde::Serialize + Send>(
+	py: Python<'_>,
+	request: impl std::future::Future<Output = Result<T, crate::Error>> + Send,
+) -> PyResult<PyObject> {
+	match py.allow_threads(|| runtime().block_on(request)) {
+		Ok(value) => to_python(py, &value),
+		Err(e) => Err(EtternaOnlineError::new_err(e.to_string())),
+	}
+}
+
+#[pyclass]
+struct V1Session {
+	inner: crate::v1::Session,
+}
+
+#[pymethods]
+impl V1Session {
+	#[new]
+	#[pyo3(signature = (api_key, cooldown = 1.0, timeout = None))]
+	fn new(api_key: String, cooldown: f64, timeout: Option<f64>) -> Self {
+		Self {
+			inner: crate::v1::Session::new(
+				api_key,
+				std::time::Duration::from_secs_f64(cooldown),
+				timeout.map(std::time::Duration::from_secs_f64),
+			),
+		}
+	}
+
+	fn song_data(&self, py: Python<'_>, song_id: u32) -> PyResult<PyObject> {
+		block_on(py, self.inner.song_data(song_id))
+	}
+
+	fn pack_list(&self, py: Python<'_>) -> PyResult<PyObject> {
+		block_on(py, self.inner.pack_list())
+	}
+
+	fn chart_leaderboard(&self, py: Python<'_>, chartkey: &str) -> PyResult<PyObject> {
+		block_on(py, self.inner.chart_leaderboard(chartkey))
+	}
+
+	fn user_latest_10_scores(&self, py: Python<'_>, username: &str) -> PyResult<PyObject> {
+		block_on(py, self.inner.user_latest_10_scores(username))
+	}
+
+	fn user_data(&self, py: Python<'_>, username: &str) -> PyResult<PyObject> {
+		block_on(py, self.inner.user_data(username))
+	}
+
+	fn user_ranks(&self, py: Python<'_>, username: &str) -> PyResult<PyObject> {
+		block_on(py, self.inner.user_ranks(username))
+	}
+
+	fn country_leaderboard(&self, py: Python<'_>, country_code: &str) -> PyResult<PyObject> {
+		block_on(py, self.inner.country_leaderboard(country_code))
+	}
+
+	fn global_leaderboard(&self, py: Python<'_>) -> PyResult<PyObject> {
+		block_on(py, self.inner.global_leaderboard())
+	}
+
+	fn score_data(&self, py: Python<'_>, scorekey: &str) -> PyResult<PyObject> {
+		block_on(py, self.inner.score_data(scorekey))
+	}
+}
+
+#[pyclass]
+struct V2Session {
+	inner: crate::v2::Session,
+}
+
+#[pymethods]
+impl V2Session {
+	#[staticmethod]
+	#[pyo3(signature = (username, password, client_data, cooldown = 1.0, timeout = None))]
+	fn login(
+		py: Python<'_>,
+		username: String,
+		password: String,
+		client_data: String,
+		cooldown: f64,
+		timeout: Option<f64>,
+	) -> PyResult<Self> {
+		let session = py.allow_threads(|| {
+			runtime().block_on(crate::v2::Session::new_from_login(
+				username,
+				password,
+				client_data,
+				std::time::Duration::from_secs_f64(cooldown),
+				timeout.map(std::time::Duration::from_secs_f64),
+			))
+		});
+		match session {
+			Ok(inner) => Ok(Self { inner }),
+			Err(e) => Err(EtternaOnlineError::new_err(e.to_string())),
+		}
+	}
+
+	fn user_details(&self, py: Python<'_>, username: &str) -> PyResult<PyObject> {
+		block_on(py, self.inner.user_details(username))
+	}
+
+	fn user_top_10_scores(&self, py: Python<'_>, username: &str) -> PyResult<PyObject> {
+		block_on(py, self.inner.user_top_10_scores(username))
+	}
+
+	fn user_latest_scores(&self, py: Python<'_>, username: &str) -> PyResult<PyObject> {
+		block_on(py, self.inner.user_latest_scores(username))
+	}
+
+	fn score_data(&self, py: Python<'_>, scorekey: &str) -> PyResult<PyObject> {
+		block_on(py, self.inner.score_data(scorekey))
+	}
+
+	fn chart_leaderboard(&self, py: Python<'_>, chartkey: &str) -> PyResult<PyObject> {
+		block_on(py, self.inner.chart_leaderboard(chartkey))
+	}
+
+	fn country_leaderboard(&self, py: Python<'_>, country_code: &str) -> PyResult<PyObject> {
+		block_on(py, self.inner.country_leaderboard(country_code))
+	}
+
+	fn world_leaderboard(&self, py: Python<'_>) -> PyResult<PyObject> {
+		block_on(py, self.inner.world_leaderboard())
+	}
+
+	fn user_favorites(&self, py: Python<'_>, username: &str) -> PyResult<PyObject> {
+		block_on(py, self.inner.user_favorites(username))
+	}
+
+	fn user_goals(&self, py: Python<'_>, username: &str) -> PyResult<PyObject> {
+		block_on(py, self.inner.user_goals(username))
+	}
+}
+
+#[pymodule]
+fn etternaonline_api(module: &Bound<'_, PyModule>) -> PyResult<()> {
+	module.add(
+		"EtternaOnlineError",
+		module.py().get_type::<EtternaOnlineError>(),
+	)?;
+	module.add_class::<V1Session>()?;
+	module.add_class::<V2Session>()?;
+	Ok(())
+}
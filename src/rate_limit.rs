@@ -0,0 +1,390 @@
+//! Rate limiting of requests to EO. Every session spaces out its requests by a fixed cooldown; the
+//! state of that can optionally be persisted with a [`RateLimitStore`], so that restarting an
+//! application doesn't reset the cooldown. Several processes can share a cooldown by using a
+//! common [`RateLimitBackend`]
+
+use crate::clock::Clock;
+
+type BoxFuture<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send + 'a>>;
+
+/// Hands out time slots for requests, for when the cooldown must be shared between multiple
+/// processes, e.g. several bot shards that use the same API key. Register one with
+/// `Session::set_rate_limit_backend` on the respective session types; by default, sessions limit
+/// their rate in-process.
+///
+/// With the `redis` feature, [`RedisRateLimitBackend`] is available.
+pub trait RateLimitBackend: Send + Sync {
+	/// Reserves the next free time slot and returns the point in time at which the request may be
+	/// sent. Consecutive slots must be at least `cooldown` apart
+	fn reserve(&self, cooldown: std::time::Duration) -> BoxFuture<'_, std::time::SystemTime>;
+}
+
+/// Persists the point in time at which a session may send its next request, e.g. to a file or a
+/// database. Register one with `Session::set_rate_limit_store` on the respective session types.
+///
+/// Sessions space out their requests by a fixed cooldown, so the time of the next request is all
+/// there is to persist.
+///
+/// Saving happens on every request, from within the async request methods, so it should be cheap
+/// and must not block for long. Errors can't be reported and should be logged or ignored by the
+/// implementation
+pub trait RateLimitStore: Send + Sync {
+	/// Returns the previously saved time, if any
+	fn load(&self) -> Option<std::time::SystemTime>;
+
+	/// Saves the time at which the next request may be sent
+	fn save(&self, next_request: std::time::SystemTime);
+}
+
+/// A [`RateLimitStore`] that saves the time as Unix timestamp in nanoseconds to a file.
+///
+/// The file is written on a background thread, so saving doesn't block the executor. When
+/// several saves pile up, only the latest time is written. Dropping the store (and all of its
+/// clones) waits for the last save to be written
+#[derive(Debug, Clone)]
+pub struct FileRateLimitStore {
+	path: std::path::PathBuf,
+	writer: std::sync::Arc<FileWriter>,
+}
+
+impl FileRateLimitStore {
+	pub fn new(path: impl Into<std::path::PathBuf>) -> Self {
+		let path = path.into();
+		let (sender, receiver) = std::sync::mpsc::channel::<std::time::SystemTime>();
+
+		let thread_path = path.clone();
+		let thread = std::thread::spawn(move || {
+			// Concurrent requests may save out of order, so only ever move the time forward
+			let mut written = std::time::UNIX_EPOCH;
+			while let Ok(mut next_request) = receiver.recv() {
+				while let Ok(other) = receiver.try_recv() {
+					next_request = next_request.max(other);
+				}
+				if next_request > written {
+					write_next_request(&thread_path, next_request);
+					written = next_request;
+				}
+			}
+		});
+
+		Self {
+			path,
+			writer: std::sync::Arc::new(FileWriter {
+				sender: Some(sender),
+				thread: Some(thread),
+			}),
+		}
+	}
+}
+
+/// Background writer of a [`FileRateLimitStore`]
+#[derive(Debug)]
+struct FileWriter {
+	sender: Option<std::sync::mpsc::Sender<std::time::SystemTime>>,
+	thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl Drop for FileWriter {
+	fn drop(&mut self) {
+		// Closing the channel makes the thread exit after writing what's left
+		drop(self.sender.take());
+		if let Some(thread) = self.thread.take() {
+			let _ = thread.join();
+		}
+	}
+}
+
+fn write_next_request(path: &std::path::Path, next_request: std::time::SystemTime) {
+	let nanos = match next_request.duration_since(std::time::UNIX_EPOCH) {
+		Ok(duration) => duration.as_nanos(),
+		Err(_) => return,
+	};
+	if let Err(e) = std::fs::write(path, nanos.to_string()) {
+		log::warn!("failed to save rate limit state to {:?}: {}", path, e);
+	}
+}
+
+impl RateLimitStore for FileRateLimitStore {
+	fn load(&self) -> Option<std::time::SystemTime> {
+		let nanos: u64 = std::fs::read_to_string(&self.path)
+			.ok()?
+			.trim()
+			.parse()
+			.ok()?;
+		Some(std::time::UNIX_EPOCH + std::time::Duration::from_nanos(nanos))
+	}
+
+	fn save(&self, next_request: std::time::SystemTime) {
+		if let Some(sender) = &self.writer.sender {
+			// The thread only stops when the store is dropped, so this can't fail
+			let _ = sender.send(next_request);
+		}
+	}
+}
+
+/// A [`RateLimitBackend`] that keeps the next free time slot in Redis, so that all processes
+/// connected to the same Redis server and key share one cooldown
+#[cfg(feature = "redis")]
+#[derive(Clone)]
+pub struct RedisRateLimitBackend {
+	connection: redis::aio::MultiplexedConnection,
+	key: String,
+}
+
+#[cfg(feature = "redis")]
+impl RedisRateLimitBackend {
+	/// Connects to the Redis server at the given URL, e.g. `redis://127.0.0.1/`. The next free
+	/// time slot is stored under `key`
+	pub async fn connect(url: &str, key: impl Into<String>) -> redis::RedisResult<Self> {
+		let client = redis::Client::open(url)?;
+		Ok(Self {
+			connection: client.get_multiplexed_tokio_connection().await?,
+			key: key.into(),
+		})
+	}
+}
+
+#[cfg(feature = "redis")]
+impl RateLimitBackend for RedisRateLimitBackend {
+	fn reserve(&self, cooldown: std::time::Duration) -> BoxFuture<'_, std::time::SystemTime> {
+		// Reads and advances the next free slot atomically. Times are in milliseconds since the
+		// Unix epoch
+		const RESERVE_SCRIPT: &str = r"
+			local now = tonumber(ARGV[1])
+			local next_slot = tonumber(redis.call('GET', KEYS[1]) or '0')
+			local slot = math.max(now, next_slot)
+			redis.call('SET', KEYS[1], slot + tonumber(ARGV[2]))
+			return slot
+		";
+
+		Box::pin(async move {
+			let now = std::time::SystemTime::now();
+			let now_millis = now
+				.duration_since(std::time::UNIX_EPOCH)
+				.unwrap_or_default()
+				.as_millis() as u64;
+
+			let mut connection = self.connection.clone();
+			let slot: redis::RedisResult<u64> = redis::Script::new(RESERVE_SCRIPT)
+				.key(&self.key)
+				.arg(now_millis)
+				.arg(cooldown.as_millis() as u64)
+				.invoke_async(&mut connection)
+				.await;
+
+			match slot {
+				Ok(slot) => std::time::UNIX_EPOCH + std::time::Duration::from_millis(slot),
+				Err(e) => {
+					// Not sending requests at all would be worse than not coordinating them
+					log::warn!("failed to reserve rate limit slot in Redis: {}", e);
+					now
+				}
+			}
+		})
+	}
+}
+
+/// Spaces out requests by a fixed cooldown. Concurrent requests are assigned consecutive time
+/// slots in the order they arrive, without holding a lock while waiting
+pub(crate) struct RateLimiter {
+	pub(crate) cooldown: std::time::Duration,
+	// Reference point for `next_slot`, in the time base of the session's clock
+	epoch: std::time::Instant,
+	// Nanoseconds after `epoch` at which the next request may be sent
+	next_slot: std::sync::atomic::AtomicU64,
+	store: Option<Box<dyn RateLimitStore>>,
+	// Replaces the in-process slot handling above if set
+	backend: Option<Box<dyn RateLimitBackend>>,
+}
+
+impl RateLimiter {
+	pub(crate) fn new(cooldown: std::time::Duration, clock: &dyn Clock) -> Self {
+		Self {
+			cooldown,
+			epoch: clock.now(),
+			next_slot: std::sync::atomic::AtomicU64::new(0),
+			store: None,
+			backend: None,
+		}
+	}
+
+	/// Switches to a different clock. Reservations made with the previous clock are discarded
+	pub(crate) fn set_clock(&mut self, clock: &dyn Clock) {
+		self.epoch = clock.now();
+		self.next_slot = std::sync::atomic::AtomicU64::new(0);
+	}
+
+	/// Persists the rate limit state from now on, and restores the state saved previously
+	pub(crate) fn set_store(&mut self, store: Box<dyn RateLimitStore>, clock: &dyn Clock) {
+		if let Some(next_request) = store.load() {
+			if let Ok(remaining) = next_request.duration_since(std::time::SystemTime::now()) {
+				let slot = (clock.now() + remaining)
+					.saturating_duration_since(self.epoch)
+					.as_nanos() as u64;
+				self.next_slot
+					.fetch_max(slot, std::sync::atomic::Ordering::SeqCst);
+			}
+		}
+		self.store = Some(store);
+	}
+
+	/// Reserves time slots from the given backend from now on, instead of in-process. The rate
+	/// limit store, if any, is not used anymore
+	pub(crate) fn set_backend(&mut self, backend: Box<dyn RateLimitBackend>) {
+		self.backend = Some(backend);
+	}
+
+	/// How long a request would have to wait if it was sent now. Always zero if a backend is used,
+	/// because its state is unknown
+	pub(crate) fn time_until_next_slot(&self, clock: &dyn Clock) -> std::time::Duration {
+		if self.backend.is_some() {
+			return std::time::Duration::from_secs(0);
+		}
+
+		let now = clock.now().saturating_duration_since(self.epoch).as_nanos() as u64;
+		let next_slot = self.next_slot.load(std::sync::atomic::Ordering::SeqCst);
+		std::time::Duration::from_nanos(next_slot.saturating_sub(now))
+	}
+
+	/// Reserves the next free time slot and returns a future that waits until then
+	pub(crate) fn wait<'a>(&'a self, clock: &'a dyn Clock) -> BoxFuture<'a, ()> {
+		if let Some(backend) = &self.backend {
+			return Box::pin(async move {
+				let slot = backend.reserve(self.cooldown).await;
+				let remaining = slot
+					.duration_since(std::time::SystemTime::now())
+					.unwrap_or_default();
+				clock.sleep_until(clock.now() + remaining).await;
+			});
+		}
+
+		let now = clock.now().saturating_duration_since(self.epoch).as_nanos() as u64;
+		let cooldown = self.cooldown.as_nanos() as u64;
+
+		let previous_slot = self.next_slot.fetch_update(
+			std::sync::atomic::Ordering::SeqCst,
+			std::sync::atomic::Ordering::SeqCst,
+			|next_slot| Some(u64::max(now, next_slot) + cooldown),
+		);
+		// UNWRAP: the closure never returns None
+		let slot = u64::max(now, previous_slot.unwrap());
+
+		if let Some(store) = &self.store {
+			let until_next_slot = std::time::Duration::from_nanos(slot + cooldown - now);
+			store.save(std::time::SystemTime::now() + until_next_slot);
+		}
+
+		Box::pin(clock.sleep_until(self.epoch + std::time::Duration::from_nanos(slot)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A clock that stands still and records what it was asked to sleep until
+	struct FrozenClock {
+		now: std::time::Instant,
+		deadlines: std::sync::Mutex<Vec<std::time::Instant>>,
+	}
+
+	impl Clock for FrozenClock {
+		fn now(&self) -> std::time::Instant {
+			self.now
+		}
+
+		fn sleep_until(
+			&self,
+			deadline: std::time::Instant,
+		) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send + Sync>> {
+			// UNWRAP: propagate panics
+			self.deadlines.lock().unwrap().push(deadline);
+			Box::pin(std::future::ready(()))
+		}
+	}
+
+	#[test]
+	fn test_concurrent_slots() {
+		let clock = FrozenClock {
+			now: std::time::Instant::now(),
+			deadlines: std::sync::Mutex::new(Vec::new()),
+		};
+		let cooldown = std::time::Duration::from_secs(2);
+		let rate_limiter = RateLimiter::new(cooldown, &clock);
+
+		std::thread::scope(|scope| {
+			for _ in 0..8 {
+				scope.spawn(|| {
+					for _ in 0..25 {
+						drop(rate_limiter.wait(&clock));
+					}
+				});
+			}
+		});
+
+		// Every request got its own slot, and no slot was skipped
+		// UNWRAP: propagate panics
+		let mut deadlines = clock.deadlines.lock().unwrap().clone();
+		deadlines.sort();
+		let expected = (0..200)
+			.map(|i| clock.now + cooldown * i)
+			.collect::<Vec<_>>();
+		assert_eq!(deadlines, expected);
+	}
+
+	#[test]
+	fn test_time_until_next_slot() {
+		let clock = crate::clock::InstantClock::new();
+		let second = std::time::Duration::from_secs(1);
+		let rate_limiter = RateLimiter::new(second * 2, &clock);
+		assert_eq!(
+			rate_limiter.time_until_next_slot(&clock),
+			std::time::Duration::from_secs(0)
+		);
+
+		// InstantClock advances when the sleep is created, no need to poll the future
+		drop(rate_limiter.wait(&clock));
+		assert_eq!(rate_limiter.time_until_next_slot(&clock), second * 2);
+		clock.advance(second);
+		assert_eq!(rate_limiter.time_until_next_slot(&clock), second);
+		clock.advance(second * 5);
+		assert_eq!(
+			rate_limiter.time_until_next_slot(&clock),
+			std::time::Duration::from_secs(0)
+		);
+	}
+
+	#[test]
+	fn test_file_store_survives_restart() {
+		let path = std::env::temp_dir().join(format!(
+			"etternaonline_api_rate_limit_test_{}",
+			std::process::id()
+		));
+		let cooldown = std::time::Duration::from_secs(60);
+
+		let next_request = std::time::SystemTime::now() + cooldown;
+		let store = FileRateLimitStore::new(&path);
+		store.save(next_request);
+		store.save(next_request - cooldown);
+		// Dropping the store waits for the background write, which keeps the latest time
+		drop(store);
+		assert_eq!(FileRateLimitStore::new(&path).load(), Some(next_request));
+
+		{
+			let clock = crate::clock::InstantClock::new();
+			let mut rate_limiter = RateLimiter::new(cooldown, &clock);
+			rate_limiter.set_store(Box::new(FileRateLimitStore::new(&path)), &clock);
+			// Use up the restored slot, which moves the saved time one more cooldown ahead
+			drop(rate_limiter.wait(&clock));
+		}
+
+		// After the "restart", the limiter continues where the previous one left off
+		let clock = crate::clock::InstantClock::new();
+		let mut rate_limiter = RateLimiter::new(cooldown, &clock);
+		rate_limiter.set_store(Box::new(FileRateLimitStore::new(&path)), &clock);
+		let remaining = rate_limiter.time_until_next_slot(&clock);
+		assert!(cooldown < remaining && remaining <= cooldown * 2);
+
+		let _ = std::fs::remove_file(&path);
+	}
+}
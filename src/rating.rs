@@ -0,0 +1,301 @@
+//! Player rating calculations, e.g. to find out how a new score would change a player's rating
+//!
+//! The rating aggregation functions in here are bit-accurate to the Etterna game code.
+
+fn is_rating_okay(rating: f32, ssrs: &[f32], delta_multiplier: f32) -> bool {
+	// The mix of f32 and f64 mirrors the C++ implementation, for bit-accuracy
+	let max_power_sum: f64 = 2f64.powf(rating as f64 * 0.1);
+
+	let power_sum: f64 = ssrs
+		.iter()
+		.map(|&ssr| (2.0 / libm::erfcf(delta_multiplier * (ssr - rating)) - 2.0) as f64)
+		.filter(|&x| x > 0.0)
+		.sum();
+
+	power_sum < max_power_sum
+}
+
+/// Aggregate a list of ratings into a single rating, the way Etterna does it everywhere: the
+/// result is the lowest rating at which the summed "power levels" of the input ratings stay
+/// below a limit.
+///
+/// `final_multiplier` scales the result, `delta_multiplier` controls how much ratings far above
+/// the result contribute. The parameters Etterna uses are baked into
+/// [`calculate_chart_overall`] (1.11, 0.25), [`calculate_player_skillset_rating`] (1.05, 0.1) and
+/// [`calculate_player_overall`] (1.125, 0.1).
+pub fn calc_rating(ssrs: &[f32], final_multiplier: f32, delta_multiplier: f32) -> f32 {
+	let num_iters: u32 = 11;
+
+	let mut rating: f32 = 0.0;
+	let mut resolution: f32 = 10.24;
+
+	// Lowest rating known to be okay. Higher ratings are always okay, so they needn't be checked
+	let mut lowest_okay = f32::INFINITY;
+	let mut is_okay = |candidate: f32| {
+		if candidate >= lowest_okay {
+			return true;
+		}
+		let is_okay = is_rating_okay(candidate, ssrs, delta_multiplier);
+		if is_okay {
+			lowest_okay = candidate;
+		}
+		is_okay
+	};
+
+	// Repeatedly approximate the final rating, with better resolution each time
+	for _ in 0..num_iters {
+		// Find lowest 'okay' rating with certain resolution
+		rating = step_until_okay(rating, resolution, &mut is_okay);
+
+		resolution /= 2.0;
+	}
+	// Always be ever so slightly above the target value instead of below
+	rating += resolution * 2.0;
+
+	rating * final_multiplier
+}
+
+/// Equivalent to `while !is_okay(rating + resolution) { rating += resolution }`, but finds the
+/// number of steps with exponential and binary search, which needs fewer calls to `is_okay`.
+///
+/// The candidate ratings are still computed by repeated addition, so the result is bit-identical
+/// to stepping one resolution at a time.
+fn step_until_okay(rating: f32, resolution: f32, is_okay: &mut impl FnMut(f32) -> bool) -> f32 {
+	let after_steps = |num_steps: u32| (0..num_steps).fold(rating, |rating, _| rating + resolution);
+
+	// `after_steps(low)` is not okay (or the start), `after_steps(high)` is okay
+	let mut low = 0;
+	let mut high = 1;
+	while !is_okay(after_steps(high)) {
+		low = high;
+		high *= 2;
+	}
+	while high - low > 1 {
+		let mid = low + (high - low) / 2;
+		if is_okay(after_steps(mid)) {
+			high = mid;
+		} else {
+			low = mid;
+		}
+	}
+
+	after_steps(low)
+}
+
+/// Calculate a score's overall SSR (or a chart's overall MSD) from its seven skillsets.
+///
+/// `AggregateRatings` in Etterna game code
+pub fn calculate_chart_overall(skillsets: &[f32; 7]) -> f32 {
+	calc_rating(skillsets, 1.11, 0.25)
+}
+
+/// Calculate a player's rating in a single skillset from their scores' SSRs in that skillset.
+///
+/// `AggregateSSRs` in Etterna game code
+pub fn calculate_player_skillset_rating(ssrs: &[f32]) -> f32 {
+	calc_rating(ssrs, 1.05, 0.1)
+}
+
+/// Calculate a player's overall rating from their seven skillset ratings.
+///
+/// `AggregateSkillsets` in Etterna game code
+pub fn calculate_player_overall(skillsets: &[f32; 7]) -> f32 {
+	calc_rating(skillsets, 1.125, 0.1)
+}
+
+/// The SSRs of a player's scores, from which the player's rating is calculated.
+///
+/// Usually, this contains the SSRs of all of the player's valid personal bests, see
+/// [`crate::scores::personal_bests_per_chart`].
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+pub struct PlayerSsrs {
+	pub ssrs: Vec<etterna::Skillsets7>,
+}
+
+impl PlayerSsrs {
+	pub fn new(ssrs: impl IntoIterator<Item = etterna::Skillsets8>) -> Self {
+		Self {
+			ssrs: ssrs.into_iter().map(|ssr| ssr.to_skillsets7()).collect(),
+		}
+	}
+
+	/// Calculate the player rating resulting from these SSRs, using the same algorithm as
+	/// Etterna 0.70+
+	pub fn rating(&self) -> etterna::Skillsets8 {
+		etterna::Skillsets7::generate(|skillset| {
+			let ssrs = self
+				.ssrs
+				.iter()
+				.map(|ssr| ssr.get(skillset))
+				.collect::<Vec<_>>();
+			calculate_player_skillset_rating(&ssrs)
+		})
+		.calc_player_overall()
+	}
+
+	/// Calculate how the player's rating would change if they got a score with the given SSR
+	///
+	/// # Example
+	/// ```rust
+	/// # use etternaonline_api::rating::*;
+	/// let player = PlayerSsrs::new(vec![
+	/// 	etterna::Skillsets8 { overall: 25.0, stream: 25.0, ..Default::default() },
+	/// 	etterna::Skillsets8 { overall: 24.0, stream: 24.0, ..Default::default() },
+	/// ]);
+	/// let impact = player.with_hypothetical_score(&etterna::Skillsets8 {
+	/// 	overall: 27.0,
+	/// 	stream: 27.0,
+	/// 	..Default::default()
+	/// });
+	/// assert!(impact.gain().stream > 0.0);
+	/// ```
+	pub fn with_hypothetical_score(&self, ssr: &etterna::Skillsets8) -> RatingImpact {
+		let mut new_ssrs = self.clone();
+		new_ssrs.ssrs.push(ssr.to_skillsets7());
+
+		RatingImpact {
+			before: self.rating(),
+			after: new_ssrs.rating(),
+		}
+	}
+
+	/// Find the lowest SSR that a new score needs to have for the player's rating in `skillset` to
+	/// reach `target_rating`.
+	///
+	/// For a specific skillset, the hypothetical score is assumed to have that SSR in the given
+	/// skillset only. For [`etterna::Skillset8::Overall`], the score is assumed to have that SSR in
+	/// every skillset.
+	///
+	/// Returns `Some(0.0)` if the rating is already at or above the target, and None if no
+	/// single score can raise the rating that far.
+	pub fn required_ssr(&self, skillset: etterna::Skillset8, target_rating: f32) -> Option<f32> {
+		const MAX_SSR: f32 = 100.0;
+
+		let rating_with_score = |ssr: f32| {
+			let score = match skillset.into_skillset7() {
+				Some(skillset) => {
+					etterna::Skillsets7::generate(|ss| if ss == skillset { ssr } else { 0.0 })
+				}
+				None => etterna::Skillsets7::generate(|_| ssr),
+			};
+			let mut new_ssrs = self.clone();
+			new_ssrs.ssrs.push(score);
+			new_ssrs.rating().get(skillset)
+		};
+
+		if self.rating().get(skillset) >= target_rating {
+			return Some(0.0);
+		}
+		if rating_with_score(MAX_SSR) < target_rating {
+			return None;
+		}
+
+		// The rating grows monotonically with the new score's SSR, so we can binary search
+		let (mut low, mut high) = (0.0, MAX_SSR);
+		while high - low > 0.001 {
+			let mid = (low + high) / 2.0;
+			if rating_with_score(mid) >= target_rating {
+				high = mid;
+			} else {
+				low = mid;
+			}
+		}
+		Some(high)
+	}
+}
+
+/// Player rating before and after a hypothetical score, see
+/// [`PlayerSsrs::with_hypothetical_score`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+pub struct RatingImpact {
+	pub before: etterna::Skillsets8,
+	pub after: etterna::Skillsets8,
+}
+
+impl RatingImpact {
+	/// How much each skillset and overall would go up
+	pub fn gain(&self) -> etterna::Skillsets8 {
+		etterna::Skillsets8::generate(|skillset| {
+			self.after.get(skillset) - self.before.get(skillset)
+		})
+	}
+}
+
+/// Estimate a chart's MSD at the given rate from its MSD at 1.0x.
+///
+/// MSD grows roughly proportionally with the rate, which is what this function assumes. The real
+/// values commonly deviate by a few percent, and more the further the rate is from 1.0x, since the
+/// difficulty calculator reacts to the changed note density in non-linear ways (e.g. jacks get
+/// disproportionately harder at high rates). For exact values, use the MSD that EO lists for the
+/// specific rate.
+pub fn msd_at_rate(base_msd: &etterna::Skillsets8, rate: etterna::Rate) -> etterna::Skillsets8 {
+	let rate = rate.as_f32();
+	let msd = base_msd.to_skillsets7();
+	etterna::Skillsets7::generate(|skillset| msd.get(skillset) * rate).calc_ssr_overall()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_matches_etterna() {
+		let skillsets = [21.0, 24.0, 23.0, 14.0, 17.0, 25.0, 24.0];
+		assert_eq!(
+			calculate_chart_overall(&skillsets),
+			etterna::calculate_score_overall(&skillsets)
+		);
+		assert_eq!(
+			calculate_player_skillset_rating(&skillsets),
+			etterna::calculate_player_skillset_rating(&skillsets)
+		);
+		assert_eq!(
+			calculate_player_overall(&skillsets),
+			etterna::calculate_player_overall(&skillsets)
+		);
+	}
+
+	#[test]
+	fn test_calc_rating_bit_identical() {
+		// Simple LCG, so that the inputs are varied but reproducible
+		let mut seed: u32 = 12345;
+		let mut random = |max: f32| {
+			seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+			(seed >> 8) as f32 / (1 << 24) as f32 * max
+		};
+
+		for num_ssrs in [0, 1, 2, 7, 50, 500] {
+			for _ in 0..20 {
+				let ssrs = (0..num_ssrs).map(|_| random(40.0)).collect::<Vec<_>>();
+				assert_eq!(
+					calculate_player_skillset_rating(&ssrs).to_bits(),
+					etterna::calculate_player_skillset_rating(&ssrs).to_bits(),
+					"{:?}",
+					ssrs
+				);
+			}
+		}
+
+		for _ in 0..500 {
+			let skillsets = [(); 7].map(|()| random(45.0));
+			assert_eq!(
+				calculate_chart_overall(&skillsets).to_bits(),
+				etterna::calculate_score_overall(&skillsets).to_bits(),
+			);
+			assert_eq!(
+				calculate_player_overall(&skillsets).to_bits(),
+				etterna::calculate_player_overall(&skillsets).to_bits(),
+			);
+		}
+	}
+}
@@ -0,0 +1,501 @@
+//! Utilities for working with collections of scores, e.g. a user's complete score list
+
+use std::collections::HashMap;
+
+/// Common information that all score types with an associated chart provide. Implemented for the
+/// score types of the v1 and v2 API.
+///
+/// Not implemented for [`web::UserScore`](crate::web::UserScore), because the web API only tells
+/// the song of a score, not which of the song's charts it was set on
+pub trait ScoreInfo {
+	/// What identifies the chart this score was set on. For most score types, this is a
+	/// [`etterna::Chartkey`]
+	type ChartId: std::hash::Hash + Eq + Clone;
+
+	fn chart_id(&self) -> Self::ChartId;
+	fn rate(&self) -> etterna::Rate;
+	fn wifescore(&self) -> etterna::Wifescore;
+	/// Overall SSR. Should be 0 if the score is invalid
+	fn ssr_overall(&self) -> f32;
+	/// Full SSR breakdown of this score, if this score type provides it and the score is valid
+	fn ssr(&self) -> Option<etterna::Skillsets8> {
+		None
+	}
+}
+
+impl ScoreInfo for crate::v2::TopScore {
+	type ChartId = etterna::Chartkey;
+
+	fn chart_id(&self) -> etterna::Chartkey {
+		self.chartkey.clone()
+	}
+	fn rate(&self) -> etterna::Rate {
+		self.rate
+	}
+	fn wifescore(&self) -> etterna::Wifescore {
+		self.wifescore
+	}
+	fn ssr_overall(&self) -> f32 {
+		self.ssr_overall
+	}
+}
+
+impl ScoreInfo for crate::v2::TopScorePerSkillset {
+	type ChartId = etterna::Chartkey;
+
+	fn chart_id(&self) -> etterna::Chartkey {
+		self.chartkey.clone()
+	}
+	fn rate(&self) -> etterna::Rate {
+		self.rate
+	}
+	fn wifescore(&self) -> etterna::Wifescore {
+		self.wifescore
+	}
+	fn ssr_overall(&self) -> f32 {
+		self.ssr.overall
+	}
+	fn ssr(&self) -> Option<etterna::Skillsets8> {
+		Some(self.ssr.clone())
+	}
+}
+
+impl ScoreInfo for crate::v1::TopScore {
+	type ChartId = etterna::Chartkey;
+
+	fn chart_id(&self) -> etterna::Chartkey {
+		self.chartkey.clone()
+	}
+	fn rate(&self) -> etterna::Rate {
+		self.rate
+	}
+	fn wifescore(&self) -> etterna::Wifescore {
+		self.wifescore
+	}
+	fn ssr_overall(&self) -> f32 {
+		self.ssr_overall
+	}
+}
+
+/// What makes a score better than another score, see [`personal_bests_per_chart`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+pub enum PbCriterion {
+	/// Compare by overall SSR first, then by wifescore
+	Ssr,
+	/// Compare by wifescore first, then by overall SSR
+	Wifescore,
+}
+
+impl PbCriterion {
+	fn is_better(self, a: &impl ScoreInfo, b: &impl ScoreInfo) -> bool {
+		// Wifescore's Ord impl can't be relied upon, so compare the raw proportions
+		let (a_wife, b_wife) = (a.wifescore().as_proportion(), b.wifescore().as_proportion());
+		let (a_ssr, b_ssr) = (a.ssr_overall(), b.ssr_overall());
+		match self {
+			Self::Ssr => a_ssr > b_ssr || (a_ssr == b_ssr && a_wife > b_wife),
+			Self::Wifescore => a_wife > b_wife || (a_wife == b_wife && a_ssr > b_ssr),
+		}
+	}
+}
+
+fn personal_bests_by_key<S: ScoreInfo, K: std::hash::Hash + Eq>(
+	scores: impl IntoIterator<Item = S>,
+	criterion: PbCriterion,
+	key: impl Fn(&S) -> K,
+) -> Vec<S> {
+	let mut pbs = Vec::<S>::new();
+	let mut pb_indices = HashMap::<K, usize>::new();
+	for score in scores {
+		match pb_indices.get(&key(&score)) {
+			Some(&i) => {
+				if criterion.is_better(&score, &pbs[i]) {
+					pbs[i] = score;
+				}
+			}
+			None => {
+				pb_indices.insert(key(&score), pbs.len());
+				pbs.push(score);
+			}
+		}
+	}
+	pbs
+}
+
+/// Reduce a list of scores to the best score on each chart. If two scores are equally good, the
+/// earlier one in the list is kept.
+///
+/// The returned scores are in the order in which their charts first appeared in the input list.
+///
+/// # Example
+/// ```rust,no_run
+/// # async fn f() -> Result<(), etternaonline_api::Error> {
+/// # use etternaonline_api::{scores::*, v2};
+/// # let session: v2::Session = unimplemented!();
+/// let top_scores = session.user_top_scores_per_skillset("kangalioo").await?;
+/// // The same score may be among the top scores of several skillsets
+/// let scores = top_scores.stream.into_iter().chain(top_scores.jackspeed);
+///
+/// let pbs = personal_bests_per_chart(scores, PbCriterion::Ssr);
+/// # Ok(()) }
+/// ```
+pub fn personal_bests_per_chart<S: ScoreInfo>(
+	scores: impl IntoIterator<Item = S>,
+	criterion: PbCriterion,
+) -> Vec<S> {
+	personal_bests_by_key(scores, criterion, |score| score.chart_id())
+}
+
+/// Like [`personal_bests_per_chart`], but keeps the best score for each rate played on a chart
+pub fn personal_bests_per_chart_and_rate<S: ScoreInfo>(
+	scores: impl IntoIterator<Item = S>,
+	criterion: PbCriterion,
+) -> Vec<S> {
+	personal_bests_by_key(scores, criterion, |score| (score.chart_id(), score.rate()))
+}
+
+/// Convenience methods for collections of scores, available on slices and `Vec`s of any
+/// [`ScoreInfo`] type
+///
+/// # Example
+/// ```rust,no_run
+/// # async fn f() -> Result<(), etternaonline_api::Error> {
+/// # use etternaonline_api::{scores::*, v2};
+/// # let session: v2::Session = unimplemented!();
+/// let mut scores = session.user_top_10_scores("kangalioo").await?;
+/// scores.sort_by_wife();
+/// for (chartkey, scores) in scores.group_by_chart() {
+/// 	println!("{}: {} scores", chartkey, scores.len());
+/// }
+/// # Ok(()) }
+/// ```
+pub trait ScoreSliceExt<S: ScoreInfo> {
+	/// Sorts by overall SSR, best first
+	fn sort_by_ssr(&mut self);
+	/// Sorts by wifescore, best first
+	fn sort_by_wife(&mut self);
+	/// The valid scores, i.e. those with an SSR
+	fn filter_valid(&self) -> Vec<&S>;
+	/// The `n` scores with the highest overall SSR, best first
+	fn top_n(&self, n: usize) -> Vec<&S>;
+	/// The scores on each chart, in their original order
+	fn group_by_chart(&self) -> HashMap<S::ChartId, Vec<&S>>;
+}
+
+impl<S: ScoreInfo> ScoreSliceExt<S> for [S] {
+	fn sort_by_ssr(&mut self) {
+		self.sort_by(|a, b| b.ssr_overall().total_cmp(&a.ssr_overall()));
+	}
+
+	fn sort_by_wife(&mut self) {
+		// Wifescore's Ord impl can't be relied upon, so compare the raw proportions
+		self.sort_by(|a, b| {
+			b.wifescore()
+				.as_proportion()
+				.total_cmp(&a.wifescore().as_proportion())
+		});
+	}
+
+	fn filter_valid(&self) -> Vec<&S> {
+		self.iter()
+			.filter(|score| score.ssr_overall() > 0.0)
+			.collect()
+	}
+
+	fn top_n(&self, n: usize) -> Vec<&S> {
+		let mut scores = self.iter().collect::<Vec<_>>();
+		scores.sort_by(|a, b| b.ssr_overall().total_cmp(&a.ssr_overall()));
+		scores.truncate(n);
+		scores
+	}
+
+	fn group_by_chart(&self) -> HashMap<S::ChartId, Vec<&S>> {
+		let mut groups = HashMap::<_, Vec<_>>::new();
+		for score in self {
+			groups.entry(score.chart_id()).or_default().push(score);
+		}
+		groups
+	}
+}
+
+/// Scores that carry the date and time at which they were set. Unlike [`ScoreInfo`], this doesn't
+/// require knowing the chart of a score
+pub trait TimedScore {
+	/// Datetime string in EO's format, e.g. `2020-07-25 14:03:51`
+	fn datetime(&self) -> &str;
+	fn wifescore(&self) -> etterna::Wifescore;
+	/// Overall SSR. Should be 0 if the score is invalid
+	fn ssr_overall(&self) -> f32;
+	/// Full SSR breakdown of this score, if the score is valid
+	fn ssr(&self) -> Option<etterna::Skillsets8>;
+}
+
+impl TimedScore for crate::web::UserScore {
+	fn datetime(&self) -> &str {
+		&self.date
+	}
+	fn wifescore(&self) -> etterna::Wifescore {
+		self.wifescore
+	}
+	fn ssr_overall(&self) -> f32 {
+		match &self.validity_dependant {
+			Some(info) => info.ssr.overall,
+			None => 0.0,
+		}
+	}
+	fn ssr(&self) -> Option<etterna::Skillsets8> {
+		Some(self.validity_dependant.as_ref()?.ssr.clone())
+	}
+}
+
+/// Parse an EO datetime string (`YYYY-MM-DD HH:MM:SS`) into seconds since the Unix epoch
+fn parse_eo_datetime(datetime: &str) -> Option<i64> {
+	let mut parts = datetime
+		.trim()
+		.split(&['-', ' ', ':', 'T'][..])
+		.map(|part| part.parse::<i64>());
+	let mut next = || parts.next()?.ok();
+	let (year, month, day) = (next()?, next()?, next()?);
+	let (hour, minute, second) = (
+		next().unwrap_or(0),
+		next().unwrap_or(0),
+		next().unwrap_or(0),
+	);
+
+	// Days since epoch from a civil date, see http://howardhinnant.github.io/date_algorithms.html
+	let year = if month <= 2 { year - 1 } else { year };
+	let era = year.div_euclid(400);
+	let year_of_era = year - era * 400;
+	let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+	let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+	let days = era * 146097 + day_of_era - 719468;
+
+	Some(days * 86400 + hour * 3600 + minute * 60 + second)
+}
+
+/// A series of scores played without long breaks in between. See [`group_into_sessions`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+pub struct PlaySession<S> {
+	/// The scores of this session in chronological order. Never empty
+	pub scores: Vec<S>,
+}
+
+impl<S: TimedScore> PlaySession<S> {
+	pub fn score_count(&self) -> usize {
+		self.scores.len()
+	}
+
+	/// Datetime of the first score in this session
+	pub fn start(&self) -> &str {
+		self.scores[0].datetime()
+	}
+
+	/// Datetime of the last score in this session
+	pub fn end(&self) -> &str {
+		self.scores[self.scores.len() - 1].datetime()
+	}
+
+	/// Time between the first and the last score of this session
+	pub fn duration(&self) -> std::time::Duration {
+		let start = parse_eo_datetime(self.start()).unwrap_or(0);
+		let end = parse_eo_datetime(self.end()).unwrap_or(0);
+		std::time::Duration::from_secs((end - start).max(0) as u64)
+	}
+
+	/// Mean wifescore of all scores in this session
+	pub fn average_wifescore(&self) -> etterna::Wifescore {
+		let sum: f32 = self
+			.scores
+			.iter()
+			.map(|score| score.wifescore().as_proportion())
+			.sum();
+		etterna::Wifescore::from_proportion(sum / self.scores.len() as f32)
+			.unwrap_or(etterna::Wifescore::D_THRESHOLD)
+	}
+
+	/// The highest overall SSR achieved in this session
+	pub fn best_ssr(&self) -> f32 {
+		self.scores
+			.iter()
+			.map(|score| score.ssr_overall())
+			.fold(0.0, f32::max)
+	}
+}
+
+/// Group scores into play sessions. A new session starts whenever more than `max_gap` time has
+/// passed since the previous score.
+///
+/// The input scores don't need to be sorted. Scores whose datetime can't be parsed are ignored.
+/// The returned sessions are in chronological order.
+pub fn group_into_sessions<S: TimedScore>(
+	scores: impl IntoIterator<Item = S>,
+	max_gap: std::time::Duration,
+) -> Vec<PlaySession<S>> {
+	let mut scores = scores
+		.into_iter()
+		.filter_map(|score| Some((parse_eo_datetime(score.datetime())?, score)))
+		.collect::<Vec<_>>();
+	scores.sort_by_key(|&(timestamp, _)| timestamp);
+
+	let mut sessions = Vec::<PlaySession<S>>::new();
+	let mut last_timestamp = None;
+	for (timestamp, score) in scores {
+		match (sessions.last_mut(), last_timestamp) {
+			(Some(session), Some(last)) if (timestamp - last) as u64 <= max_gap.as_secs() => {
+				session.scores.push(score)
+			}
+			_ => sessions.push(PlaySession {
+				scores: vec![score],
+			}),
+		}
+		last_timestamp = Some(timestamp);
+	}
+	sessions
+}
+
+/// Recompute a player's rating over time from a chronologically sorted list of dated SSRs.
+///
+/// EO only stores a player's current rating. This function reproduces the rating as it was after
+/// each point in time. The "date" can be anything comparable, e.g. a day string: scores with
+/// equal dates that follow each other are grouped and the rating is only calculated once per
+/// group.
+///
+/// Only pass in SSRs of valid scores, like the game does.
+pub fn rating_history<T: PartialEq + Send>(
+	ssrs: impl IntoIterator<Item = (T, etterna::Skillsets8)>,
+) -> Vec<(T, etterna::Skillsets8)> {
+	let ssrs = ssrs
+		.into_iter()
+		.map(|(date, ssr)| (date, ssr.to_skillsets7()));
+	etterna::SkillTimeline::calculate(ssrs, false).changes
+}
+
+/// Like [`rating_history`], but takes a list of scores in any order. The rating is recalculated
+/// once per day; the returned list contains the day (`YYYY-MM-DD`) and the player's rating at the
+/// end of that day.
+///
+/// Invalid scores, scores without SSR breakdown and scores whose datetime can't be parsed are
+/// ignored.
+pub fn rating_history_from_scores<S: TimedScore>(
+	scores: impl IntoIterator<Item = S>,
+) -> Vec<(String, etterna::Skillsets8)> {
+	let mut scores = scores
+		.into_iter()
+		.filter_map(|score| {
+			let timestamp = parse_eo_datetime(score.datetime())?;
+			let day = score.datetime().get(..10)?.to_owned();
+			Some((timestamp, day, score.ssr()?))
+		})
+		.collect::<Vec<_>>();
+	scores.sort_by_key(|&(timestamp, _, _)| timestamp);
+
+	rating_history(scores.into_iter().map(|(_, day, ssr)| (day, ssr)))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	struct TestScore {
+		chart: u32,
+		rate: f32,
+		wife: f32,
+		ssr: f32,
+	}
+
+	impl ScoreInfo for TestScore {
+		type ChartId = u32;
+
+		fn chart_id(&self) -> u32 {
+			self.chart
+		}
+		fn rate(&self) -> etterna::Rate {
+			etterna::Rate::from_f32(self.rate).unwrap()
+		}
+		fn wifescore(&self) -> etterna::Wifescore {
+			etterna::Wifescore::from_percent(self.wife).unwrap()
+		}
+		fn ssr_overall(&self) -> f32 {
+			self.ssr
+		}
+	}
+
+	fn score(chart: u32, rate: f32, wife: f32, ssr: f32) -> TestScore {
+		TestScore {
+			chart,
+			rate,
+			wife,
+			ssr,
+		}
+	}
+
+	#[test]
+	fn test_score_slice_ext() {
+		let mut scores = [
+			score(1, 1.0, 93.0, 20.0),
+			score(2, 1.0, 97.0, 0.0),
+			score(1, 1.1, 90.0, 22.0),
+		];
+
+		assert_eq!(scores.filter_valid().len(), 2);
+		assert_eq!(
+			scores.top_n(2).iter().map(|s| s.ssr).collect::<Vec<_>>(),
+			vec![22.0, 20.0]
+		);
+		assert_eq!(scores.group_by_chart()[&1].len(), 2);
+
+		scores.sort_by_wife();
+		assert_eq!(
+			scores.iter().map(|s| s.wife).collect::<Vec<_>>(),
+			vec![97.0, 93.0, 90.0]
+		);
+	}
+
+	#[test]
+	fn test_personal_bests() {
+		let scores = || {
+			vec![
+				score(1, 1.0, 93.0, 20.0),
+				score(2, 1.0, 90.0, 25.0),
+				score(1, 1.1, 90.0, 22.0),
+				score(1, 1.0, 95.0, 21.0),
+				score(2, 1.0, 90.0, 25.0),
+			]
+		};
+		let summarize = |scores: Vec<TestScore>| -> Vec<(u32, f32, f32)> {
+			scores.iter().map(|s| (s.chart, s.rate, s.wife)).collect()
+		};
+
+		assert_eq!(
+			summarize(personal_bests_per_chart(scores(), PbCriterion::Ssr)),
+			vec![(1, 1.1, 90.0), (2, 1.0, 90.0)],
+		);
+		assert_eq!(
+			summarize(personal_bests_per_chart(scores(), PbCriterion::Wifescore)),
+			vec![(1, 1.0, 95.0), (2, 1.0, 90.0)],
+		);
+		assert_eq!(
+			summarize(personal_bests_per_chart_and_rate(
+				scores(),
+				PbCriterion::Ssr
+			)),
+			vec![(1, 1.0, 95.0), (2, 1.0, 90.0), (1, 1.1, 90.0)],
+		);
+	}
+
+	#[test]
+	fn test_parse_eo_datetime() {
+		assert_eq!(parse_eo_datetime("1970-01-01 00:00:00"), Some(0));
+		assert_eq!(parse_eo_datetime("2020-07-25 14:03:51"), Some(1595685831));
+		assert_eq!(parse_eo_datetime("not a date"), None);
+	}
+}
@@ -0,0 +1,225 @@
+//! A common interface over the v1, v2 and web sessions, for code that should keep working when
+//! one of EO's APIs is down.
+//!
+//! The data returned by the trait methods is normalized to what all APIs provide. If you need
+//! the full, API-specific data, call the session methods directly.
+
+use etterna::*;
+
+use crate::Error;
+
+pub(crate) type BoxFuture<'a, T> =
+	std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send + 'a>>;
+
+/// Requests that all session types can answer. Use `Box<dyn EoSession>` to pick the backend at
+/// runtime.
+///
+/// Not every API supports every request; those return [`Error::Unsupported`], so that callers
+/// can fall back to another backend.
+///
+/// The method names collide with the inherent methods of the sessions, which take precedence. Call
+/// the trait methods through a trait object or with `EoSession::user_details(&session, ...)`.
+///
+/// # Example
+/// ```rust,no_run
+/// # async fn f() -> Result<(), etternaonline_api::Error> {
+/// # use etternaonline_api::{session::EoSession, v1, v2};
+/// # let v2_session: v2::Session = unimplemented!();
+/// # let v1_session: v1::Session = unimplemented!();
+/// let backends: Vec<Box<dyn EoSession>> = vec![Box::new(v2_session), Box::new(v1_session)];
+///
+/// for backend in &backends {
+/// 	match backend.user_details("kangalioo").await {
+/// 		Ok(user) => {
+/// 			println!("Rating: {:.2}", user.rating.overall);
+/// 			break;
+/// 		}
+/// 		Err(e) => eprintln!("Backend failed, trying the next one: {}", e),
+/// 	}
+/// }
+/// # Ok(()) }
+/// ```
+pub trait EoSession: Send + Sync {
+	/// Retrieves the profile of the specified user.
+	///
+	/// # Errors
+	/// - [`Error::UserNotFound`] if the supplied username was not found
+	/// - [`Error::Unsupported`] on web sessions
+	fn user_details<'a>(&'a self, username: &'a str) -> BoxFuture<'a, Result<UserProfile, Error>>;
+
+	/// Retrieves the score with the given scorekey.
+	///
+	/// # Errors
+	/// - [`Error::ScoreNotFound`] if the supplied scorekey was not found
+	/// - [`Error::InvalidScorekey`] if the supplied scorekey is malformed
+	/// - [`Error::Unsupported`] on web sessions
+	fn score_data<'a>(&'a self, scorekey: &'a str) -> BoxFuture<'a, Result<Score, Error>>;
+
+	/// Retrieves the leaderboard of the given chart, with each user's best score.
+	///
+	/// # Errors
+	/// - [`Error::ChartNotTracked`] if the chartkey provided is not tracked by EO
+	/// - [`Error::InvalidChartkey`] if the supplied chartkey is malformed
+	fn chart_leaderboard<'a>(
+		&'a self,
+		chartkey: &'a str,
+	) -> BoxFuture<'a, Result<Vec<LeaderboardScore>, Error>>;
+}
+
+/// User profile, as returned by [`EoSession::user_details`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct UserProfile {
+	pub username: String,
+	pub country_code: Option<String>,
+	pub rating: Skillsets8,
+}
+
+/// A single score, as returned by [`EoSession::score_data`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct Score {
+	pub scorekey: Scorekey,
+	pub username: String,
+	pub ssr: Skillsets8,
+	pub wifescore: Wifescore,
+	pub rate: Rate,
+	pub max_combo: u32,
+	pub is_valid: bool,
+	pub has_chord_cohesion: bool,
+	pub modifiers: String,
+	pub judgements: FullJudgements,
+	pub replay: Option<crate::Replay>,
+}
+
+/// An entry of a chart leaderboard, as returned by [`EoSession::chart_leaderboard`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct LeaderboardScore {
+	/// None on v1 sessions, whose chart leaderboards don't include scorekeys
+	pub scorekey: Option<Scorekey>,
+	pub username: String,
+	pub ssr_overall: f32,
+	pub wifescore: Wifescore,
+	pub rate: Rate,
+	pub max_combo: u32,
+	pub datetime: String,
+	pub judgements: TapJudgements,
+}
+
+impl From<crate::v1::UserData> for UserProfile {
+	fn from(user: crate::v1::UserData) -> Self {
+		Self {
+			username: user.user_name,
+			country_code: user.country_code,
+			rating: user.rating,
+		}
+	}
+}
+
+impl From<crate::v2::UserDetails> for UserProfile {
+	fn from(user: crate::v2::UserDetails) -> Self {
+		Self {
+			username: user.username,
+			country_code: Some(user.country_code).filter(|code| !code.is_empty()),
+			rating: user.rating,
+		}
+	}
+}
+
+impl Score {
+	/// v1 score data doesn't include the scorekey, so it needs to be passed in
+	pub fn from_v1(scorekey: Scorekey, score: crate::v1::ScoreData) -> Self {
+		Self {
+			scorekey,
+			username: score.user.username,
+			ssr: score.ssr,
+			wifescore: score.wifescore,
+			rate: score.rate,
+			max_combo: score.max_combo,
+			is_valid: score.is_valid,
+			has_chord_cohesion: score.has_chord_cohesion,
+			modifiers: score.modifiers,
+			judgements: score.judgements,
+			replay: score.replay,
+		}
+	}
+}
+
+impl From<crate::v2::ScoreData> for Score {
+	fn from(score: crate::v2::ScoreData) -> Self {
+		Self {
+			scorekey: score.scorekey,
+			username: score.user.username,
+			ssr: score.ssr,
+			wifescore: score.wifescore,
+			rate: score.rate,
+			max_combo: score.max_combo,
+			is_valid: score.is_valid,
+			has_chord_cohesion: score.has_chord_cohesion,
+			modifiers: score.modifiers,
+			judgements: score.judgements,
+			replay: score.replay,
+		}
+	}
+}
+
+impl From<crate::v1::ChartLeaderboardEntry> for LeaderboardScore {
+	fn from(entry: crate::v1::ChartLeaderboardEntry) -> Self {
+		Self {
+			scorekey: None,
+			username: entry.user.username,
+			ssr_overall: entry.ssr.overall,
+			wifescore: entry.wifescore,
+			rate: entry.rate,
+			max_combo: entry.max_combo,
+			datetime: entry.datetime,
+			judgements: entry.judgements.into(),
+		}
+	}
+}
+
+impl From<crate::v2::ChartLeaderboardScore> for LeaderboardScore {
+	fn from(entry: crate::v2::ChartLeaderboardScore) -> Self {
+		Self {
+			scorekey: Some(entry.scorekey),
+			username: entry.user.username,
+			ssr_overall: entry.ssr.overall,
+			wifescore: entry.wifescore,
+			rate: entry.rate,
+			max_combo: entry.max_combo,
+			datetime: entry.datetime,
+			judgements: entry.judgements.into(),
+		}
+	}
+}
+
+impl From<crate::web::ChartLeaderboardEntry> for LeaderboardScore {
+	fn from(entry: crate::web::ChartLeaderboardEntry) -> Self {
+		Self {
+			scorekey: Some(entry.scorekey),
+			username: entry.username,
+			ssr_overall: entry.ssr_overall,
+			wifescore: entry.wifescore,
+			rate: entry.rate,
+			max_combo: entry.max_combo,
+			datetime: entry.date,
+			judgements: entry.judgements,
+		}
+	}
+}
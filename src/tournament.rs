@@ -0,0 +1,380 @@
+//! Tournament standings from EO scores.
+//!
+//! Register the scores that count with [`Tournament::best_score`] or [`Tournament::score`], then
+//! compute the standings with [`Tournament::standings`], which fetches everything through a v2
+//! session. To check whether players have played every chart of a pool, e.g. for qualifiers, use
+//! [`pool_completion`].
+//!
+//! # Example
+//! ```rust,no_run
+//! # async fn run() -> Result<(), etternaonline_api::Error> {
+//! use etternaonline_api::tournament::*;
+//! # let session: etternaonline_api::v2::Session = unimplemented!();
+//!
+//! let standings = Tournament::new(Scoring::RankPoints(vec![10.0, 7.0, 5.0, 3.0, 1.0]))
+//! 	.best_score("kangalioo", "X4a15f62b66a80b62ec64521704f98c6c03d98e03")
+//! 	.best_score("Rubyyi", "X4a15f62b66a80b62ec64521704f98c6c03d98e03")
+//! 	.standings(&session)
+//! 	.await?;
+//! for standing in standings {
+//! 	println!("#{} {} ({} points)", standing.rank, standing.username, standing.points);
+//! }
+//! # Ok(()) }
+//! ```
+
+use etterna::{Rate, Scorekey, Wifescore};
+
+use crate::Error;
+
+/// How players earn points on each chart
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+pub enum Scoring {
+	/// A score is worth its wifescore in percent
+	Wifescore,
+	/// The best player on a chart gets the first amount of points, the second best the second
+	/// amount, and so on. Players beyond the end of the list get nothing
+	RankPoints(Vec<f32>),
+}
+
+/// A score that counts towards the standings
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct TournamentScore {
+	pub chartkey: String,
+	pub scorekey: Scorekey,
+	pub wifescore: Wifescore,
+	pub rate: Rate,
+	/// Points awarded for this score
+	pub points: f32,
+}
+
+/// A player's position in the standings
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct Standing {
+	/// Starting at 1. Players with equal points share a rank
+	pub rank: u32,
+	pub username: String,
+	pub points: f32,
+	pub scores: Vec<TournamentScore>,
+}
+
+#[derive(Debug, Clone)]
+enum Submission {
+	Best { username: String, chartkey: String },
+	Score { chartkey: String, scorekey: String },
+}
+
+/// Collects the scores of a tournament and computes standings from them
+#[derive(Debug, Clone)]
+pub struct Tournament {
+	scoring: Scoring,
+	submissions: Vec<Submission>,
+}
+
+impl Tournament {
+	pub fn new(scoring: Scoring) -> Self {
+		Self {
+			scoring,
+			submissions: Vec::new(),
+		}
+	}
+
+	/// Counts the player's best score on the chart's leaderboard. If the player has no score on
+	/// the chart, they get no points for it
+	pub fn best_score(mut self, username: impl Into<String>, chartkey: impl Into<String>) -> Self {
+		self.submissions.push(Submission::Best {
+			username: username.into(),
+			chartkey: chartkey.into(),
+		});
+		self
+	}
+
+	/// Counts a specific score on the given chart. The chartkey is needed because EO doesn't
+	/// report which chart a score was made on
+	pub fn score(mut self, chartkey: impl Into<String>, scorekey: impl Into<String>) -> Self {
+		self.submissions.push(Submission::Score {
+			chartkey: chartkey.into(),
+			scorekey: scorekey.into(),
+		});
+		self
+	}
+
+	/// Fetches all registered scores and computes the standings, best player first. Players with
+	/// equal points are ordered by username.
+	///
+	/// Each chart leaderboard is only requested once, no matter how many submissions refer to it.
+	/// If a player has several scores on one chart, only the one with the highest
+	/// wifescore counts.
+	///
+	/// # Errors
+	/// - [`Error::ChartNotTracked`] if a chartkey is not tracked by EO
+	/// - [`Error::ScoreNotFound`] if a scorekey was not found
+	pub async fn standings(&self, session: &crate::v2::Session) -> Result<Vec<Standing>, Error> {
+		// chartkey -> username -> best score on that chart
+		let mut charts = std::collections::BTreeMap::<
+			&str,
+			std::collections::HashMap<String, TournamentScore>,
+		>::new();
+		let mut leaderboards = std::collections::HashMap::new();
+
+		for submission in &self.submissions {
+			let (chartkey, username, score) = match submission {
+				Submission::Best { username, chartkey } => {
+					if !leaderboards.contains_key(chartkey.as_str()) {
+						let leaderboard = session.chart_leaderboard(chartkey).await?;
+						leaderboards.insert(chartkey.as_str(), leaderboard);
+					}
+					let best = leaderboards[chartkey.as_str()]
+						.iter()
+						.filter(|entry| entry.user.username.eq_ignore_ascii_case(username))
+						.max_by(|a, b| {
+							a.wifescore
+								.as_proportion()
+								.total_cmp(&b.wifescore.as_proportion())
+						});
+					match best {
+						Some(entry) => (
+							chartkey.as_str(),
+							entry.user.username.clone(),
+							TournamentScore {
+								chartkey: chartkey.clone(),
+								scorekey: entry.scorekey.clone(),
+								wifescore: entry.wifescore,
+								rate: entry.rate,
+								points: 0.0,
+							},
+						),
+						None => continue,
+					}
+				}
+				Submission::Score { chartkey, scorekey } => {
+					let score = session.score_data(scorekey).await?;
+					(
+						chartkey.as_str(),
+						score.user.username,
+						TournamentScore {
+							chartkey: chartkey.clone(),
+							scorekey: score.scorekey,
+							wifescore: score.wifescore,
+							rate: score.rate,
+							points: 0.0,
+						},
+					)
+				}
+			};
+
+			let chart = charts.entry(chartkey).or_default();
+			match chart.get(&username) {
+				Some(existing)
+					if existing.wifescore.as_proportion() >= score.wifescore.as_proportion() => {}
+				_ => {
+					chart.insert(username, score);
+				}
+			}
+		}
+
+		Ok(self.compute_standings(charts))
+	}
+
+	fn compute_standings(
+		&self,
+		charts: std::collections::BTreeMap<
+			&str,
+			std::collections::HashMap<String, TournamentScore>,
+		>,
+	) -> Vec<Standing> {
+		let mut standings = std::collections::BTreeMap::<String, Standing>::new();
+
+		for scores in charts.into_values() {
+			let mut scores = scores.into_iter().collect::<Vec<_>>();
+			// Best first; ties are broken by username to keep the result deterministic
+			scores.sort_by(|(a_name, a), (b_name, b)| {
+				b.wifescore
+					.as_proportion()
+					.total_cmp(&a.wifescore.as_proportion())
+					.then_with(|| a_name.cmp(b_name))
+			});
+
+			for (i, (username, mut score)) in scores.into_iter().enumerate() {
+				score.points = match &self.scoring {
+					Scoring::Wifescore => score.wifescore.as_percent(),
+					Scoring::RankPoints(points) => points.get(i).copied().unwrap_or(0.0),
+				};
+
+				let standing = standings
+					.entry(username.clone())
+					.or_insert_with(|| Standing {
+						rank: 0,
+						username,
+						points: 0.0,
+						scores: Vec::new(),
+					});
+				standing.points += score.points;
+				standing.scores.push(score);
+			}
+		}
+
+		// The BTreeMap yields players by username, and the sort is stable
+		let mut standings = standings.into_values().collect::<Vec<_>>();
+		standings.sort_by(|a, b| b.points.total_cmp(&a.points));
+
+		let mut previous: Option<(f32, u32)> = None;
+		for (i, standing) in standings.iter_mut().enumerate() {
+			standing.rank = match previous {
+				Some((points, rank)) if points == standing.points => rank,
+				_ => i as u32 + 1,
+			};
+			previous = Some((standing.points, standing.rank));
+		}
+		standings
+	}
+}
+
+/// Which charts of a pool each player has valid scores on. See [`pool_completion`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct PoolCompletion {
+	/// The charts of the pool, in the order they were given
+	pub chartkeys: Vec<String>,
+	/// For each player, the rates at which they have a valid score on each played chart of the
+	/// pool, sorted ascending. Charts without a valid score are absent
+	pub rates_per_player:
+		std::collections::BTreeMap<String, std::collections::BTreeMap<String, Vec<Rate>>>,
+}
+
+impl PoolCompletion {
+	/// Charts of the pool that the player has no valid score on
+	pub fn missing_charts(&self, username: &str) -> Vec<&str> {
+		let played = self.rates_per_player.get(username);
+		self.chartkeys
+			.iter()
+			.filter(|chartkey| !played.is_some_and(|played| played.contains_key(*chartkey)))
+			.map(|chartkey| chartkey.as_str())
+			.collect()
+	}
+
+	/// Whether the player has a valid score on every chart of the pool
+	pub fn is_complete(&self, username: &str) -> bool {
+		self.missing_charts(username).is_empty()
+	}
+}
+
+/// Checks which of the players have valid scores on which charts of the pool, e.g. to verify
+/// tournament qualifier submissions. One chart leaderboard request is made per chart, subject to
+/// the session's rate limit. Usernames are matched case-insensitively; the result uses the
+/// usernames as given.
+///
+/// # Errors
+/// - [`Error::ChartNotTracked`] if a chartkey is not tracked by EO
+pub async fn pool_completion(
+	session: &crate::v2::Session,
+	usernames: &[&str],
+	chartkeys: &[&str],
+) -> Result<PoolCompletion, Error> {
+	let mut completion = PoolCompletion {
+		chartkeys: chartkeys.iter().map(|&c| c.to_owned()).collect(),
+		rates_per_player: usernames
+			.iter()
+			.map(|&username| (username.to_owned(), Default::default()))
+			.collect(),
+	};
+
+	for &chartkey in chartkeys {
+		for entry in session.chart_leaderboard(chartkey).await? {
+			if !entry.is_valid {
+				continue;
+			}
+			let username = match usernames
+				.iter()
+				.find(|username| username.eq_ignore_ascii_case(&entry.user.username))
+			{
+				Some(&username) => username,
+				None => continue,
+			};
+
+			// UNWRAP: all given usernames were inserted above
+			let rates = completion
+				.rates_per_player
+				.get_mut(username)
+				.unwrap()
+				.entry(chartkey.to_owned())
+				.or_default();
+			if !rates.contains(&entry.rate) {
+				rates.push(entry.rate);
+				rates.sort();
+			}
+		}
+	}
+
+	Ok(completion)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn score(chartkey: &str, percent: f32) -> TournamentScore {
+		TournamentScore {
+			chartkey: chartkey.to_owned(),
+			scorekey: "S65565b5bc377c6d78b60c0aecfd9e05955b4cf63".parse().unwrap(),
+			wifescore: Wifescore::from_percent(percent).unwrap(),
+			rate: Rate::from_f32(1.0).unwrap(),
+			points: 0.0,
+		}
+	}
+
+	#[test]
+	fn test_rank_points() {
+		let mut charts = std::collections::BTreeMap::new();
+		charts.insert(
+			"A",
+			vec![
+				("alice".to_owned(), score("A", 95.0)),
+				("bob".to_owned(), score("A", 96.0)),
+			]
+			.into_iter()
+			.collect(),
+		);
+		charts.insert(
+			"B",
+			vec![
+				("alice".to_owned(), score("B", 97.0)),
+				("bob".to_owned(), score("B", 93.0)),
+			]
+			.into_iter()
+			.collect(),
+		);
+
+		let standings =
+			Tournament::new(Scoring::RankPoints(vec![3.0, 1.0])).compute_standings(charts);
+		// Both players have 4 points, so they share the first rank and are ordered by name
+		assert_eq!(
+			standings
+				.iter()
+				.map(|s| (s.rank, s.username.as_str(), s.points))
+				.collect::<Vec<_>>(),
+			vec![(1, "alice", 4.0), (1, "bob", 4.0)],
+		);
+	}
+}
@@ -0,0 +1,100 @@
+//! A blocking version of [`v1::Session`](super::Session), for programs without an async runtime.
+//!
+//! Each method blocks the current thread until the request is done. Like reqwest's blocking
+//! client, this must not be used from within an async runtime; calling it there panics.
+//!
+//! # Example
+//! ```rust,no_run
+//! # fn main() -> Result<(), etternaonline_api::Error> {
+//! use etternaonline_api::v1::blocking::Session;
+//!
+//! let session = Session::new(
+//! 	"<API KEY>".into(),
+//! 	std::time::Duration::from_millis(2000), // wait 2s inbetween requests
+//! 	None, // no timeout
+//! );
+//!
+//! let user = session.user_data("kangalioo")?;
+//! println!("kangalioo's rating is {:.2}", user.rating.overall);
+//! # Ok(()) }
+//! ```
+
+use super::*;
+use crate::Error;
+
+pub struct Session {
+	inner: super::Session,
+	runtime: tokio::runtime::Runtime,
+}
+
+impl Session {
+	/// See [`v1::Session::new`](super::Session::new)
+	pub fn new(
+		api_key: String,
+		cooldown: std::time::Duration,
+		timeout: Option<std::time::Duration>,
+	) -> Self {
+		Self::from_async(super::Session::new(api_key, cooldown, timeout))
+	}
+
+	/// Wraps an async session, e.g. one that was already configured
+	pub fn from_async(session: super::Session) -> Self {
+		Self {
+			inner: session,
+			runtime: crate::blocking::runtime(),
+		}
+	}
+
+	/// The wrapped async session, for its non-request methods like
+	/// [`stats`](super::Session::stats)
+	pub fn as_async(&self) -> &super::Session {
+		&self.inner
+	}
+
+	/// The wrapped async session, for its settings like
+	/// [`set_lenient`](super::Session::set_lenient)
+	pub fn as_async_mut(&mut self) -> &mut super::Session {
+		&mut self.inner
+	}
+
+	pub fn into_async(self) -> super::Session {
+		self.inner
+	}
+}
+
+blocking_methods! {
+	fn song_data(&self, song_id: impl Into<SongId>) -> Result<SongData, Error>;
+	fn duplicate_charts(
+		&self,
+		song_ids: &[impl Into<SongId> + Copy],
+	) -> Result<Vec<DuplicateChart>, Error>;
+	fn client_version(&self) -> Result<String, Error>;
+	fn compare_client_version(&self, version: &str) -> Result<VersionComparison, Error>;
+	fn register_link(&self) -> Result<String, Error>;
+	fn pack_list(&self) -> Result<Vec<PackEntry>, Error>;
+	fn chart_leaderboard(
+		&self,
+		chartkey: impl AsRef<str>,
+	) -> Result<Vec<ChartLeaderboardEntry>, Error>;
+	fn chart_overview(
+		&self,
+		song_id: impl Into<SongId>,
+		chartkey: impl AsRef<str>,
+	) -> Result<ChartOverview, Error>;
+	fn user_latest_10_scores(&self, username: &str) -> Result<Vec<LatestScore>, Error>;
+	fn user_latest_10_scores_with_keys(
+		&self,
+		username: &str,
+	) -> Result<Vec<KeyedLatestScore>, Error>;
+	fn user_data(&self, username: &str) -> Result<UserData, Error>;
+	fn user_ranks(&self, username: &str) -> Result<etterna::UserRank, Error>;
+	fn user_top_scores(
+		&self,
+		username: &str,
+		skillset: etterna::Skillset8,
+		number: u32,
+	) -> Result<Vec<TopScore>, Error>;
+	fn country_leaderboard(&self, country_code: &str) -> Result<Vec<LeaderboardEntry>, Error>;
+	fn global_leaderboard(&self) -> Result<Vec<LeaderboardEntry>, Error>;
+	fn score_data(&self, scorekey: impl AsRef<str>) -> Result<ScoreData, Error>;
+}
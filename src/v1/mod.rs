@@ -1,19 +1,26 @@
+#[cfg(all(feature = "v1", feature = "blocking"))]
+pub mod blocking;
 mod structs;
 pub use structs::*;
+#[cfg(feature = "wire")]
+mod wire;
 
+#[cfg(feature = "v1")]
 use crate::extension_traits::*;
+#[cfg(feature = "v1")]
 use crate::{Error, RequestContext};
 
-fn skillsets_from_eo(json: &serde_json::Value) -> Result<etterna::Skillsets8, Error> {
+#[cfg(feature = "v1")]
+fn skillsets_from_eo(json: &Json<'_>) -> Result<etterna::Skillsets8, Error> {
 	Ok(etterna::Skillsets8 {
-		overall: json["Overall"].parse()?,
-		stream: json["Stream"].parse()?,
-		jumpstream: json["Jumpstream"].parse()?,
-		handstream: json["Handstream"].parse()?,
-		stamina: json["Stamina"].parse()?,
-		jackspeed: json["JackSpeed"].parse()?,
-		chordjack: json["Chordjack"].parse()?,
-		technical: json["Technical"].parse()?,
+		overall: json.at("Overall").parse()?,
+		stream: json.at("Stream").parse()?,
+		jumpstream: json.at("Jumpstream").parse()?,
+		handstream: json.at("Handstream").parse()?,
+		stamina: json.at("Stamina").parse()?,
+		jackspeed: json.at("JackSpeed").parse()?,
+		chordjack: json.at("Chordjack").parse()?,
+		technical: json.at("Technical").parse()?,
 	})
 }
 
@@ -45,14 +52,19 @@ fn skillsets_from_eo(json: &serde_json::Value) -> Result<etterna::Skillsets8, Er
 /// );
 /// # Ok(()) }
 /// ```
+#[cfg(feature = "v1")]
 pub struct Session {
 	api_key: String,
-	cooldown: std::time::Duration,
 	timeout: Option<std::time::Duration>,
-	last_request: std::sync::Mutex<std::time::Instant>,
+	rate_limiter: crate::rate_limit::RateLimiter,
 	http: reqwest::Client,
+	parse_settings: crate::ParseSettings,
+	stats: crate::StatsRecorder,
+	clock: Box<dyn crate::clock::Clock>,
+	client_version: std::sync::Mutex<Option<String>>,
 }
 
+#[cfg(feature = "v1")]
 impl Session {
 	pub fn new(
 		api_key: String,
@@ -61,22 +73,77 @@ impl Session {
 	) -> Self {
 		Self {
 			api_key,
-			cooldown,
 			timeout,
-			last_request: std::sync::Mutex::new(std::time::Instant::now() - cooldown),
+			rate_limiter: crate::rate_limit::RateLimiter::new(cooldown, &crate::clock::RealClock),
 			http: reqwest::Client::new(),
+			parse_settings: crate::ParseSettings::default(),
+			stats: crate::StatsRecorder::default(),
+			clock: Box::new(crate::clock::RealClock),
+			client_version: std::sync::Mutex::new(None),
 		}
 	}
 
+	/// Enables or disables lenient parsing. In lenient mode, entries of a list response that fail
+	/// to parse are skipped and reported to the parse warning callback instead of failing the
+	/// whole request
+	pub fn set_lenient(&mut self, lenient: bool) {
+		self.parse_settings.lenient = lenient;
+	}
+
+	/// Enables or disables raw replay retention. If enabled, parsed replays additionally contain
+	/// the replay rows exactly as EO sent them, in [`Replay::raw`](crate::Replay::raw)
+	pub fn set_keep_raw_replays(&mut self, keep_raw_replays: bool) {
+		self.parse_settings.keep_raw_replays = keep_raw_replays;
+	}
+
+	/// Registers a callback that receives every [`ParseWarning`](crate::ParseWarning), so that you
+	/// can monitor EO for format changes without hard failures
+	pub fn set_parse_warning_callback(
+		&mut self,
+		callback: impl Fn(crate::ParseWarning) + Send + Sync + 'static,
+	) {
+		self.parse_settings.warning_callback = Some(Box::new(callback));
+	}
+
+	/// Persists the rate limiting state using the given store, so that the cooldown carries over
+	/// when the application restarts. If the store contains previously saved state, it is restored
+	/// immediately
+	pub fn set_rate_limit_store(
+		&mut self,
+		store: impl crate::rate_limit::RateLimitStore + 'static,
+	) {
+		self.rate_limiter.set_store(Box::new(store), &*self.clock);
+	}
+
+	/// Reserves request time slots from the given backend instead of limiting the rate
+	/// in-process, so that multiple processes can share one cooldown. The session's own
+	/// cooldown is still passed to the backend
+	pub fn set_rate_limit_backend(
+		&mut self,
+		backend: impl crate::rate_limit::RateLimitBackend + 'static,
+	) {
+		self.rate_limiter.set_backend(Box::new(backend));
+	}
+
+	/// Returns statistics about the requests this session has sent so far
+	pub fn stats(&self) -> crate::RequestStats {
+		self.stats.get()
+	}
+
+	/// Replaces the time source used for rate limiting, e.g. with a
+	/// [`InstantClock`](crate::clock::InstantClock) in tests
+	pub fn set_clock(&mut self, clock: impl crate::clock::Clock + 'static) {
+		self.rate_limiter.set_clock(&clock);
+		self.clock = Box::new(clock);
+	}
+
 	async fn request(
 		&self,
 		path: &str,
 		parameters: &[(&str, &str)],
 		context: RequestContext<'_>,
 	) -> Result<serde_json::Value, Error> {
-		// UNWRAP: propagate panics
-		let rate_limit = crate::rate_limit(self.last_request.lock().unwrap(), self.cooldown);
-		rate_limit.await;
+		self.rate_limiter.wait(&*self.clock).await;
 
 		let mut request = self
 			.http
@@ -87,7 +154,30 @@ impl Session {
 			request = request.timeout(timeout);
 		}
 
-		let json: serde_json::Value = request.send().await?.json().await?;
+		let start = std::time::Instant::now();
+		let result = self.send(request, &context).await;
+		self.stats
+			.record(path, context.user, start.elapsed(), result.as_ref().err());
+		result
+	}
+
+	async fn send(
+		&self,
+		request: reqwest::RequestBuilder,
+		context: &RequestContext<'_>,
+	) -> Result<serde_json::Value, Error> {
+		let response = request.send().await?;
+		let status = response.status();
+		let headers = response.headers().clone();
+		let response = response.text().await?;
+
+		if crate::is_cloudflare_challenge(status, &headers, &response) {
+			return Err(Error::CloudflareChallenge {
+				status_code: status.as_u16(),
+			});
+		}
+
+		let json: serde_json::Value = serde_json::from_str(&response)?;
 
 		if let Some(error) = json["error"].as_str() {
 			return Err(match error {
@@ -123,60 +213,104 @@ impl Session {
 	/// assert_eq!(song.name, "Game Time");
 	/// # Ok(()) }
 	/// ```
-	pub async fn song_data(&self, song_id: u32) -> Result<SongData, Error> {
+	pub async fn song_data(&self, song_id: impl Into<SongId>) -> Result<SongData, Error> {
+		let song_id = song_id.into();
 		let ctx = RequestContext::default();
 		let json = self
 			.request("song", &[("key", song_id.to_string().as_str())], ctx)
 			.await?;
+		let json = Json::new(&json, "");
 		let json = json.singular_array_item()?;
 
-		if json["songkey"].is_null() {
+		if json.at("songkey").is_null() {
 			return Err(Error::SongNotFound);
 		}
 
 		Ok(SongData {
-			songkey: json["songkey"].string()?,
-			id: json["id"].parse()?,
-			name: json["songname"].string()?,
-			subtitle: json["subtitle"].string_maybe()?,
-			author: json["author"].string_maybe()?,
-			artist: json["artist"].string()?,
-			banner_url: json["banner"].string_maybe()?,
-			background_url: json["banner"].string_maybe()?,
-			cdtitle: json["cdtitle"].string_maybe()?,
-			charts: json["charts"]
-				.array()?
-				.iter()
+			songkey: json.at("songkey").string()?,
+			id: json.at("id").parse()?,
+			name: json.at("songname").string()?,
+			subtitle: json.at("subtitle").string_maybe()?,
+			author: json.at("author").string_maybe()?,
+			artist: json.at("artist").string()?,
+			banner_url: json.at("banner").string_maybe()?,
+			background_url: json.at("banner").string_maybe()?,
+			cdtitle: json.at("cdtitle").string_maybe()?,
+			charts: json
+				.at("charts")
+				.elements()?
 				.map(|json| {
 					Ok(SongChartData {
-						chartkey: json["chartkey"].parse()?,
-						msd: json["msd"].parse()?,
-						difficulty: json["difficulty"].parse()?,
-						is_blacklisted: json["blacklisted"].bool_int_string()?,
-						leaderboard: json["leaderboard"]
-							.array()?
-							.iter()
+						chartkey: json.at("chartkey").parse()?,
+						msd: json.at("msd").parse()?,
+						difficulty: json.at("difficulty").parse()?,
+						is_blacklisted: json.at("blacklisted").bool_int_string()?,
+						leaderboard: json
+							.at("leaderboard")
+							.elements()?
 							.map(|json| {
 								Ok(SongChartLeaderboardEntry {
-									username: json["username"].string()?,
-									wifescore: json["wifescore"].wifescore_proportion_string()?,
-									ssr_overall: json["Overall"].f32_()?,
-									rate: json["user_chart_rate_rate"].parse()?,
-									datetime: json["datetime"].string()?,
+									username: json.at("username").string()?,
+									wifescore: json
+										.at("wifescore")
+										.wifescore_proportion_string()?,
+									ssr_overall: json.at("Overall").f32_()?,
+									rate: json.at("user_chart_rate_rate").parse()?,
+									datetime: json.at("datetime").string()?,
 								})
 							})
 							.collect::<Result<Vec<SongChartLeaderboardEntry>, Error>>()?,
 					})
 				})
 				.collect::<Result<Vec<SongChartData>, Error>>()?,
-			packs: json["packs"]
-				.array()?
-				.iter()
+			packs: json
+				.at("packs")
+				.elements()?
 				.map(|v| Ok(v.string()?))
 				.collect::<Result<Vec<String>, Error>>()?,
 		})
 	}
 
+	/// Finds charts among the given songs that appear in more than one pack, e.g. to check
+	/// whether a pack about to be released contains files that are already on EO.
+	///
+	/// EO can't list the charts of a pack, so the songs to check are given by ID. One request is
+	/// made per song. The result is sorted by chartkey.
+	///
+	/// # Errors
+	/// - [`Error::SongNotFound`] if one of the songs doesn't exist
+	pub async fn duplicate_charts(
+		&self,
+		song_ids: &[impl Into<SongId> + Copy],
+	) -> Result<Vec<DuplicateChart>, Error> {
+		let mut charts = std::collections::BTreeMap::<String, DuplicateChart>::new();
+		for &song_id in song_ids {
+			let song = self.song_data(song_id).await?;
+			for chart in song.charts {
+				let duplicate = charts
+					.entry(chart.chartkey.as_str().to_owned())
+					.or_insert_with(|| DuplicateChart {
+						chartkey: chart.chartkey,
+						song_names: Vec::new(),
+						packs: Vec::new(),
+					});
+				if !duplicate.song_names.contains(&song.name) {
+					duplicate.song_names.push(song.name.clone());
+				}
+				for pack in &song.packs {
+					if !duplicate.packs.contains(pack) {
+						duplicate.packs.push(pack.clone());
+					}
+				}
+			}
+		}
+
+		Ok(charts
+			.into_values()
+			.filter(|chart| chart.packs.len() > 1)
+			.collect())
+	}
+
 	/// Retrieves an Etterna version string. I don't know what this specific version string stands
 	/// for. Maybe the minimum version that the site was tested with? I don't know
 	///
@@ -195,6 +329,34 @@ impl Session {
 		Ok(self.request("clientVersion", &[], ctx).await?["version"].string()?)
 	}
 
+	/// Compares the given Etterna version string (e.g. "0.70.1") with the one returned by
+	/// [`Self::client_version`]. The client version is only retrieved once per session
+	///
+	/// # Example
+	/// ```rust,no_run
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
+	/// # use etternaonline_api::v1::*;
+	/// # let mut session: Session = unimplemented!();
+	/// if session.compare_client_version("0.69.0").await? == VersionComparison::Older {
+	/// 	println!("Your Etterna version is outdated!");
+	/// }
+	/// # Ok(()) }
+	/// ```
+	pub async fn compare_client_version(&self, version: &str) -> Result<VersionComparison, Error> {
+		// UNWRAP: propagate panics
+		let cached = self.client_version.lock().unwrap().clone();
+		let client_version = match cached {
+			Some(client_version) => client_version,
+			None => {
+				let client_version = self.client_version().await?;
+				*self.client_version.lock().unwrap() = Some(client_version.clone());
+				client_version
+			}
+		};
+
+		Ok(VersionComparison::compare(version, &client_version))
+	}
+
 	/// Retrieve the link where you can register for an EO account
 	///
 	/// # Example
@@ -230,20 +392,21 @@ impl Session {
 	pub async fn pack_list(&self) -> Result<Vec<PackEntry>, Error> {
 		let ctx = RequestContext::default();
 		let json = self.request("pack_list", &[], ctx).await?;
-		json.array()?
-			.iter()
-			.map(|json| {
+		let json = Json::new(&json, "");
+		self.parse_settings.collect(
+			"pack_list",
+			json.elements()?.map(|json| {
 				Ok(PackEntry {
-					id: json["packid"].u32_()?,
-					name: json["packname"].string()?,
-					average_msd: json["average"].f32_()?,
-					date_added: json["date"].string()?,
-					download_link: json["download"].string()?,
-					download_link_mirror: json["mirror"].string()?,
-					size: FileSize::from_bytes(json["size"].u64_()?),
+					id: PackId(json.at("packid").u32_()?),
+					name: json.at("packname").string()?,
+					average_msd: json.at("average").f32_()?,
+					date_added: json.at("date").string()?,
+					download_link: json.at("download").string()?,
+					download_link_mirror: json.at("mirror").string()?,
+					size: FileSize::from_bytes(json.at("size").u64_()?),
 				})
-			})
-			.collect()
+			}),
+		)
 	}
 
 	/// Retrieves the leaderboard for a chart, which includes the replay data for each leaderboard
@@ -266,44 +429,99 @@ impl Session {
 		&self,
 		chartkey: impl AsRef<str>,
 	) -> Result<Vec<ChartLeaderboardEntry>, Error> {
+		crate::common::validate_chartkey(chartkey.as_ref())?;
 		let ctx = RequestContext::default();
 		let json = self
 			.request("chartLeaderboard", &[("chartkey", chartkey.as_ref())], ctx)
 			.await?;
-		json.array()?
-			.iter()
-			.map(|json| {
+		let json = Json::new(&json, "");
+		self.parse_settings.collect(
+			"chartLeaderboard",
+			json.elements()?.map(|json| {
+				let (replay, replay_raw) = crate::common::parse_replay_with_settings(
+					&json.at("replay"),
+					&self.parse_settings,
+				);
 				Ok(ChartLeaderboardEntry {
 					ssr: skillsets_from_eo(&json)?,
-					wifescore: json["wifescore"].wifescore_proportion_string()?,
-					max_combo: json["maxcombo"].parse()?,
-					is_valid: json["valid"].bool_int_string()?,
-					modifiers: json["modifiers"].string()?,
+					wifescore: json.at("wifescore").wifescore_proportion_string()?,
+					max_combo: json.at("maxcombo").parse()?,
+					is_valid: json.at("valid").bool_int_string()?,
+					modifiers: json.at("modifiers").string()?,
 					judgements: etterna::FullJudgements {
-						marvelouses: json["marv"].parse()?,
-						perfects: json["perfect"].parse()?,
-						greats: json["great"].parse()?,
-						goods: json["good"].parse()?,
-						bads: json["bad"].parse()?,
-						misses: json["miss"].parse()?,
-						hit_mines: json["hitmine"].parse()?,
-						held_holds: json["held"].parse()?,
-						let_go_holds: json["letgo"].parse()?,
-						missed_holds: json["missedhold"].parse()?,
+						marvelouses: json.at("marv").parse()?,
+						perfects: json.at("perfect").parse()?,
+						greats: json.at("great").parse()?,
+						goods: json.at("good").parse()?,
+						bads: json.at("bad").parse()?,
+						misses: json.at("miss").parse()?,
+						hit_mines: json.at("hitmine").parse()?,
+						held_holds: json.at("held").parse()?,
+						let_go_holds: json.at("letgo").parse()?,
+						missed_holds: json.at("missedhold").parse()?,
 					},
-					datetime: json["datetime"].string()?,
-					has_chord_cohesion: !json["nocc"].bool_int_string()?,
-					rate: json["user_chart_rate_rate"].parse()?,
+					datetime: json.at("datetime").string()?,
+					has_chord_cohesion: !json.at("nocc").bool_int_string()?,
+					rate: json.at("user_chart_rate_rate").parse()?,
 					user: User {
-						username: json["username"].string()?,
-						avatar: json["avatar"].string()?,
-						country_code: json["countrycode"].string_maybe()?,
-						rating: json["player_rating"].parse()?,
+						username: json.at("username").string()?,
+						avatar: json.at("avatar").string()?,
+						country_code: json.at("countrycode").string_maybe()?,
+						rating: json.at("player_rating").parse()?,
 					},
-					replay: crate::common::parse_replay(&json["replay"]),
+					replay,
+					replay_raw,
 				})
-			})
-			.collect()
+			}),
+		)
+	}
+
+	/// Retrieves song metadata, MSD and a leaderboard summary of a chart in one go.
+	///
+	/// EO can't look up a song by chartkey, so the ID of the song that the chart belongs to must
+	/// be passed in too. It's available on most scores, for example.
+	///
+	/// # Errors
+	/// - [`Error::SongNotFound`] if the given song doesn't exist
+	/// - [`Error::ChartNotTracked`] if the song has no chart with the given chartkey, or the chart
+	///   is not tracked on EO
+	///
+	/// # Example
+	/// ```rust,no_run
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
+	/// # use etternaonline_api::v1::*;
+	/// # use etterna::*;
+	/// # let mut session: Session = unimplemented!();
+	/// let overview = session.chart_overview(2858, "Xbbff339a2c301d7bf03dc99bc1b013c3b80e75d3").await?;
+	/// println!("{} ({:.2} MSD), played {} times", overview.song_name, overview.msd, overview.num_scores);
+	/// # Ok(()) }
+	/// ```
+	pub async fn chart_overview(
+		&self,
+		song_id: impl Into<SongId>,
+		chartkey: impl AsRef<str>,
+	) -> Result<ChartOverview, Error> {
+		let chartkey = chartkey.as_ref();
+		let song = self.song_data(song_id).await?;
+		let chart = song
+			.charts
+			.into_iter()
+			.find(|chart| chart.chartkey.as_str() == chartkey)
+			.ok_or(Error::ChartNotTracked)?;
+		let leaderboard = self.chart_leaderboard(chartkey).await?;
+
+		Ok(ChartOverview {
+			chartkey: chart.chartkey,
+			song_name: song.name,
+			artist: song.artist,
+			packs: song.packs,
+			difficulty: chart.difficulty,
+			msd: chart.msd,
+			num_scores: leaderboard.len() as u32,
+			top_score: leaderboard
+				.into_iter()
+				.max_by(|a, b| a.ssr.overall.total_cmp(&b.ssr.overall)),
+		})
 	}
 
 	/// Retrieves the user's ten latest scores
@@ -328,18 +546,57 @@ impl Session {
 		let json = self
 			.request("last_user_session", &[("username", username)], ctx)
 			.await?;
+		let json = Json::new(&json, "");
 
-		json.array()?
-			.iter()
-			.map(|json| {
+		self.parse_settings.collect(
+			"last_user_session",
+			json.elements()?.map(|json| {
 				Ok(LatestScore {
-					song_name: json["songname"].string()?,
-					rate: json["user_chart_rate_rate"].parse()?,
-					ssr_overall: json["Overall"].parse()?,
-					wifescore: json["wifescore"].wifescore_proportion_string()?,
+					song_name: json.at("songname").string()?,
+					rate: json.at("user_chart_rate_rate").parse()?,
+					ssr_overall: json.at("Overall").parse()?,
+					wifescore: json.at("wifescore").wifescore_proportion_string()?,
 				})
+			}),
+		)
+	}
+
+	/// Like [`Self::user_latest_10_scores`], but with scorekey, chartkey and difficulty of each
+	/// score, so that details can be fetched or scores deduplicated.
+	///
+	/// The latest scores endpoint doesn't provide the keys, so they are looked up by matching
+	/// song name, rate, wifescore and SSR against all of the user's top scores, at the cost of a
+	/// second request. Scores that aren't among the top scores, e.g. because they're invalid or
+	/// not the best on their chart and rate, are returned without keys.
+	///
+	/// # Errors
+	/// - [`Error::UserNotFound`] if the specified user does not exist
+	pub async fn user_latest_10_scores_with_keys(
+		&self,
+		username: &str,
+	) -> Result<Vec<KeyedLatestScore>, Error> {
+		let latest_scores = self.user_latest_10_scores(username).await?;
+		let top_scores = self
+			.user_top_scores(username, etterna::Skillset8::Overall, 0)
+			.await?;
+
+		Ok(latest_scores
+			.into_iter()
+			.map(|score| {
+				let top_score = top_scores.iter().find(|top| {
+					top.song_name == score.song_name
+						&& top.rate == score.rate
+						&& (top.wifescore.as_proportion() - score.wifescore.as_proportion()).abs()
+							< 0.00001 && (top.ssr_overall - score.ssr_overall).abs() < 0.01
+				});
+				KeyedLatestScore {
+					scorekey: top_score.map(|top| top.scorekey.clone()),
+					chartkey: top_score.map(|top| top.chartkey.clone()),
+					difficulty: top_score.map(|top| top.difficulty),
+					score,
+				}
 			})
-			.collect()
+			.collect())
 	}
 
 	/// Retrieves detailed data about the user
@@ -366,20 +623,21 @@ impl Session {
 		let json = self
 			.request("user_data", &[("username", username)], ctx)
 			.await?;
+		let json = Json::new(&json, "");
 
 		Ok(UserData {
-			user_name: json["username"].string()?,     // "kangalioo"
-			about_me: json["aboutme"].string_maybe()?, // "<p>I'm a very, very mysterious person.</p>"
-			country_code: json["countrycode"].string_maybe()?, // "DE"
-			is_moderator: json["moderator"].bool_int_string()?, // "0"
-			avatar: json["avatar"].string()?,          // "251c375b7c64494a304ea4d3a55afa92.jpg"
-			default_modifiers: json["default_modifiers"].string_maybe()?, // null
+			user_name: json.at("username").string()?,     // "kangalioo"
+			about_me: json.at("aboutme").string_maybe()?, // "<p>I'm a very, very mysterious person.</p>"
+			country_code: json.at("countrycode").string_maybe()?, // "DE"
+			is_moderator: json.at("moderator").bool_int_string()?, // "0"
+			avatar: json.at("avatar").string()?,          // "251c375b7c64494a304ea4d3a55afa92.jpg"
+			default_modifiers: json.at("default_modifiers").string_maybe()?, // null
 			rating: skillsets_from_eo(&json)?,
-			is_patreon: if json["Patreon"].is_null() {
+			is_patreon: if json.at("Patreon").is_null() {
 				// null
 				false
 			} else {
-				json["Patreon"].bool_int_string()?
+				json.at("Patreon").bool_int_string()?
 			},
 		})
 	}
@@ -408,16 +666,17 @@ impl Session {
 		let json = self
 			.request("user_rank", &[("username", username)], ctx)
 			.await?;
+		let json = Json::new(&json, "");
 
 		let user_rank = etterna::UserRank {
-			overall: json["Overall"].parse()?,
-			stream: json["Stream"].parse()?,
-			jumpstream: json["Jumpstream"].parse()?,
-			handstream: json["Handstream"].parse()?,
-			stamina: json["Stamina"].parse()?,
-			jackspeed: json["JackSpeed"].parse()?,
-			chordjack: json["Chordjack"].parse()?,
-			technical: json["Technical"].parse()?,
+			overall: json.at("Overall").parse()?,
+			stream: json.at("Stream").parse()?,
+			jumpstream: json.at("Jumpstream").parse()?,
+			handstream: json.at("Handstream").parse()?,
+			stamina: json.at("Stamina").parse()?,
+			jackspeed: json.at("JackSpeed").parse()?,
+			chordjack: json.at("Chordjack").parse()?,
+			technical: json.at("Technical").parse()?,
 		};
 
 		Ok(user_rank)
@@ -471,21 +730,22 @@ impl Session {
 				},
 			)
 			.await?;
+		let json = Json::new(&json, "");
 
-		json.array()?
-			.iter()
-			.map(|json| {
+		self.parse_settings.collect(
+			"user_top_scores",
+			json.elements()?.map(|json| {
 				Ok(TopScore {
-					song_name: json["songname"].string()?, // "Everytime I hear Your Name"
-					rate: json["user_chart_rate_rate"].parse()?, // "1.40"
-					ssr_overall: json["Overall"].parse()?, // "30.78"
-					wifescore: json["wifescore"].wifescore_proportion_string()?, // "0.96986"
-					chartkey: json["chartkey"].parse()?,   // "X4b537c03eb1f72168f51a0ab92f8a58a62fbe4b4"
-					scorekey: json["scorekey"].parse()?,   // "S11f0f01ab55220ebbf4e0e5ee28d36cce9a72721"
-					difficulty: json["difficulty"].parse()?, // "Hard"
+					song_name: json.at("songname").string()?, // "Everytime I hear Your Name"
+					rate: json.at("user_chart_rate_rate").parse()?, // "1.40"
+					ssr_overall: json.at("Overall").parse()?, // "30.78"
+					wifescore: json.at("wifescore").wifescore_proportion_string()?, // "0.96986"
+					chartkey: json.at("chartkey").parse()?,   // "X4b537c03eb1f72168f51a0ab92f8a58a62fbe4b4"
+					scorekey: json.at("scorekey").parse()?,   // "S11f0f01ab55220ebbf4e0e5ee28d36cce9a72721"
+					difficulty: json.at("difficulty").parse()?, // "Hard"
 				})
-			})
-			.collect()
+			}),
+		)
 	}
 
 	async fn generic_leaderboard(
@@ -494,18 +754,19 @@ impl Session {
 	) -> Result<Vec<LeaderboardEntry>, Error> {
 		let ctx = RequestContext::default();
 		let json = self.request("leaderboard", params, ctx).await?;
+		let json = Json::new(&json, "");
 
-		json.array()?
-			.iter()
-			.map(|json| {
+		self.parse_settings.collect(
+			"leaderboard",
+			json.elements()?.map(|json| {
 				Ok(LeaderboardEntry {
-					username: json["username"].string()?,
-					avatar: json["avatar"].string()?,
-					rating: skillsets_from_eo(json)?,
-					country_code: json["countrycode"].string()?,
+					username: json.at("username").string()?,
+					avatar: json.at("avatar").string()?,
+					rating: skillsets_from_eo(&json)?,
+					country_code: json.at("countrycode").string()?,
 				})
-			})
-			.collect()
+			}),
+		)
 	}
 
 	/// Retrieves the top 10 players in the given country
@@ -560,6 +821,7 @@ impl Session {
 	///
 	/// # Errors
 	/// - [`Error::ScoreNotFound`] if the supplied scorekey was not found
+	/// - [`Error::InvalidScorekey`] if the supplied scorekey is malformed
 	///
 	/// # Example
 	/// ```rust,no_run
@@ -573,44 +835,49 @@ impl Session {
 	/// # Ok(()) }
 	/// ```
 	pub async fn score_data(&self, scorekey: impl AsRef<str>) -> Result<ScoreData, Error> {
+		crate::common::validate_scorekey(scorekey.as_ref())?;
 		let ctx = RequestContext::default();
 		let json = self
 			.request("score", &[("key", scorekey.as_ref())], ctx)
 			.await?;
+		let json = Json::new(&json, "");
 		let json = json.singular_array_item()?;
 
+		let (replay, replay_raw) =
+			crate::common::parse_replay_with_settings(&json.at("replay"), &self.parse_settings);
 		Ok(ScoreData {
 			ssr: skillsets_from_eo(&json)?,
-			wifescore: json["wifescore"].wifescore_proportion_string()?,
-			max_combo: json["maxcombo"].parse()?,
-			is_valid: json["valid"].bool_int_string()?,
-			modifiers: json["modifiers"].string()?,
+			wifescore: json.at("wifescore").wifescore_proportion_string()?,
+			max_combo: json.at("maxcombo").parse()?,
+			is_valid: json.at("valid").bool_int_string()?,
+			modifiers: json.at("modifiers").string()?,
 			judgements: etterna::FullJudgements {
-				marvelouses: json["marv"].parse()?,
-				perfects: json["perfect"].parse()?,
-				greats: json["great"].parse()?,
-				goods: json["good"].parse()?,
-				bads: json["bad"].parse()?,
-				misses: json["miss"].parse()?,
-				hit_mines: json["hitmine"].parse()?,
-				held_holds: json["held"].parse()?,
-				let_go_holds: json["letgo"].parse()?,
-				missed_holds: json["missedhold"].parse()?,
+				marvelouses: json.at("marv").parse()?,
+				perfects: json.at("perfect").parse()?,
+				greats: json.at("great").parse()?,
+				goods: json.at("good").parse()?,
+				bads: json.at("bad").parse()?,
+				misses: json.at("miss").parse()?,
+				hit_mines: json.at("hitmine").parse()?,
+				held_holds: json.at("held").parse()?,
+				let_go_holds: json.at("letgo").parse()?,
+				missed_holds: json.at("missedhold").parse()?,
 			},
-			datetime: json["datetime"].string()?,
-			has_chord_cohesion: !json["nocc"].bool_int_string()?,
-			rate: json["user_chart_rate_rate"].parse()?,
+			datetime: json.at("datetime").string()?,
+			has_chord_cohesion: !json.at("nocc").bool_int_string()?,
+			rate: json.at("user_chart_rate_rate").parse()?,
 			user: User {
-				username: json["username"].string()?,
-				avatar: json["avatar"].string()?,
-				country_code: json["countrycode"].string_maybe()?,
-				rating: json["player_rating"].parse()?,
+				username: json.at("username").string()?,
+				avatar: json.at("avatar").string()?,
+				country_code: json.at("countrycode").string_maybe()?,
+				rating: json.at("player_rating").parse()?,
 			},
-			replay: crate::common::parse_replay(&json["replay"]),
+			replay,
+			replay_raw,
 			song: Song {
-				name: json["songname"].string()?,
-				artist: json["artist"].string()?,
-				id: json["id"].parse()?,
+				name: json.at("songname").string()?,
+				artist: json.at("artist").string()?,
+				id: json.at("id").parse()?,
 			},
 		})
 	}
@@ -631,3 +898,42 @@ login - username: username, password: password
 leaderboard - cc?: country code
 score - key: scorekey
 */
+
+#[cfg(feature = "v1")]
+impl crate::pool::PooledSession for Session {
+	fn time_until_next_request(&self) -> std::time::Duration {
+		self.rate_limiter.time_until_next_slot(&*self.clock)
+	}
+}
+
+#[cfg(feature = "v1")]
+impl crate::session::EoSession for Session {
+	fn user_details<'a>(
+		&'a self,
+		username: &'a str,
+	) -> crate::session::BoxFuture<'a, Result<crate::session::UserProfile, Error>> {
+		Box::pin(async move { Ok(self.user_data(username).await?.into()) })
+	}
+
+	fn score_data<'a>(
+		&'a self,
+		scorekey: &'a str,
+	) -> crate::session::BoxFuture<'a, Result<crate::session::Score, Error>> {
+		Box::pin(async move {
+			let score = Session::score_data(self, scorekey).await?;
+			// UNWRAP: score_data already rejected invalid scorekeys
+			let scorekey = scorekey.parse().unwrap();
+			Ok(crate::session::Score::from_v1(scorekey, score))
+		})
+	}
+
+	fn chart_leaderboard<'a>(
+		&'a self,
+		chartkey: &'a str,
+	) -> crate::session::BoxFuture<'a, Result<Vec<crate::session::LeaderboardScore>, Error>> {
+		Box::pin(async move {
+			let leaderboard = Session::chart_leaderboard(self, chartkey).await?;
+			Ok(leaderboard.into_iter().map(Into::into).collect())
+		})
+	}
+}
@@ -3,15 +3,16 @@ pub use crate::common::structs::*;
 
 use etterna::*;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 #[cfg_attr(
 	feature = "serde",
 	derive(serde::Serialize, serde::Deserialize),
 	serde(crate = "serde_")
 )]
+#[non_exhaustive]
 pub struct SongData {
 	pub songkey: String, // I have no idea what key is this. It has no prefix (??)
-	pub id: u32,
+	pub id: SongId,
 	pub name: String,
 	pub subtitle: Option<String>,
 	pub author: Option<String>,
@@ -58,7 +59,7 @@ pub struct SongChartLeaderboardEntry {
 	serde(crate = "serde_")
 )]
 pub struct PackEntry {
-	pub id: u32,
+	pub id: PackId,
 	pub name: String,
 	pub average_msd: f32,
 	pub date_added: String,
@@ -73,6 +74,14 @@ pub struct PackEntry {
 	derive(serde::Serialize, serde::Deserialize),
 	serde(crate = "serde_")
 )]
+#[cfg_attr(
+	feature = "wire",
+	serde(
+		into = "super::wire::WireChartLeaderboardEntry",
+		try_from = "super::wire::WireChartLeaderboardEntry"
+	)
+)]
+#[non_exhaustive]
 pub struct ChartLeaderboardEntry {
 	pub ssr: Skillsets8,
 	pub wifescore: Wifescore,
@@ -85,6 +94,49 @@ pub struct ChartLeaderboardEntry {
 	pub rate: Rate,
 	pub user: User,
 	pub replay: Option<Replay>,
+	/// The replay exactly as EO sent it, if it was present but couldn't be parsed into
+	/// [`Self::replay`]
+	pub replay_raw: Option<String>,
+}
+
+/// A chart that is contained in more than one pack, see
+/// [`Session::duplicate_charts`](super::Session::duplicate_charts)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct DuplicateChart {
+	pub chartkey: Chartkey,
+	/// Names of the songs that contain the chart, without duplicates
+	pub song_names: Vec<String>,
+	/// Names of the packs that contain the chart, without duplicates
+	pub packs: Vec<String>,
+}
+
+/// Everything needed to describe a chart at a glance, see
+/// [`Session::chart_overview`](super::Session::chart_overview)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct ChartOverview {
+	pub chartkey: Chartkey,
+	pub song_name: String,
+	pub artist: String,
+	/// Names of the packs that contain the song
+	pub packs: Vec<String>,
+	pub difficulty: Difficulty,
+	pub msd: f32,
+	/// The leaderboard score with the highest overall SSR, if anyone has played the chart
+	pub top_score: Option<ChartLeaderboardEntry>,
+	/// Number of scores on the chart leaderboard
+	pub num_scores: u32,
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -106,6 +158,13 @@ pub struct User {
 	derive(serde::Serialize, serde::Deserialize),
 	serde(crate = "serde_")
 )]
+#[cfg_attr(
+	feature = "wire",
+	serde(
+		into = "super::wire::WireLatestScore",
+		try_from = "super::wire::WireLatestScore"
+	)
+)]
 pub struct LatestScore {
 	pub song_name: String,
 	pub rate: Rate,
@@ -113,12 +172,39 @@ pub struct LatestScore {
 	pub wifescore: Wifescore,
 }
 
+/// A [`LatestScore`] with the keys that the latest scores endpoint doesn't provide. See
+/// [`Session::user_latest_10_scores_with_keys`](super::Session::user_latest_10_scores_with_keys)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct KeyedLatestScore {
+	pub score: LatestScore,
+	/// None if no matching score was found among the user's top scores
+	pub scorekey: Option<Scorekey>,
+	/// None if no matching score was found among the user's top scores
+	pub chartkey: Option<Chartkey>,
+	/// None if no matching score was found among the user's top scores
+	pub difficulty: Option<Difficulty>,
+}
+
 #[derive(Debug, Clone, PartialEq, Default)]
 #[cfg_attr(
 	feature = "serde",
 	derive(serde::Serialize, serde::Deserialize),
 	serde(crate = "serde_")
 )]
+#[cfg_attr(
+	feature = "wire",
+	serde(
+		into = "super::wire::WireUserData",
+		try_from = "super::wire::WireUserData"
+	)
+)]
+#[non_exhaustive]
 pub struct UserData {
 	pub user_name: String,
 	pub about_me: Option<String>,
@@ -136,6 +222,14 @@ pub struct UserData {
 	derive(serde::Serialize, serde::Deserialize),
 	serde(crate = "serde_")
 )]
+#[cfg_attr(
+	feature = "wire",
+	serde(
+		into = "super::wire::WireTopScore",
+		try_from = "super::wire::WireTopScore"
+	)
+)]
+#[non_exhaustive]
 pub struct TopScore {
 	pub song_name: String,
 	pub rate: Rate,
@@ -146,12 +240,35 @@ pub struct TopScore {
 	pub difficulty: Difficulty,
 }
 
+impl TopScore {
+	/// Create a top score with the given keys. All other fields are zeroed and can be filled in
+	/// afterwards
+	pub fn new(scorekey: Scorekey, chartkey: Chartkey, difficulty: Difficulty) -> Self {
+		Self {
+			song_name: String::new(),
+			rate: Rate::default(),
+			ssr_overall: 0.0,
+			wifescore: Wifescore::default(),
+			chartkey,
+			scorekey,
+			difficulty,
+		}
+	}
+}
+
 #[derive(Debug, Clone, PartialEq, Default)]
 #[cfg_attr(
 	feature = "serde",
 	derive(serde::Serialize, serde::Deserialize),
 	serde(crate = "serde_")
 )]
+#[cfg_attr(
+	feature = "wire",
+	serde(
+		into = "super::wire::WireLeaderboardEntry",
+		try_from = "super::wire::WireLeaderboardEntry"
+	)
+)]
 pub struct LeaderboardEntry {
 	pub username: String,
 	pub avatar: String,
@@ -165,6 +282,14 @@ pub struct LeaderboardEntry {
 	derive(serde::Serialize, serde::Deserialize),
 	serde(crate = "serde_")
 )]
+#[cfg_attr(
+	feature = "wire",
+	serde(
+		into = "super::wire::WireScoreData",
+		try_from = "super::wire::WireScoreData"
+	)
+)]
+#[non_exhaustive]
 pub struct ScoreData {
 	pub ssr: Skillsets8,
 	pub wifescore: Wifescore,
@@ -177,6 +302,9 @@ pub struct ScoreData {
 	pub rate: Rate,
 	pub user: User,
 	pub replay: Option<Replay>,
+	/// The replay exactly as EO sent it, if it was present but couldn't be parsed into
+	/// [`Self::replay`]
+	pub replay_raw: Option<String>,
 	pub song: Song,
 }
 
@@ -189,5 +317,52 @@ pub struct ScoreData {
 pub struct Song {
 	pub name: String,
 	pub artist: String,
-	pub id: u32,
+	pub id: SongId,
+}
+
+/// How a version compares to EO's client version, see
+/// [`Session::compare_client_version`](super::Session::compare_client_version)
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+pub enum VersionComparison {
+	Older,
+	Same,
+	Newer,
+}
+
+impl VersionComparison {
+	/// Compare two dot-separated version strings like "0.70.1" component by component. Missing
+	/// components count as zero and anything after the leading digits of a component (e.g. a
+	/// "-beta" suffix) is ignored
+	pub fn compare(version: &str, reference: &str) -> Self {
+		fn components(version: &str) -> Vec<u32> {
+			version
+				.trim()
+				.trim_start_matches(&['v', 'V'][..])
+				.split('.')
+				.map(|component| {
+					let digits_end = component
+						.find(|c: char| !c.is_ascii_digit())
+						.unwrap_or(component.len());
+					component[..digits_end].parse().unwrap_or(0)
+				})
+				.collect()
+		}
+
+		let (version, reference) = (components(version), components(reference));
+		for i in 0..version.len().max(reference.len()) {
+			let a = version.get(i).copied().unwrap_or(0);
+			let b = reference.get(i).copied().unwrap_or(0);
+			match a.cmp(&b) {
+				std::cmp::Ordering::Less => return Self::Older,
+				std::cmp::Ordering::Greater => return Self::Newer,
+				std::cmp::Ordering::Equal => {}
+			}
+		}
+		Self::Same
+	}
 }
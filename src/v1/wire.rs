@@ -0,0 +1,573 @@
+//! The v1 response JSON that the structs are (de)serialized as with the `wire` feature. v1 sends
+//! flat objects in which numbers and booleans are strings, e.g. `"1.40"` or `"0"`
+
+use std::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+
+use etterna::{Chartkey, Difficulty, Scorekey};
+
+use super::structs::*;
+use crate::common::wire::*;
+use crate::Error;
+
+/// Same format as EO, e.g. "1.40"
+fn rate_to_eo(rate: etterna::Rate) -> String {
+	let hundredths = rate.as_x20() * 5;
+	format!("{}.{:02}", hundredths / 100, hundredths % 100)
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_")]
+struct Skillsets {
+	#[serde(rename = "Overall")]
+	overall: String,
+	#[serde(rename = "Stream")]
+	stream: String,
+	#[serde(rename = "Jumpstream")]
+	jumpstream: String,
+	#[serde(rename = "Handstream")]
+	handstream: String,
+	#[serde(rename = "Stamina")]
+	stamina: String,
+	#[serde(rename = "JackSpeed")]
+	jackspeed: String,
+	#[serde(rename = "Chordjack")]
+	chordjack: String,
+	#[serde(rename = "Technical")]
+	technical: String,
+}
+
+impl From<&etterna::Skillsets8> for Skillsets {
+	fn from(ssr: &etterna::Skillsets8) -> Self {
+		Self {
+			overall: ssr.overall.to_string(),
+			stream: ssr.stream.to_string(),
+			jumpstream: ssr.jumpstream.to_string(),
+			handstream: ssr.handstream.to_string(),
+			stamina: ssr.stamina.to_string(),
+			jackspeed: ssr.jackspeed.to_string(),
+			chordjack: ssr.chordjack.to_string(),
+			technical: ssr.technical.to_string(),
+		}
+	}
+}
+
+impl TryFrom<Skillsets> for etterna::Skillsets8 {
+	type Error = Error;
+
+	fn try_from(ssr: Skillsets) -> Result<Self, Error> {
+		Ok(Self {
+			overall: parse_str(&ssr.overall)?,
+			stream: parse_str(&ssr.stream)?,
+			jumpstream: parse_str(&ssr.jumpstream)?,
+			handstream: parse_str(&ssr.handstream)?,
+			stamina: parse_str(&ssr.stamina)?,
+			jackspeed: parse_str(&ssr.jackspeed)?,
+			chordjack: parse_str(&ssr.chordjack)?,
+			technical: parse_str(&ssr.technical)?,
+		})
+	}
+}
+
+/// The fields that score data and chart leaderboard entries have in common
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_")]
+struct Score {
+	#[serde(flatten)]
+	skillsets: Skillsets,
+	/// Proportion, not percent
+	wifescore: String,
+	maxcombo: String,
+	valid: String,
+	modifiers: String,
+	marv: String,
+	perfect: String,
+	great: String,
+	good: String,
+	bad: String,
+	miss: String,
+	hitmine: String,
+	held: String,
+	letgo: String,
+	missedhold: String,
+	datetime: String,
+	nocc: String,
+	user_chart_rate_rate: String,
+	username: String,
+	avatar: String,
+	countrycode: Option<String>,
+	player_rating: String,
+	/// Null, the replay string or an array containing the replay string
+	#[serde(default)]
+	replay: serde_json::Value,
+}
+
+/// Fields of [`Score`] in the crate's representation
+struct ParsedScore {
+	ssr: etterna::Skillsets8,
+	wifescore: etterna::Wifescore,
+	max_combo: u32,
+	is_valid: bool,
+	modifiers: String,
+	judgements: etterna::FullJudgements,
+	datetime: String,
+	has_chord_cohesion: bool,
+	rate: etterna::Rate,
+	user: User,
+	replay: Option<Replay>,
+	replay_raw: Option<String>,
+}
+
+impl From<ParsedScore> for Score {
+	fn from(score: ParsedScore) -> Self {
+		let j = &score.judgements;
+		Self {
+			skillsets: (&score.ssr).into(),
+			wifescore: score.wifescore.as_proportion().to_string(),
+			maxcombo: score.max_combo.to_string(),
+			valid: bool_to_int_str(score.is_valid),
+			marv: j.marvelouses.to_string(),
+			perfect: j.perfects.to_string(),
+			great: j.greats.to_string(),
+			good: j.goods.to_string(),
+			bad: j.bads.to_string(),
+			miss: j.misses.to_string(),
+			hitmine: j.hit_mines.to_string(),
+			held: j.held_holds.to_string(),
+			letgo: j.let_go_holds.to_string(),
+			missedhold: j.missed_holds.to_string(),
+			nocc: bool_to_int_str(!score.has_chord_cohesion),
+			user_chart_rate_rate: rate_to_eo(score.rate),
+			player_rating: score.user.rating.to_string(),
+			replay: replay_to_eo(score.replay.as_ref(), score.replay_raw.as_deref()),
+			modifiers: score.modifiers,
+			datetime: score.datetime,
+			username: score.user.username,
+			avatar: score.user.avatar,
+			countrycode: score.user.country_code,
+		}
+	}
+}
+
+impl TryFrom<Score> for ParsedScore {
+	type Error = Error;
+
+	fn try_from(score: Score) -> Result<Self, Error> {
+		let (replay, replay_raw) = crate::common::parse_replay(&score.replay);
+		Ok(Self {
+			ssr: etterna::Skillsets8::try_from(score.skillsets)?,
+			wifescore: wifescore_from_proportion(parse_str(&score.wifescore)?)?,
+			max_combo: parse_str(&score.maxcombo)?,
+			is_valid: bool_from_int_str(&score.valid)?,
+			modifiers: score.modifiers,
+			judgements: etterna::FullJudgements {
+				marvelouses: parse_str(&score.marv)?,
+				perfects: parse_str(&score.perfect)?,
+				greats: parse_str(&score.great)?,
+				goods: parse_str(&score.good)?,
+				bads: parse_str(&score.bad)?,
+				misses: parse_str(&score.miss)?,
+				hit_mines: parse_str(&score.hitmine)?,
+				held_holds: parse_str(&score.held)?,
+				let_go_holds: parse_str(&score.letgo)?,
+				missed_holds: parse_str(&score.missedhold)?,
+			},
+			datetime: score.datetime,
+			has_chord_cohesion: !bool_from_int_str(&score.nocc)?,
+			rate: parse_str(&score.user_chart_rate_rate)?,
+			user: User {
+				username: score.username,
+				avatar: score.avatar,
+				country_code: score.countrycode,
+				rating: parse_str(&score.player_rating)?,
+			},
+			replay,
+			replay_raw,
+		})
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_")]
+pub(crate) struct WireScoreData {
+	#[serde(flatten)]
+	score: Score,
+	songname: String,
+	artist: String,
+	id: String,
+}
+
+impl From<ScoreData> for WireScoreData {
+	fn from(score: ScoreData) -> Self {
+		Self {
+			songname: score.song.name,
+			artist: score.song.artist,
+			id: score.song.id.to_string(),
+			score: ParsedScore {
+				ssr: score.ssr,
+				wifescore: score.wifescore,
+				max_combo: score.max_combo,
+				is_valid: score.is_valid,
+				modifiers: score.modifiers,
+				judgements: score.judgements,
+				datetime: score.datetime,
+				has_chord_cohesion: score.has_chord_cohesion,
+				rate: score.rate,
+				user: score.user,
+				replay: score.replay,
+				replay_raw: score.replay_raw,
+			}
+			.into(),
+		}
+	}
+}
+
+impl TryFrom<WireScoreData> for ScoreData {
+	type Error = Error;
+
+	fn try_from(wire: WireScoreData) -> Result<Self, Error> {
+		let score = ParsedScore::try_from(wire.score)?;
+		Ok(Self {
+			ssr: score.ssr,
+			wifescore: score.wifescore,
+			max_combo: score.max_combo,
+			is_valid: score.is_valid,
+			modifiers: score.modifiers,
+			judgements: score.judgements,
+			datetime: score.datetime,
+			has_chord_cohesion: score.has_chord_cohesion,
+			rate: score.rate,
+			user: score.user,
+			replay: score.replay,
+			replay_raw: score.replay_raw,
+			song: Song {
+				name: wire.songname,
+				artist: wire.artist,
+				id: parse_str(&wire.id)?,
+			},
+		})
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_")]
+pub(crate) struct WireChartLeaderboardEntry(Score);
+
+impl From<ChartLeaderboardEntry> for WireChartLeaderboardEntry {
+	fn from(entry: ChartLeaderboardEntry) -> Self {
+		Self(
+			ParsedScore {
+				ssr: entry.ssr,
+				wifescore: entry.wifescore,
+				max_combo: entry.max_combo,
+				is_valid: entry.is_valid,
+				modifiers: entry.modifiers,
+				judgements: entry.judgements,
+				datetime: entry.datetime,
+				has_chord_cohesion: entry.has_chord_cohesion,
+				rate: entry.rate,
+				user: entry.user,
+				replay: entry.replay,
+				replay_raw: entry.replay_raw,
+			}
+			.into(),
+		)
+	}
+}
+
+impl TryFrom<WireChartLeaderboardEntry> for ChartLeaderboardEntry {
+	type Error = Error;
+
+	fn try_from(
+		WireChartLeaderboardEntry(score): WireChartLeaderboardEntry,
+	) -> Result<Self, Error> {
+		let score = ParsedScore::try_from(score)?;
+		Ok(Self {
+			ssr: score.ssr,
+			wifescore: score.wifescore,
+			max_combo: score.max_combo,
+			is_valid: score.is_valid,
+			modifiers: score.modifiers,
+			judgements: score.judgements,
+			datetime: score.datetime,
+			has_chord_cohesion: score.has_chord_cohesion,
+			rate: score.rate,
+			user: score.user,
+			replay: score.replay,
+			replay_raw: score.replay_raw,
+		})
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_")]
+pub(crate) struct WireUserData {
+	username: String,
+	aboutme: Option<String>,
+	countrycode: Option<String>,
+	moderator: String,
+	avatar: String,
+	default_modifiers: Option<String>,
+	#[serde(flatten)]
+	skillsets: Skillsets,
+	/// Null or "0" if the user isn't a patron
+	#[serde(rename = "Patreon")]
+	patreon: Option<String>,
+}
+
+impl From<UserData> for WireUserData {
+	fn from(user: UserData) -> Self {
+		Self {
+			skillsets: (&user.rating).into(),
+			username: user.user_name,
+			aboutme: user.about_me,
+			countrycode: user.country_code,
+			moderator: bool_to_int_str(user.is_moderator),
+			avatar: user.avatar,
+			default_modifiers: user.default_modifiers,
+			patreon: if user.is_patreon {
+				Some(bool_to_int_str(true))
+			} else {
+				None
+			},
+		}
+	}
+}
+
+impl TryFrom<WireUserData> for UserData {
+	type Error = Error;
+
+	fn try_from(user: WireUserData) -> Result<Self, Error> {
+		Ok(Self {
+			rating: etterna::Skillsets8::try_from(user.skillsets)?,
+			user_name: user.username,
+			about_me: user.aboutme,
+			country_code: user.countrycode,
+			is_moderator: bool_from_int_str(&user.moderator)?,
+			avatar: user.avatar,
+			default_modifiers: user.default_modifiers,
+			is_patreon: match &user.patreon {
+				Some(patreon) => bool_from_int_str(patreon)?,
+				None => false,
+			},
+		})
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_")]
+pub(crate) struct WireTopScore {
+	songname: String,
+	user_chart_rate_rate: String,
+	#[serde(rename = "Overall")]
+	overall: String,
+	/// Proportion, not percent
+	wifescore: String,
+	chartkey: Chartkey,
+	scorekey: Scorekey,
+	difficulty: Difficulty,
+}
+
+impl From<TopScore> for WireTopScore {
+	fn from(score: TopScore) -> Self {
+		Self {
+			songname: score.song_name,
+			user_chart_rate_rate: rate_to_eo(score.rate),
+			overall: score.ssr_overall.to_string(),
+			wifescore: score.wifescore.as_proportion().to_string(),
+			chartkey: score.chartkey,
+			scorekey: score.scorekey,
+			difficulty: score.difficulty,
+		}
+	}
+}
+
+impl TryFrom<WireTopScore> for TopScore {
+	type Error = Error;
+
+	fn try_from(score: WireTopScore) -> Result<Self, Error> {
+		Ok(Self {
+			song_name: score.songname,
+			rate: parse_str(&score.user_chart_rate_rate)?,
+			ssr_overall: parse_str(&score.overall)?,
+			wifescore: wifescore_from_proportion(parse_str(&score.wifescore)?)?,
+			chartkey: score.chartkey,
+			scorekey: score.scorekey,
+			difficulty: score.difficulty,
+		})
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_")]
+pub(crate) struct WireLatestScore {
+	songname: String,
+	user_chart_rate_rate: String,
+	#[serde(rename = "Overall")]
+	overall: String,
+	/// Proportion, not percent
+	wifescore: String,
+}
+
+impl From<LatestScore> for WireLatestScore {
+	fn from(score: LatestScore) -> Self {
+		Self {
+			songname: score.song_name,
+			user_chart_rate_rate: rate_to_eo(score.rate),
+			overall: score.ssr_overall.to_string(),
+			wifescore: score.wifescore.as_proportion().to_string(),
+		}
+	}
+}
+
+impl TryFrom<WireLatestScore> for LatestScore {
+	type Error = Error;
+
+	fn try_from(score: WireLatestScore) -> Result<Self, Error> {
+		Ok(Self {
+			song_name: score.songname,
+			rate: parse_str(&score.user_chart_rate_rate)?,
+			ssr_overall: parse_str(&score.overall)?,
+			wifescore: wifescore_from_proportion(parse_str(&score.wifescore)?)?,
+		})
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_")]
+pub(crate) struct WireLeaderboardEntry {
+	username: String,
+	avatar: String,
+	#[serde(flatten)]
+	skillsets: Skillsets,
+	countrycode: String,
+}
+
+impl From<LeaderboardEntry> for WireLeaderboardEntry {
+	fn from(entry: LeaderboardEntry) -> Self {
+		Self {
+			skillsets: (&entry.rating).into(),
+			username: entry.username,
+			avatar: entry.avatar,
+			countrycode: entry.country_code,
+		}
+	}
+}
+
+impl TryFrom<WireLeaderboardEntry> for LeaderboardEntry {
+	type Error = Error;
+
+	fn try_from(entry: WireLeaderboardEntry) -> Result<Self, Error> {
+		Ok(Self {
+			rating: etterna::Skillsets8::try_from(entry.skillsets)?,
+			username: entry.username,
+			avatar: entry.avatar,
+			country_code: entry.countrycode,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Deserializes the sample, checks that it serializes back to the same JSON and returns the
+	/// deserialized value
+	fn round_trip<T>(json: serde_json::Value) -> T
+	where
+		T: Serialize + serde::de::DeserializeOwned,
+	{
+		let value = serde_json::from_value::<T>(json.clone()).unwrap();
+		assert_eq!(serde_json::to_value(&value).unwrap(), json);
+		value
+	}
+
+	fn with_skillsets(json: serde_json::Value) -> serde_json::Value {
+		let mut json = json.as_object().unwrap().clone();
+		json.extend(
+			serde_json::json!({
+				"Overall": "25.5",
+				"Stream": "24.25",
+				"Jumpstream": "23.75",
+				"Handstream": "24.5",
+				"Stamina": "22.5",
+				"JackSpeed": "20",
+				"Chordjack": "23",
+				"Technical": "21.25",
+			})
+			.as_object()
+			.unwrap()
+			.clone(),
+		);
+		json.into()
+	}
+
+	#[test]
+	fn test_user_data() {
+		// The values quoted in `Session::user_data`
+		let user = round_trip::<UserData>(with_skillsets(serde_json::json!({
+			"username": "kangalioo",
+			"aboutme": "<p>I'm a very, very mysterious person.</p>",
+			"countrycode": "DE",
+			"moderator": "0",
+			"avatar": "251c375b7c64494a304ea4d3a55afa92.jpg",
+			"default_modifiers": null,
+			"Patreon": null,
+		})));
+		assert_eq!(user.country_code.as_deref(), Some("DE"));
+		assert!(!user.is_moderator);
+		assert!(!user.is_patreon);
+		assert_eq!(user.rating.overall, 25.5);
+	}
+
+	#[test]
+	fn test_top_score() {
+		// The values quoted in `Session::user_top_scores`
+		let score = round_trip::<TopScore>(serde_json::json!({
+			"songname": "Everytime I hear Your Name",
+			"user_chart_rate_rate": "1.40",
+			"Overall": "30.78",
+			"wifescore": "0.96986",
+			"chartkey": "X4b537c03eb1f72168f51a0ab92f8a58a62fbe4b4",
+			"scorekey": "S11f0f01ab55220ebbf4e0e5ee28d36cce9a72721",
+			"difficulty": "Hard",
+		}));
+		assert_eq!(score.rate.as_x20(), 28);
+		assert_eq!(score.difficulty, Difficulty::Hard);
+	}
+
+	#[test]
+	fn test_score_data() {
+		let score = round_trip::<ScoreData>(with_skillsets(serde_json::json!({
+			"wifescore": "0.875",
+			"maxcombo": "1026",
+			"valid": "1",
+			"modifiers": "C900",
+			"marv": "1000",
+			"perfect": "200",
+			"great": "30",
+			"good": "4",
+			"bad": "2",
+			"miss": "1",
+			"hitmine": "0",
+			"held": "50",
+			"letgo": "1",
+			"missedhold": "0",
+			"datetime": "2020-06-29 18:54:00",
+			"nocc": "1",
+			"user_chart_rate_rate": "1.00",
+			"username": "kangalioo",
+			"avatar": "251c375b7c64494a304ea4d3a55afa92.jpg",
+			"countrycode": "DE",
+			"player_rating": "25.5",
+			"replay": null,
+			"songname": "Game Time",
+			"artist": "Bill Nye",
+			"id": "1234",
+		})));
+		assert!(!score.has_chord_cohesion);
+		assert_eq!(score.max_combo, 1026);
+		assert_eq!(score.song.id, SongId(1234));
+	}
+}
@@ -1,13 +1,28 @@
 mod structs;
 pub use structs::*;
+#[cfg(feature = "wire")]
+mod wire;
 
+#[cfg(feature = "v2")]
 use etterna::*;
 
+#[cfg(feature = "v2")]
 use crate::extension_traits::*;
+#[cfg(feature = "v2")]
 use crate::{Error, RequestContext};
 
+#[cfg(feature = "v2")]
 type BoxFuture<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send + 'a>>;
+#[cfg(feature = "v2")]
+type AuthFailureCallback = Box<dyn Fn(&Error) + Send + Sync>;
+#[cfg(feature = "v2")]
+type AuditCallback = Box<dyn Fn(AuditEntry) + Send + Sync>;
 
+/// Form parameters whose values are replaced in [`AuditEntry::parameters`]
+#[cfg(feature = "v2")]
+const SECRET_PARAMETERS: &[&str] = &["password", "clientData"];
+
+#[cfg(feature = "v2")]
 fn difficulty_from_eo(string: &str) -> Result<etterna::Difficulty, Error> {
 	Ok(match string {
 		"Beginner" => Difficulty::Beginner,
@@ -25,21 +40,310 @@ fn difficulty_from_eo(string: &str) -> Result<etterna::Difficulty, Error> {
 	})
 }
 
-fn parse_judgements(json: &serde_json::Value) -> Result<etterna::FullJudgements, Error> {
+/// Extracts the payload of a JSON:API response
+#[cfg(feature = "v2")]
+fn response_data(response: &str) -> Result<serde_json::Value, Error> {
+	let mut json: serde_json::Value = serde_json::from_str(response)?;
+	Ok(json["data"].take())
+}
+
+/// Extracts the path of the next page from a JSON:API `links` object. EO sends absolute URLs;
+/// links to other hosts are rejected so that credentials are never sent elsewhere
+#[cfg(feature = "v2")]
+fn next_page_path(response: &serde_json::Value) -> Result<Option<String>, Error> {
+	const API_BASE: &str = "https://api.etternaonline.com/v2/";
+
+	let next = match response["links"]["next"].as_str() {
+		Some(next) if !next.is_empty() => next,
+		_ => return Ok(None),
+	};
+	match next.strip_prefix(API_BASE) {
+		Some(path) => Ok(Some(path.to_owned())),
+		None => Err(Error::InvalidDataStructure(format!(
+			"Pagination link points outside of the API: {}",
+			next
+		))),
+	}
+}
+
+#[cfg(feature = "v2")]
+fn parse_judgements(json: &Json<'_>) -> Result<etterna::FullJudgements, Error> {
 	Ok(etterna::FullJudgements {
-		marvelouses: json["marvelous"].u32_()?,
-		perfects: json["perfect"].u32_()?,
-		greats: json["great"].u32_()?,
-		goods: json["good"].u32_()?,
-		bads: json["bad"].u32_()?,
-		misses: json["miss"].u32_()?,
-		hit_mines: json["hitMines"].u32_()?,
-		held_holds: json["heldHold"].u32_()?,
-		let_go_holds: json["letGoHold"].u32_()?,
-		missed_holds: json["missedHold"].u32_()?,
+		marvelouses: json.at("marvelous").u32_()?,
+		perfects: json.at("perfect").u32_()?,
+		greats: json.at("great").u32_()?,
+		goods: json.at("good").u32_()?,
+		bads: json.at("bad").u32_()?,
+		misses: json.at("miss").u32_()?,
+		hit_mines: json.at("hitMines").u32_()?,
+		held_holds: json.at("heldHold").u32_()?,
+		let_go_holds: json.at("letGoHold").u32_()?,
+		missed_holds: json.at("missedHold").u32_()?,
 	})
 }
 
+/// Parses an element of the country/world leaderboard response
+#[cfg(feature = "v2")]
+fn parse_leaderboard_entry(json: &Json<'_>) -> Result<LeaderboardEntry, Error> {
+	Ok(LeaderboardEntry {
+		user: ScoreUser {
+			username: json.at("attributes").at("user").at("username").string()?,
+			avatar: json.at("attributes").at("user").at("avatar").string()?,
+			country_code: json
+				.at("attributes")
+				.at("user")
+				.at("countryCode")
+				.string()?,
+			overall_rating: json.at("attributes").at("user").at("Overall").f32_()?,
+		},
+		rating: etterna::Skillsets8 {
+			overall: json.at("attributes").at("user").at("Overall").f32_()?,
+			stream: json.at("attributes").at("skillsets").at("Stream").f32_()?,
+			jumpstream: json
+				.at("attributes")
+				.at("skillsets")
+				.at("Jumpstream")
+				.f32_()?,
+			handstream: json
+				.at("attributes")
+				.at("skillsets")
+				.at("Handstream")
+				.f32_()?,
+			stamina: json.at("attributes").at("skillsets").at("Stamina").f32_()?,
+			jackspeed: json
+				.at("attributes")
+				.at("skillsets")
+				.at("JackSpeed")
+				.f32_()?,
+			chordjack: json
+				.at("attributes")
+				.at("skillsets")
+				.at("Chordjack")
+				.f32_()?,
+			technical: json
+				.at("attributes")
+				.at("skillsets")
+				.at("Technical")
+				.f32_()?,
+		},
+	})
+}
+
+#[cfg(feature = "v2")]
+impl PageCursor<LeaderboardEntry> {
+	/// Restore a cursor from its [path](Self::path), e.g. one that was saved before a crawl of the
+	/// player leaderboard was interrupted
+	pub fn from_path(path: String) -> Self {
+		Self {
+			path,
+			parse_item: parse_leaderboard_entry,
+		}
+	}
+}
+
+/// Leaderboard cursors are (de)serialized as their path
+#[cfg(all(feature = "v2", feature = "serde"))]
+impl serde::Serialize for PageCursor<LeaderboardEntry> {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&self.path)
+	}
+}
+
+#[cfg(all(feature = "v2", feature = "serde"))]
+impl<'de> serde::Deserialize<'de> for PageCursor<LeaderboardEntry> {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		<String as serde::Deserialize>::deserialize(deserializer).map(Self::from_path)
+	}
+}
+
+/// Parses an element of the chart leaderboard response
+#[cfg(feature = "v2")]
+fn parse_chart_leaderboard_score(json: &Json<'_>) -> Result<ChartLeaderboardScore, Error> {
+	Ok(ChartLeaderboardScore {
+		scorekey: json.at("id").parse()?,
+		wifescore: json.at("attributes").at("wife").wifescore_percent_float()?,
+		max_combo: json.at("attributes").at("maxCombo").u32_()?,
+		is_valid: json.at("attributes").at("valid").bool_()?,
+		modifiers: json.at("attributes").at("modifiers").string()?,
+		has_chord_cohesion: !json.at("attributes").at("noCC").bool_()?,
+		rate: json.at("attributes").at("rate").rate_float()?,
+		datetime: json.at("attributes").at("datetime").string()?,
+		ssr: etterna::Skillsets8 {
+			overall: json.at("attributes").at("skillsets").at("Overall").f32_()?,
+			stream: json.at("attributes").at("skillsets").at("Stream").f32_()?,
+			jumpstream: json
+				.at("attributes")
+				.at("skillsets")
+				.at("Jumpstream")
+				.f32_()?,
+			handstream: json
+				.at("attributes")
+				.at("skillsets")
+				.at("Handstream")
+				.f32_()?,
+			stamina: json.at("attributes").at("skillsets").at("Stamina").f32_()?,
+			jackspeed: json
+				.at("attributes")
+				.at("skillsets")
+				.at("JackSpeed")
+				.f32_()?,
+			chordjack: json
+				.at("attributes")
+				.at("skillsets")
+				.at("Chordjack")
+				.f32_()?,
+			technical: json
+				.at("attributes")
+				.at("skillsets")
+				.at("Technical")
+				.f32_()?,
+		},
+		judgements: parse_judgements(&json.at("attributes").at("judgements"))?,
+		has_replay: json.at("attributes").at("hasReplay").bool_()?, // API docs are wrong again
+		user: ScoreUser {
+			username: json.at("attributes").at("user").at("userName").string()?,
+			avatar: json.at("attributes").at("user").at("avatar").string()?,
+			country_code: json
+				.at("attributes")
+				.at("user")
+				.at("countryCode")
+				.string()?,
+			overall_rating: json.at("attributes").at("user").at("playerRating").f32_()?,
+		},
+		#[cfg(feature = "extra-fields")]
+		extra: json.at("attributes").unknown_fields(&[
+			"wife",
+			"maxCombo",
+			"valid",
+			"modifiers",
+			"noCC",
+			"rate",
+			"datetime",
+			"skillsets",
+			"judgements",
+			"hasReplay",
+			"user",
+		]),
+	})
+}
+
+/// After this many consecutive rejected logins, the session stops trying and returns
+/// [`Error::CredentialsRejected`]
+#[cfg(feature = "v2")]
+const MAX_LOGIN_FAILURES: u32 = 5;
+/// Wait time after the first rejected login. Doubles with every further rejected login
+#[cfg(feature = "v2")]
+const LOGIN_BACKOFF_BASE: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Chart leaderboards that were retrieved recently, keyed by chartkey
+#[cfg(feature = "v2")]
+struct LeaderboardCache {
+	ttl: std::time::Duration,
+	entries: std::sync::Mutex<
+		std::collections::HashMap<String, (std::time::Instant, Vec<ChartLeaderboardScore>)>,
+	>,
+}
+
+/// Makes sure that only one re-login happens at a time, even if many concurrent requests notice an
+/// expired token at once. Every finished login attempt increments the generation
+#[cfg(feature = "v2")]
+#[derive(Default)]
+struct LoginFlight {
+	state: std::sync::Mutex<LoginFlightState>,
+}
+
+#[cfg(feature = "v2")]
+#[derive(Default)]
+struct LoginFlightState {
+	generation: u64,
+	in_flight: bool,
+	waiters: Vec<std::task::Waker>,
+}
+
+#[cfg(feature = "v2")]
+impl LoginFlight {
+	fn generation(&self) -> u64 {
+		// UNWRAP: propagate panics
+		self.state.lock().unwrap().generation
+	}
+}
+
+/// Resolves to true if the caller should log in, or to false if another login has finished since
+/// `seen_generation`
+#[cfg(feature = "v2")]
+struct LoginFlightAcquire<'a> {
+	flight: &'a LoginFlight,
+	seen_generation: u64,
+}
+
+#[cfg(feature = "v2")]
+impl std::future::Future for LoginFlightAcquire<'_> {
+	type Output = bool;
+
+	fn poll(
+		self: std::pin::Pin<&mut Self>,
+		cx: &mut std::task::Context<'_>,
+	) -> std::task::Poll<bool> {
+		// UNWRAP: propagate panics
+		let mut state = self.flight.state.lock().unwrap();
+		if state.generation > self.seen_generation {
+			std::task::Poll::Ready(false)
+		} else if !state.in_flight {
+			state.in_flight = true;
+			std::task::Poll::Ready(true)
+		} else {
+			state.waiters.push(cx.waker().clone());
+			std::task::Poll::Pending
+		}
+	}
+}
+
+/// Marks the login as finished when dropped, also if the login future is cancelled
+#[cfg(feature = "v2")]
+struct LoginFlightGuard<'a>(&'a LoginFlight);
+
+#[cfg(feature = "v2")]
+impl Drop for LoginFlightGuard<'_> {
+	fn drop(&mut self) {
+		// UNWRAP: propagate panics
+		let mut state = self.0.state.lock().unwrap();
+		state.generation += 1;
+		state.in_flight = false;
+		for waker in state.waiters.drain(..) {
+			waker.wake();
+		}
+	}
+}
+
+/// Extracts the form parameters of a request for the audit log, with secrets redacted
+#[cfg(feature = "v2")]
+fn audit_parameters(request: &reqwest::RequestBuilder) -> Vec<(String, String)> {
+	let request = match request.try_clone().map(|request| request.build()) {
+		Some(Ok(request)) => request,
+		_ => return Vec::new(),
+	};
+	let body = match request.body().and_then(|body| body.as_bytes()) {
+		Some(body) => String::from_utf8_lossy(body).into_owned(),
+		None => return Vec::new(),
+	};
+
+	// Let the URL parser do the decoding of the form body
+	let url = match reqwest::Url::parse(&format!("http://localhost/?{}", body)) {
+		Ok(url) => url,
+		Err(_) => return Vec::new(),
+	};
+	url.query_pairs()
+		.map(|(key, value)| {
+			let value = if SECRET_PARAMETERS.contains(&&*key) {
+				"<redacted>".to_owned()
+			} else {
+				value.into_owned()
+			};
+			(key.into_owned(), value)
+		})
+		.collect()
+}
+
 /// EtternaOnline API session client, handles all requests to and from EtternaOnline.
 ///
 /// This wrapper keeps care of expiring tokens by automatically logging back in when the login
@@ -52,7 +356,7 @@ fn parse_judgements(json: &serde_json::Value) -> Result<etterna::FullJudgements,
 ///
 /// # Example
 /// ```rust,no_run
-/// # fn main() -> Result<(), etternaonline_api::Error> {
+/// # async fn f() -> Result<(), etternaonline_api::Error> {
 /// # use etternaonline_api::v2::*;
 /// let mut session = Session::new_from_login(
 /// 	"<USERNAME>".into(),
@@ -60,17 +364,18 @@ fn parse_judgements(json: &serde_json::Value) -> Result<etterna::FullJudgements,
 /// 	"<CLIENT_DATA>".into(),
 /// 	std::time::Duration::from_millis(2000), // Wait 2s inbetween requests
 /// 	None, // No request timeout
-/// )?;
+/// ).await?;
 ///
-/// println!("Details about kangalioo: {:?}", session.user_details("kangalioo")?);
+/// println!("Details about kangalioo: {:?}", session.user_details("kangalioo").await?);
 ///
-/// let best_score = &session.user_top_10_scores("kangalioo")?[0];
+/// let best_score = &session.user_top_10_scores("kangalioo").await?[0];
 /// println!(
 /// 	"kangalioo's best score has {} misses",
-/// 	session.score_data(&best_score.scorekey)?.judgements.misses
+/// 	session.score_data(&best_score.scorekey).await?.judgements.misses
 /// );
 /// # Ok(()) }
 /// ```
+#[cfg(feature = "v2")]
 pub struct Session {
 	// This stuff is needed for re-login
 	username: String,
@@ -79,15 +384,31 @@ pub struct Session {
 
 	// The auth key that we get from the server on login
 	authorization: std::sync::Mutex<Option<String>>,
+	// Set by new_from_login_tolerant if the initial login failed
+	initial_login_error: std::sync::Mutex<Option<Error>>,
+	login_flight: LoginFlight,
+	// Number of consecutive logins that were rejected by the server
+	login_failures: std::sync::Mutex<u32>,
+	last_login_attempt: std::sync::Mutex<Option<std::time::Instant>>,
+	last_login: std::sync::Mutex<Option<std::time::Instant>>,
+	// How long the last token lasted until the server rejected it
+	observed_token_lifetime: std::sync::Mutex<Option<std::time::Duration>>,
+	auth_failure_callback: Option<AuthFailureCallback>,
+	audit_callback: Option<AuditCallback>,
+	// Only present if enabled via set_leaderboard_cache_ttl
+	leaderboard_cache: Option<LeaderboardCache>,
 
 	// Rate limiting stuff
-	last_request: std::sync::Mutex<std::time::Instant>,
-	cooldown: std::time::Duration,
+	rate_limiter: crate::rate_limit::RateLimiter,
 
 	http: reqwest::Client,
 	timeout: Option<std::time::Duration>,
+	parse_settings: crate::ParseSettings,
+	stats: crate::StatsRecorder,
+	clock: Box<dyn crate::clock::Clock>,
 }
 
+#[cfg(feature = "v2")]
 impl Session {
 	/// Initiate a new session by logging in using the specified credentials and API token.
 	///
@@ -96,9 +417,13 @@ impl Session {
 	/// # Errors
 	/// - [`Error::InvalidLogin`] if username or password are wrong
 	///
+	/// Rejected logins are retried with increasing delays. After five rejected logins in a row,
+	/// requests fail with [`Error::CredentialsRejected`] without contacting EO, until
+	/// [`Self::reset_login_failures`] is called
+	///
 	/// # Example
 	/// ```rust,no_run
-	/// # fn main() -> Result<(), etternaonline_api::Error> {
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
 	/// # use etternaonline_api::v2::*;
 	/// let mut session = Session::new_from_login(
 	/// 	"kangalioo".into(),
@@ -106,9 +431,9 @@ impl Session {
 	/// 	"<CLIENT_DATA>".into(),
 	/// 	std::time::Duration::from_millis(2000), // wait 2s inbetween requests
 	/// 	None, // no timeout
-	/// )?;
+	/// ).await?;
 	///
-	/// println!("Details about kangalioo: {:?}", session.user_details("kangalioo"));
+	/// println!("Details about kangalioo: {:?}", session.user_details("kangalioo").await?);
 	/// # Ok(()) }
 	/// ```
 	pub async fn new_from_login(
@@ -118,27 +443,244 @@ impl Session {
 		cooldown: std::time::Duration,
 		timeout: Option<std::time::Duration>,
 	) -> Result<Self, Error> {
-		let session = Self {
+		let session = Self::new_logged_out(username, password, client_data, cooldown, timeout);
+		session.login().await?;
+
+		Ok(session)
+	}
+
+	fn new_logged_out(
+		username: String,
+		password: String,
+		client_data: String,
+		cooldown: std::time::Duration,
+		timeout: Option<std::time::Duration>,
+	) -> Self {
+		Self {
 			username,
 			password,
 			client_data,
-			cooldown,
 			timeout,
 			authorization: std::sync::Mutex::new(None),
-			last_request: std::sync::Mutex::new(std::time::Instant::now() - cooldown),
+			initial_login_error: std::sync::Mutex::new(None),
+			login_flight: LoginFlight::default(),
+			login_failures: std::sync::Mutex::new(0),
+			last_login_attempt: std::sync::Mutex::new(None),
+			last_login: std::sync::Mutex::new(None),
+			observed_token_lifetime: std::sync::Mutex::new(None),
+			auth_failure_callback: None,
+			audit_callback: None,
+			leaderboard_cache: None,
+			rate_limiter: crate::rate_limit::RateLimiter::new(cooldown, &crate::clock::RealClock),
 			http: reqwest::Client::new(),
-		};
-		session.login().await?;
+			parse_settings: crate::ParseSettings::default(),
+			stats: crate::StatsRecorder::default(),
+			clock: Box::new(crate::clock::RealClock),
+		}
+	}
 
-		Ok(session)
+	/// Like [`Self::new_from_login`], but doesn't fail if the initial login fails, e.g. because EO
+	/// is temporarily down. Instead, the login is retried on the first request. The error of the
+	/// initial login can be retrieved with [`Self::take_initial_login_error`].
+	///
+	/// # Example
+	/// ```rust,no_run
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
+	/// # use etternaonline_api::v2::*;
+	/// let session = Session::new_from_login_tolerant(
+	/// 	"kangalioo".into(),
+	/// 	"<PASSWORD>".into(),
+	/// 	"<CLIENT_DATA>".into(),
+	/// 	std::time::Duration::from_millis(2000), // wait 2s inbetween requests
+	/// 	None, // no timeout
+	/// ).await;
+	///
+	/// if let Some(error) = session.take_initial_login_error() {
+	/// 	println!("EO seems to be down ({}), will try again later", error);
+	/// }
+	/// # Ok(()) }
+	/// ```
+	pub async fn new_from_login_tolerant(
+		username: String,
+		password: String,
+		client_data: String,
+		cooldown: std::time::Duration,
+		timeout: Option<std::time::Duration>,
+	) -> Self {
+		let session = Self::new_logged_out(username, password, client_data, cooldown, timeout);
+		if let Err(e) = session.login().await {
+			log::warn!("Initial login failed, will retry on first request: {}", e);
+			*session.initial_login_error.lock().unwrap() = Some(e);
+		}
+
+		session
+	}
+
+	/// If this session was created with [`Self::new_from_login_tolerant`] and the initial login
+	/// failed, returns the error of that login attempt. Subsequent calls return None
+	pub fn take_initial_login_error(&self) -> Option<Error> {
+		// UNWRAP: propagate panics
+		self.initial_login_error.lock().unwrap().take()
+	}
+
+	/// Whether this session currently holds a login token. The token may have expired on the
+	/// server side nevertheless; it's renewed automatically on the next request in that case
+	pub fn is_logged_in(&self) -> bool {
+		// UNWRAP: propagate panics
+		self.authorization.lock().unwrap().is_some()
+	}
+
+	/// When the last successful login happened according to the session's clock, if any
+	pub fn last_login_time(&self) -> Option<std::time::Instant> {
+		// UNWRAP: propagate panics
+		*self.last_login.lock().unwrap()
+	}
+
+	/// Estimates when the current login token expires. EO doesn't tell us, so this is based on
+	/// how long the previous token lasted until EO rejected it. Returns None if no token has
+	/// expired yet in this session
+	pub fn estimated_token_expiry(&self) -> Option<std::time::Instant> {
+		// UNWRAP: propagate panics
+		let lifetime = (*self.observed_token_lifetime.lock().unwrap())?;
+		Some(self.last_login_time()? + lifetime)
+	}
+
+	/// Enables or disables lenient parsing. In lenient mode, entries of a list response that fail
+	/// to parse are skipped and reported to the parse warning callback instead of failing the
+	/// whole request
+	pub fn set_lenient(&mut self, lenient: bool) {
+		self.parse_settings.lenient = lenient;
+	}
+
+	/// Enables or disables raw replay retention. If enabled, parsed replays additionally contain
+	/// the replay rows exactly as EO sent them, in [`Replay::raw`](crate::Replay::raw)
+	pub fn set_keep_raw_replays(&mut self, keep_raw_replays: bool) {
+		self.parse_settings.keep_raw_replays = keep_raw_replays;
+	}
+
+	/// Registers a callback that receives every [`ParseWarning`](crate::ParseWarning), so that you
+	/// can monitor EO for format changes without hard failures
+	pub fn set_parse_warning_callback(
+		&mut self,
+		callback: impl Fn(crate::ParseWarning) + Send + Sync + 'static,
+	) {
+		self.parse_settings.warning_callback = Some(Box::new(callback));
+	}
+
+	/// Registers a callback that is invoked whenever logging in again after the session token
+	/// expired fails, e.g. because the password was changed. Interactive applications can use
+	/// this to prompt for new credentials or alert an operator.
+	///
+	/// The request that triggered the re-login still fails with the same error afterwards.
+	pub fn set_auth_failure_callback(&mut self, callback: impl Fn(&Error) + Send + Sync + 'static) {
+		self.auth_failure_callback = Some(Box::new(callback));
+	}
+
+	/// Registers a callback that receives an [`AuditEntry`] for every request that isn't a GET
+	/// request, i.e. logins and all changes to goals and favorites. Useful to keep track of what a
+	/// bot did on behalf of its users.
+	///
+	/// Passwords and client data are redacted from the recorded parameters.
+	pub fn set_audit_callback(&mut self, callback: impl Fn(AuditEntry) + Send + Sync + 'static) {
+		self.audit_callback = Some(Box::new(callback));
+	}
+
+	/// Enables caching of [`Self::chart_leaderboard`] results for the given duration, or disables
+	/// it if `None` is passed. Disabling or changing the duration clears the cache.
+	///
+	/// Chart leaderboards are among the heaviest requests for EO, so if you expect the same charts
+	/// to be requested repeatedly, consider enabling this.
+	pub fn set_leaderboard_cache_ttl(&mut self, ttl: Option<std::time::Duration>) {
+		self.leaderboard_cache = ttl.map(|ttl| LeaderboardCache {
+			ttl,
+			entries: std::sync::Mutex::new(std::collections::HashMap::new()),
+		});
+	}
+
+	/// Removes the cached leaderboard of the given chart, if any, so that the next call to
+	/// [`Self::chart_leaderboard`] retrieves it from EO again
+	pub fn invalidate_cached_leaderboard(&self, chartkey: impl AsRef<str>) {
+		if let Some(cache) = &self.leaderboard_cache {
+			// UNWRAP: propagate panics
+			cache.entries.lock().unwrap().remove(chartkey.as_ref());
+		}
+	}
+
+	/// Removes all cached chart leaderboards
+	pub fn clear_leaderboard_cache(&self) {
+		if let Some(cache) = &self.leaderboard_cache {
+			// UNWRAP: propagate panics
+			cache.entries.lock().unwrap().clear();
+		}
+	}
+
+	/// Persists the rate limiting state using the given store, so that the cooldown carries over
+	/// when the application restarts. If the store contains previously saved state, it is restored
+	/// immediately
+	pub fn set_rate_limit_store(
+		&mut self,
+		store: impl crate::rate_limit::RateLimitStore + 'static,
+	) {
+		self.rate_limiter.set_store(Box::new(store), &*self.clock);
+	}
+
+	/// Reserves request time slots from the given backend instead of limiting the rate
+	/// in-process, so that multiple processes can share one cooldown. The session's own
+	/// cooldown is still passed to the backend
+	pub fn set_rate_limit_backend(
+		&mut self,
+		backend: impl crate::rate_limit::RateLimitBackend + 'static,
+	) {
+		self.rate_limiter.set_backend(Box::new(backend));
+	}
+
+	/// Returns statistics about the requests this session has sent so far
+	pub fn stats(&self) -> crate::RequestStats {
+		self.stats.get()
+	}
+
+	/// Replaces the time source used for rate limiting, e.g. with a
+	/// [`InstantClock`](crate::clock::InstantClock) in tests
+	pub fn set_clock(&mut self, clock: impl crate::clock::Clock + 'static) {
+		self.rate_limiter.set_clock(&clock);
+		self.clock = Box::new(clock);
 	}
 
 	// login again to generate a new session token
-	// hmmm I wonder if there's a risk that the server won't properly generate a session token,
-	// return Unauthorized, and then my client will try to login to get a fresh token, and the
-	// process repeats indefinitely...? I just hope that the EO server never throws an Unauthorized
-	// on login
 	async fn login(&self) -> Result<(), Error> {
+		// Back off after rejected logins, and give up entirely after too many
+		let failures = *self.login_failures.lock().unwrap();
+		if failures >= MAX_LOGIN_FAILURES {
+			return Err(Error::CredentialsRejected { attempts: failures });
+		}
+		if failures > 0 {
+			let backoff = LOGIN_BACKOFF_BASE * 2u32.pow(failures - 1);
+			let last_login_attempt = self
+				.last_login_attempt
+				.lock()
+				.unwrap()
+				.unwrap_or_else(|| self.clock.now());
+			self.clock.sleep_until(last_login_attempt + backoff).await;
+		}
+		*self.last_login_attempt.lock().unwrap() = Some(self.clock.now());
+
+		let result = self.login_uncounted().await;
+		match &result {
+			Ok(()) => *self.login_failures.lock().unwrap() = 0,
+			Err(Error::InvalidLogin) => *self.login_failures.lock().unwrap() += 1,
+			Err(_) => {}
+		}
+		result
+	}
+
+	/// Forget about previous rejected login attempts, so that the session tries to log in again
+	/// after having failed with [`Error::CredentialsRejected`]
+	pub fn reset_login_failures(&self) {
+		// UNWRAP: propagate panics
+		*self.login_failures.lock().unwrap() = 0;
+	}
+
+	async fn login_uncounted(&self) -> Result<(), Error> {
 		let form: &[(&str, &str)] = &[
 			// eh fuck it. I dont wanna bother with those lifetime headaches
 			// who needs allocation efficiency anyways
@@ -147,7 +689,7 @@ impl Session {
 			("clientData", &self.client_data.clone()),
 		];
 
-		let json = self
+		let response = self
 			.generic_request(
 				reqwest::Method::POST,
 				"login",
@@ -158,15 +700,37 @@ impl Session {
 				false,
 			)
 			.await?;
+		let json = response_data(&response)?;
 
 		*self.authorization.lock().unwrap() = Some(format!(
 			"Bearer {}",
 			json["attributes"]["accessToken"].str_()?,
 		));
+		*self.last_login.lock().unwrap() = Some(self.clock.now());
 
 		Ok(())
 	}
 
+	// Logs in again, unless another login has completed since `seen_generation`. If a login is
+	// already in progress, waits for it instead of starting another one
+	async fn relogin(&self, seen_generation: u64) -> Result<(), Error> {
+		let should_login = LoginFlightAcquire {
+			flight: &self.login_flight,
+			seen_generation,
+		}
+		.await;
+		if !should_login {
+			return Ok(());
+		}
+
+		let _guard = LoginFlightGuard(&self.login_flight);
+		let result = self.login().await;
+		if let (Err(e), Some(callback)) = (&result, &self.auth_failure_callback) {
+			callback(e);
+		}
+		result
+	}
+
 	// If `do_authorization` is set, the authorization field will be locked immutably! So if the
 	// caller has a mutable lock active when calling generic_request, DONT PASS true FOR
 	// do_authorization, or we'll deadlock!
@@ -177,11 +741,9 @@ impl Session {
 		request_callback: impl Fn(reqwest::RequestBuilder) -> reqwest::RequestBuilder + Send + Sync + 'a,
 		context: RequestContext<'a>,
 		do_authorization: bool,
-	) -> BoxFuture<'a, Result<serde_json::Value, Error>> {
+	) -> BoxFuture<'a, Result<String, Error>> {
 		Box::pin(async move {
-			// UNWRAP: propagate panics
-			let rate_limit = crate::rate_limit(self.last_request.lock().unwrap(), self.cooldown);
-			rate_limit.await;
+			self.rate_limiter.wait(&*self.clock).await;
 
 			let mut request = self.http.request(
 				method.clone(),
@@ -190,74 +752,121 @@ impl Session {
 			if let Some(timeout) = self.timeout {
 				request = request.timeout(timeout);
 			}
+			let login_generation = self.login_flight.generation();
 			if do_authorization {
-				let auth = self
-					.authorization
-					.lock()
-					.unwrap()
-					.as_ref()
-					.expect("No authorization set even though it was requested??")
-					.clone();
+				let auth = loop {
+					// UNWRAP: propagate panics
+					let auth = self.authorization.lock().unwrap().clone();
+					match auth {
+						Some(auth) => break auth,
+						// The initial login failed, so try again now
+						None => self.relogin(self.login_flight.generation()).await?,
+					}
+				};
 				request = request.header("Authorization", &auth);
 			}
 			request = request_callback(request);
 
-			let response = request.send().await?;
-			let status = response.status();
-			let response = response.text().await?;
-
-			if status.is_server_error() {
-				return Err(Error::InternalServerError {
-					status_code: status.as_u16(),
-				});
-			}
-
-			if response.is_empty() {
-				return Err(Error::EmptyServerResponse);
+			let audit_parameters = match &self.audit_callback {
+				Some(_) if method != reqwest::Method::GET => Some(audit_parameters(&request)),
+				_ => None,
+			};
+
+			let start = std::time::Instant::now();
+			let result = self.send(request, &context).await;
+			self.stats
+				.record(path, context.user, start.elapsed(), result.as_ref().err());
+
+			// An expired token isn't the final result, the request is repeated below
+			if let (Some(callback), Some(parameters)) = (&self.audit_callback, audit_parameters) {
+				if !matches!(result, Ok(None)) {
+					callback(AuditEntry {
+						method: method.to_string(),
+						endpoint: path.to_owned(),
+						parameters,
+						error: result.as_ref().err().map(|e| e.to_string()),
+						timestamp: std::time::SystemTime::now(),
+					});
+				}
 			}
 
-			// only parse json if the response code is not 5xx because on 5xx response codes, the server
-			// sometimes sends empty responses
-			let mut json: serde_json::Value = serde_json::from_str(&response)?;
-
-			// Error handling
-			if status.is_client_error() {
-				return match json["errors"][0]["title"].str_()? {
-					"Unauthorized" => {
-						// Token expired, let's login again and retry
-						self.login().await?;
-						return self
-							.generic_request(
-								method,
-								path,
-								request_callback,
-								context,
-								do_authorization,
-							)
-							.await;
+			match result? {
+				Some(response) => Ok(response),
+				// Unauthorized on login itself means that the credentials are wrong. Don't try
+				// logging in to fix that, that would recurse forever
+				None if !do_authorization => Err(Error::InvalidLogin),
+				None => {
+					// Token expired, let's login again and retry
+					crate::instrumentation::retry("token_expired");
+					let last_login = *self.last_login.lock().unwrap();
+					if let Some(last_login) = last_login {
+						*self.observed_token_lifetime.lock().unwrap() =
+							Some(self.clock.now() - last_login);
 					}
-					"Score not found" => Err(Error::ScoreNotFound),
-					"Chart not tracked" => Err(Error::ChartNotTracked),
-					"User not found" => Err(Error::UserNotFound {
-						name: context.user.map(|x| x.to_owned()),
-					}),
-					"Favorite already exists" => Err(Error::ChartAlreadyFavorited),
-					"Database error" => Err(Error::DatabaseError),
-					"Goal already exist" => Err(Error::GoalAlreadyExists),
-					"Chart already exists" => Err(Error::ChartAlreadyAdded),
-					"Malformed XML file" => Err(Error::InvalidXml),
-					"No users found" => Err(Error::NoUsersFound),
-					other => Err(Error::UnknownApiError(other.to_owned())),
-				};
-			} else if status != 200 {
-				// TODO: should we have print calls in a library?
-				println!("Warning: status code {}", status);
+					self.relogin(login_generation).await?;
+					self.generic_request(method, path, request_callback, context, do_authorization)
+						.await
+				}
 			}
-
-			Ok(json["data"].take())
 		})
 	}
 
+	// Returns the response body, or None if the server rejected the login token
+	async fn send(
+		&self,
+		request: reqwest::RequestBuilder,
+		context: &RequestContext<'_>,
+	) -> Result<Option<String>, Error> {
+		let response = request.send().await?;
+		let status = response.status();
+		let headers = response.headers().clone();
+		let response = response.text().await?;
+
+		if crate::is_cloudflare_challenge(status, &headers, &response) {
+			return Err(Error::CloudflareChallenge {
+				status_code: status.as_u16(),
+			});
+		}
+
+		if status.is_server_error() {
+			return Err(Error::InternalServerError {
+				status_code: status.as_u16(),
+			});
+		}
+
+		if response.is_empty() {
+			return Err(Error::EmptyServerResponse);
+		}
+
+		// Error handling
+		if status.is_client_error() {
+			// only parse json if the response code is not 5xx because on 5xx response codes, the
+			// server sometimes sends empty responses
+			let json: serde_json::Value = serde_json::from_str(&response)?;
+			return match json["errors"][0]["title"].str_()? {
+				// Token expired
+				"Unauthorized" => Ok(None),
+				"Score not found" => Err(Error::ScoreNotFound),
+				"Chart not tracked" => Err(Error::ChartNotTracked),
+				"User not found" => Err(Error::UserNotFound {
+					name: context.user.map(|x| x.to_owned()),
+				}),
+				"Favorite already exists" => Err(Error::ChartAlreadyFavorited),
+				"Database error" => Err(Error::DatabaseError),
+				"Goal already exist" => Err(Error::GoalAlreadyExists),
+				"Chart already exists" => Err(Error::ChartAlreadyAdded),
+				"Malformed XML file" => Err(Error::InvalidXml),
+				"No users found" => Err(Error::NoUsersFound),
+				other => Err(Error::UnknownApiError(other.to_owned())),
+			};
+		} else if status != 200 {
+			// TODO: should we have print calls in a library?
+			println!("Warning: status code {}", status);
+		}
+
+		Ok(Some(response))
+	}
+
 	async fn request(
 		&self,
 		method: reqwest::Method,
@@ -265,8 +874,10 @@ impl Session {
 		request_callback: impl Fn(reqwest::RequestBuilder) -> reqwest::RequestBuilder + Send + Sync,
 		context: RequestContext<'_>,
 	) -> Result<serde_json::Value, Error> {
-		self.generic_request(method, path, request_callback, context, true)
-			.await
+		let response = self
+			.generic_request(method, path, request_callback, context, true)
+			.await?;
+		response_data(&response)
 	}
 
 	async fn get(
@@ -287,11 +898,11 @@ impl Session {
 	///
 	/// # Example
 	/// ```rust,no_run
-	/// # fn main() -> Result<(), etternaonline_api::Error> {
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
 	/// # use etternaonline_api::v2::*;
 	/// # let mut session: Session = unimplemented!();
 	/// // Retrieve details about user "kangalioo"
-	/// let details = session.user_details("kangalioo")?;
+	/// let details = session.user_details("kangalioo").await?;
 	/// # Ok(()) }
 	/// ```
 	pub async fn user_details(&self, username: &str) -> Result<UserDetails, Error> {
@@ -299,30 +910,43 @@ impl Session {
 			user: Some(username),
 		};
 		let json = self.get(&format!("user/{}", username), ctx).await?;
-		let json = &json["attributes"];
+		let json = Json::new(&json, "data");
+		let json = &json.at("attributes");
 
 		Ok(UserDetails {
-			username: json["userName"].string()?,
-			about_me: json["aboutMe"].string()?,
-			is_moderator: json["moderator"].bool_()?,
-			is_patreon: json["patreon"].bool_()?,
-			avatar_url: json["avatar"].string()?,
-			country_code: json["countryCode"].string()?,
-			player_rating: json["playerRating"].f32_()?,
-			default_modifiers: match json["defaultModifiers"].str_()? {
+			username: json.at("userName").string()?,
+			about_me: json.at("aboutMe").string()?,
+			is_moderator: json.at("moderator").bool_()?,
+			is_patreon: json.at("patreon").bool_()?,
+			avatar_url: json.at("avatar").string()?,
+			country_code: json.at("countryCode").string()?,
+			player_rating: json.at("playerRating").f32_()?,
+			default_modifiers: match json.at("defaultModifiers").str_()? {
 				"" => None,
 				modifiers => Some(modifiers.to_owned()),
 			},
 			rating: etterna::Skillsets8 {
-				overall: json["playerRating"].f32_()?,
-				stream: json["skillsets"]["Stream"].f32_()?,
-				jumpstream: json["skillsets"]["Jumpstream"].f32_()?,
-				handstream: json["skillsets"]["Handstream"].f32_()?,
-				stamina: json["skillsets"]["Stamina"].f32_()?,
-				jackspeed: json["skillsets"]["JackSpeed"].f32_()?,
-				chordjack: json["skillsets"]["Chordjack"].f32_()?,
-				technical: json["skillsets"]["Technical"].f32_()?,
+				overall: json.at("playerRating").f32_()?,
+				stream: json.at("skillsets").at("Stream").f32_()?,
+				jumpstream: json.at("skillsets").at("Jumpstream").f32_()?,
+				handstream: json.at("skillsets").at("Handstream").f32_()?,
+				stamina: json.at("skillsets").at("Stamina").f32_()?,
+				jackspeed: json.at("skillsets").at("JackSpeed").f32_()?,
+				chordjack: json.at("skillsets").at("Chordjack").f32_()?,
+				technical: json.at("skillsets").at("Technical").f32_()?,
 			},
+			#[cfg(feature = "extra-fields")]
+			extra: json.unknown_fields(&[
+				"userName",
+				"aboutMe",
+				"moderator",
+				"patreon",
+				"avatar",
+				"countryCode",
+				"playerRating",
+				"defaultModifiers",
+				"skillsets",
+			]),
 		})
 	}
 
@@ -332,31 +956,62 @@ impl Session {
 		context: RequestContext<'_>,
 	) -> Result<Vec<TopScore>, Error> {
 		let json = self.get(url, context).await?;
+		let json = Json::new(&json, "data");
 
-		json.array()?
-			.iter()
-			.map(|json| {
+		self.parse_settings.collect(
+			"top scores",
+			json.elements()?.map(|json| {
 				Ok(TopScore {
-					scorekey: json["id"].parse()?,
-					song_name: json["attributes"]["songName"].string()?,
-					ssr_overall: json["attributes"]["Overall"].f32_()?,
-					wifescore: json["attributes"]["wife"].wifescore_percent_float()?,
-					rate: json["attributes"]["rate"].rate_float()?,
-					difficulty: json["attributes"]["difficulty"].parse()?,
-					chartkey: json["attributes"]["chartKey"].parse()?,
+					scorekey: json.at("id").parse()?,
+					song_name: json.at("attributes").at("songName").string()?,
+					ssr_overall: json.at("attributes").at("Overall").f32_()?,
+					wifescore: json.at("attributes").at("wife").wifescore_percent_float()?,
+					rate: json.at("attributes").at("rate").rate_float()?,
+					difficulty: json.at("attributes").at("difficulty").parse()?,
+					chartkey: json.at("attributes").at("chartKey").parse()?,
 					base_msd: etterna::Skillsets8 {
-						overall: json["attributes"]["Overall"].f32_()?,
-						stream: json["attributes"]["skillsets"]["Stream"].f32_()?,
-						jumpstream: json["attributes"]["skillsets"]["Jumpstream"].f32_()?,
-						handstream: json["attributes"]["skillsets"]["Handstream"].f32_()?,
-						stamina: json["attributes"]["skillsets"]["Stamina"].f32_()?,
-						jackspeed: json["attributes"]["skillsets"]["JackSpeed"].f32_()?,
-						chordjack: json["attributes"]["skillsets"]["Chordjack"].f32_()?,
-						technical: json["attributes"]["skillsets"]["Technical"].f32_()?,
+						overall: json.at("attributes").at("Overall").f32_()?,
+						stream: json.at("attributes").at("skillsets").at("Stream").f32_()?,
+						jumpstream: json
+							.at("attributes")
+							.at("skillsets")
+							.at("Jumpstream")
+							.f32_()?,
+						handstream: json
+							.at("attributes")
+							.at("skillsets")
+							.at("Handstream")
+							.f32_()?,
+						stamina: json.at("attributes").at("skillsets").at("Stamina").f32_()?,
+						jackspeed: json
+							.at("attributes")
+							.at("skillsets")
+							.at("JackSpeed")
+							.f32_()?,
+						chordjack: json
+							.at("attributes")
+							.at("skillsets")
+							.at("Chordjack")
+							.f32_()?,
+						technical: json
+							.at("attributes")
+							.at("skillsets")
+							.at("Technical")
+							.f32_()?,
 					},
+					#[cfg(feature = "extra-fields")]
+					extra: json.at("attributes").unknown_fields(&[
+						"songName",
+						"Overall",
+						"wife",
+						"rate",
+						"difficulty",
+						"chartKey",
+						"skillsets",
+					]),
 				})
-			})
-			.collect()
+			}),
+		)
 	}
 
 	/// Retrieve the user's top scores by the given skillset. The number of scores returned is equal
@@ -367,12 +1022,12 @@ impl Session {
 	///
 	/// # Example
 	/// ```rust,no_run
-	/// # fn main() -> Result<(), etternaonline_api::Error> {
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
 	/// # use etternaonline_api::v2::*;
 	/// # use etterna::*;
 	/// # let mut session: Session = unimplemented!();
 	/// // Retrieve the top 10 chordjack scores of user "kangalioo"
-	/// let scores = session.user_top_skillset_scores("kangalioo", Skillset7::Chordjack, 10)?;
+	/// let scores = session.user_top_skillset_scores("kangalioo", Skillset7::Chordjack, 10).await?;
 	/// # Ok(()) }
 	/// ```
 	pub async fn user_top_skillset_scores(
@@ -403,11 +1058,11 @@ impl Session {
 	///
 	/// # Example
 	/// ```rust,no_run
-	/// # fn main() -> Result<(), etternaonline_api::Error> {
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
 	/// # use etternaonline_api::v2::*;
 	/// # let mut session: Session = unimplemented!();
 	/// // Retrieve the top 10 scores of user "kangalioo"
-	/// let scores = session.user_top_10_scores("kangalioo")?;
+	/// let scores = session.user_top_10_scores("kangalioo").await?;
 	/// # Ok(()) }
 	/// ```
 	pub async fn user_top_10_scores(&self, username: &str) -> Result<Vec<TopScore>, Error> {
@@ -427,11 +1082,11 @@ impl Session {
 	///
 	/// # Example
 	/// ```rust,no_run
-	/// # fn main() -> Result<(), etternaonline_api::Error> {
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
 	/// # use etternaonline_api::v2::*;
 	/// # let mut session: Session = unimplemented!();
 	/// // Retrieve the latest 10 scores of user "kangalioo"
-	/// let scores = session.user_latest_scores("kangalioo")?;
+	/// let scores = session.user_latest_scores("kangalioo").await?;
 	/// # Ok(()) }
 	/// ```
 	pub async fn user_latest_scores(&self, username: &str) -> Result<Vec<LatestScore>, Error> {
@@ -443,20 +1098,29 @@ impl Session {
 				},
 			)
 			.await?;
+		let json = Json::new(&json, "data");
 
-		json.array()?
-			.iter()
-			.map(|json| {
+		self.parse_settings.collect(
+			"latest scores",
+			json.elements()?.map(|json| {
 				Ok(LatestScore {
-					scorekey: json["id"].parse()?,
-					song_name: json["attributes"]["songName"].string()?,
-					ssr_overall: json["attributes"]["Overall"].f32_()?,
-					wifescore: json["attributes"]["wife"].wifescore_percent_float()?,
-					rate: json["attributes"]["rate"].rate_float()?,
-					difficulty: difficulty_from_eo(json["attributes"]["difficulty"].str_()?)?,
+					scorekey: json.at("id").parse()?,
+					song_name: json.at("attributes").at("songName").string()?,
+					ssr_overall: json.at("attributes").at("Overall").f32_()?,
+					wifescore: json.at("attributes").at("wife").wifescore_percent_float()?,
+					rate: json.at("attributes").at("rate").rate_float()?,
+					difficulty: difficulty_from_eo(json.at("attributes").at("difficulty").str_()?)?,
+					#[cfg(feature = "extra-fields")]
+					extra: json.at("attributes").unknown_fields(&[
+						"songName",
+						"Overall",
+						"wife",
+						"rate",
+						"difficulty",
+					]),
 				})
-			})
-			.collect()
+			}),
+		)
 	}
 
 	/// Retrieve the user's rank for each skillset.
@@ -466,11 +1130,11 @@ impl Session {
 	///
 	/// # Example
 	/// ```rust,no_run
-	/// # fn main() -> Result<(), etternaonline_api::Error> {
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
 	/// # use etternaonline_api::v2::*;
 	/// # let mut session: Session = unimplemented!();
 	/// // Retrieve "kangalioo"'s rank for each skillset
-	/// let scores = session.user_ranks_per_skillset("kangalioo")?;
+	/// let scores = session.user_ranks_per_skillset("kangalioo").await?;
 	/// # Ok(()) }
 	/// ```
 	pub async fn user_ranks_per_skillset(
@@ -485,17 +1149,18 @@ impl Session {
 				},
 			)
 			.await?;
-		let json = &json["attributes"];
+		let json = Json::new(&json, "data");
+		let json = &json.at("attributes");
 
 		Ok(etterna::UserRank {
-			overall: json["Overall"].u32_()?,
-			stream: json["Stream"].u32_()?,
-			jumpstream: json["Jumpstream"].u32_()?,
-			handstream: json["Handstream"].u32_()?,
-			stamina: json["Stamina"].u32_()?,
-			jackspeed: json["JackSpeed"].u32_()?,
-			chordjack: json["Chordjack"].u32_()?,
-			technical: json["Technical"].u32_()?,
+			overall: json.at("Overall").u32_()?,
+			stream: json.at("Stream").u32_()?,
+			jumpstream: json.at("Jumpstream").u32_()?,
+			handstream: json.at("Handstream").u32_()?,
+			stamina: json.at("Stamina").u32_()?,
+			jackspeed: json.at("JackSpeed").u32_()?,
+			chordjack: json.at("Chordjack").u32_()?,
+			technical: json.at("Technical").u32_()?,
 		})
 	}
 
@@ -507,10 +1172,10 @@ impl Session {
 	///
 	/// # Example
 	/// ```rust,no_run
-	/// # fn main() -> Result<(), etternaonline_api::Error> {
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
 	/// # use etternaonline_api::v2::*;
 	/// # let mut session: Session = unimplemented!();
-	/// let top_scores = session.user_top_scores_per_skillset("kangalioo")?;
+	/// let top_scores = session.user_top_scores_per_skillset("kangalioo").await?;
 	/// println!("kangalioo's 5th best handstream score is {:?}", top_scores.handstream[4]);
 	/// # Ok(()) }
 	/// ```
@@ -526,28 +1191,28 @@ impl Session {
 				},
 			)
 			.await?;
+		let json = Json::new(&json, "data");
 
-		let parse_skillset_top_scores = |array: &serde_json::Value| -> Result<Vec<_>, Error> {
+		let parse_skillset_top_scores = |array: Json<'_>| -> Result<Vec<_>, Error> {
 			array
-				.array()?
-				.iter()
+				.elements()?
 				.map(|json| {
 					Ok(TopScorePerSkillset {
-						song_name: json["songname"].string()?,
-						rate: json["user_chart_rate_rate"].rate_float()?,
-						wifescore: json["wifescore"].wifescore_proportion_float()?,
-						chartkey: json["chartkey"].parse()?,
-						scorekey: json["scorekey"].parse()?,
-						difficulty: difficulty_from_eo(json["difficulty"].str_()?)?,
+						song_name: json.at("songname").string()?,
+						rate: json.at("user_chart_rate_rate").rate_float()?,
+						wifescore: json.at("wifescore").wifescore_proportion_float()?,
+						chartkey: json.at("chartkey").parse()?,
+						scorekey: json.at("scorekey").parse()?,
+						difficulty: difficulty_from_eo(json.at("difficulty").str_()?)?,
 						ssr: etterna::Skillsets8 {
-							overall: json["Overall"].f32_()?,
-							stream: json["Stream"].f32_()?,
-							jumpstream: json["Jumpstream"].f32_()?,
-							handstream: json["Handstream"].f32_()?,
-							stamina: json["Stamina"].f32_()?,
-							jackspeed: json["JackSpeed"].f32_()?,
-							chordjack: json["Chordjack"].f32_()?,
-							technical: json["Technical"].f32_()?,
+							overall: json.at("Overall").f32_()?,
+							stream: json.at("Stream").f32_()?,
+							jumpstream: json.at("Jumpstream").f32_()?,
+							handstream: json.at("Handstream").f32_()?,
+							stamina: json.at("Stamina").f32_()?,
+							jackspeed: json.at("JackSpeed").f32_()?,
+							chordjack: json.at("Chordjack").f32_()?,
+							technical: json.at("Technical").f32_()?,
 						},
 					})
 				})
@@ -555,14 +1220,39 @@ impl Session {
 		};
 
 		Ok(UserTopScoresPerSkillset {
-			overall: parse_skillset_top_scores(&json["attributes"]["Overall"])?,
-			stream: parse_skillset_top_scores(&json["attributes"]["Stream"])?,
-			jumpstream: parse_skillset_top_scores(&json["attributes"]["Jumpstream"])?,
-			handstream: parse_skillset_top_scores(&json["attributes"]["Handstream"])?,
-			stamina: parse_skillset_top_scores(&json["attributes"]["Stamina"])?,
-			jackspeed: parse_skillset_top_scores(&json["attributes"]["JackSpeed"])?,
-			chordjack: parse_skillset_top_scores(&json["attributes"]["Chordjack"])?,
-			technical: parse_skillset_top_scores(&json["attributes"]["Technical"])?,
+			overall: parse_skillset_top_scores(json.at("attributes").at("Overall"))?,
+			stream: parse_skillset_top_scores(json.at("attributes").at("Stream"))?,
+			jumpstream: parse_skillset_top_scores(json.at("attributes").at("Jumpstream"))?,
+			handstream: parse_skillset_top_scores(json.at("attributes").at("Handstream"))?,
+			stamina: parse_skillset_top_scores(json.at("attributes").at("Stamina"))?,
+			jackspeed: parse_skillset_top_scores(json.at("attributes").at("JackSpeed"))?,
+			chordjack: parse_skillset_top_scores(json.at("attributes").at("Chordjack"))?,
+			technical: parse_skillset_top_scores(json.at("attributes").at("Technical"))?,
+		})
+	}
+
+	/// Retrieve the user's details, their rank in each skillset and their top 10 scores in one go.
+	/// This is the bundle that a typical profile command displays, so it saves you from making
+	/// the individual calls yourself. The requests are made one after another and are subject to
+	/// the session's rate limit like any others
+	///
+	/// # Errors
+	/// - [`Error::UserNotFound`] if the supplied username was not found
+	///
+	/// # Example
+	/// ```rust,no_run
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
+	/// # use etternaonline_api::v2::*;
+	/// # let mut session: Session = unimplemented!();
+	/// let card = session.user_profile_card("kangalioo").await?;
+	/// println!("{} is rank #{} overall", card.details.username, card.ranks.overall);
+	/// # Ok(()) }
+	/// ```
+	pub async fn user_profile_card(&self, username: &str) -> Result<UserProfileCard, Error> {
+		Ok(UserProfileCard {
+			details: self.user_details(username).await?,
+			ranks: self.user_ranks_per_skillset(username).await?,
+			top_scores: self.user_top_10_scores(username).await?,
 		})
 	}
 
@@ -570,72 +1260,146 @@ impl Session {
 	///
 	/// # Errors
 	/// - [`Error::ScoreNotFound`] if the supplied scorekey was not found
-	/// - panics if the passed in scorekey is in an invalid format (only applies if passed in as a
-	///   `&str`, since `&Scorekey` is guaranteed to be valid)
+	/// - [`Error::InvalidScorekey`] if the supplied scorekey is malformed (only applies if passed
+	///   in as a `&str`, since `&Scorekey` is guaranteed to be valid)
 	///
 	/// # Example
 	/// ```rust,no_run
-	/// # fn main() -> Result<(), etternaonline_api::Error> {
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
 	/// # use etternaonline_api::v2::*;
 	/// # let mut session: Session = unimplemented!();
-	/// let score_info = session.score_data("S65565b5bc377c6d78b60c0aecfd9e05955b4cf63")?;
+	/// let score_info = session.score_data("S65565b5bc377c6d78b60c0aecfd9e05955b4cf63").await?;
 	/// # Ok(()) }
 	/// ```
 	pub async fn score_data(&self, scorekey: impl AsRef<str>) -> Result<ScoreData, Error> {
+		crate::common::validate_scorekey(scorekey.as_ref())?;
 		let json = self
 			.get(
 				&format!("score/{}", scorekey.as_ref()),
 				RequestContext::default(),
 			)
 			.await?;
+		let json = Json::new(&json, "data");
 
-		let scorekey = json["id"].parse()?;
-		let json = &json["attributes"];
+		let scorekey = json.at("id").parse()?;
+		let json = &json.at("attributes");
 
+		let (replay, replay_raw) =
+			crate::common::parse_replay_with_settings(&json.at("replay"), &self.parse_settings);
 		Ok(ScoreData {
 			scorekey,
-			modifiers: json["modifiers"].string()?,
-			wifescore: json["wife"].wifescore_proportion_float()?,
-			rate: json["rate"].rate_float()?,
-			max_combo: json["maxCombo"].u32_()?,
-			is_valid: json["valid"].bool_()?,
-			has_chord_cohesion: !json["nocc"].bool_()?,
-			song_name: json["song"]["songName"].string()?,
-			artist: json["song"]["artist"].string()?,
-			song_id: json["song"]["id"].u32_()?,
+			modifiers: json.at("modifiers").string()?,
+			wifescore: json.at("wife").wifescore_proportion_float()?,
+			rate: json.at("rate").rate_float()?,
+			max_combo: json.at("maxCombo").u32_()?,
+			is_valid: json.at("valid").bool_()?,
+			has_chord_cohesion: !json.at("nocc").bool_()?,
+			song_name: json.at("song").at("songName").string()?,
+			artist: json.at("song").at("artist").string()?,
+			song_id: SongId(json.at("song").at("id").u32_()?),
 			ssr: etterna::Skillsets8 {
-				overall: json["skillsets"]["Overall"].f32_()?,
-				stream: json["skillsets"]["Stream"].f32_()?,
-				jumpstream: json["skillsets"]["Jumpstream"].f32_()?,
-				handstream: json["skillsets"]["Handstream"].f32_()?,
-				stamina: json["skillsets"]["Stamina"].f32_()?,
-				jackspeed: json["skillsets"]["JackSpeed"].f32_()?,
-				chordjack: json["skillsets"]["Chordjack"].f32_()?,
-				technical: json["skillsets"]["Technical"].f32_()?,
+				overall: json.at("skillsets").at("Overall").f32_()?,
+				stream: json.at("skillsets").at("Stream").f32_()?,
+				jumpstream: json.at("skillsets").at("Jumpstream").f32_()?,
+				handstream: json.at("skillsets").at("Handstream").f32_()?,
+				stamina: json.at("skillsets").at("Stamina").f32_()?,
+				jackspeed: json.at("skillsets").at("JackSpeed").f32_()?,
+				chordjack: json.at("skillsets").at("Chordjack").f32_()?,
+				technical: json.at("skillsets").at("Technical").f32_()?,
 			},
-			judgements: parse_judgements(&json["judgements"])?,
-			replay: crate::common::parse_replay(&json["replay"]),
+			judgements: parse_judgements(&json.at("judgements"))?,
+			replay,
+			replay_raw,
 			user: ScoreUser {
-				username: json["user"]["username"].string()?,
-				avatar: json["user"]["avatar"].string()?,
-				country_code: json["user"]["countryCode"].string()?,
-				overall_rating: json["user"]["Overall"].f32_()?,
+				username: json.at("user").at("username").string()?,
+				avatar: json.at("user").at("avatar").string()?,
+				country_code: json.at("user").at("countryCode").string()?,
+				overall_rating: json.at("user").at("Overall").f32_()?,
 			},
+			#[cfg(feature = "extra-fields")]
+			extra: json.unknown_fields(&[
+				"modifiers",
+				"wife",
+				"rate",
+				"maxCombo",
+				"valid",
+				"nocc",
+				"song",
+				"skillsets",
+				"judgements",
+				"replay",
+				"user",
+			]),
+		})
+	}
+
+	/// Retrieves the score with the given scorekey along with its position on the chart
+	/// leaderboard and, if the score has a replay, its wifescore rescored on J4.
+	///
+	/// EO doesn't say which chart a score was made on, so the chartkey must be passed in as well.
+	/// It's available on most score listings, like [`Self::user_top_10_scores`].
+	///
+	/// # Errors
+	/// - [`Error::ScoreNotFound`] if the supplied scorekey was not found
+	/// - [`Error::ChartNotTracked`] if the chartkey provided is not tracked by EO
+	///
+	/// # Example
+	/// ```rust,no_run
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
+	/// # use etternaonline_api::v2::*;
+	/// # let mut session: Session = unimplemented!();
+	/// let card = session.score_card(
+	/// 	"S65565b5bc377c6d78b60c0aecfd9e05955b4cf63",
+	/// 	"X4a15f62b66a80b62ec64521704f98c6c03d98e03",
+	/// ).await?;
+	/// if let Some(rank) = card.leaderboard_rank {
+	/// 	println!("#{} on the leaderboard", rank);
+	/// }
+	/// # Ok(()) }
+	/// ```
+	pub async fn score_card(
+		&self,
+		scorekey: impl AsRef<str>,
+		chartkey: impl AsRef<str>,
+	) -> Result<ScoreCard, Error> {
+		let score = self.score_data(scorekey).await?;
+		let leaderboard = self.chart_leaderboard(chartkey).await?;
+
+		let leaderboard_rank = leaderboard
+			.iter()
+			.position(|entry| entry.scorekey == score.scorekey)
+			.map(|i| i as u32 + 1);
+		let wifescore_j4 = score.replay.as_ref().and_then(|replay| {
+			crate::rescore::<etterna::MatchingScorer, etterna::Wife3>(
+				replay,
+				score.judgements.hit_mines,
+				score.judgements.let_go_holds + score.judgements.missed_holds,
+				etterna::J4,
+			)
+		});
+
+		Ok(ScoreCard {
+			score,
+			leaderboard_rank,
+			wifescore_j4,
 		})
 	}
 
 	/// Retrieves the leaderboard for the specified chart. The return type is a vector of
 	/// leaderboard entries.
 	///
+	/// If enabled with [`Self::set_leaderboard_cache_ttl`], recently retrieved leaderboards are
+	/// served from the cache.
+	///
 	/// # Errors
 	/// - [`Error::ChartNotTracked`] if the chartkey provided is not tracked by EO
 	///
 	/// # Example
 	/// ```rust,no_run
-	/// # fn main() -> Result<(), etternaonline_api::Error> {
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
 	/// # use etternaonline_api::v2::*;
 	/// # let mut session: Session = unimplemented!();
-	/// let leaderboard = session.chart_leaderboard("X4a15f62b66a80b62ec64521704f98c6c03d98e03")?;
+	/// let leaderboard = session.chart_leaderboard("X4a15f62b66a80b62ec64521704f98c6c03d98e03").await?;
 	///
 	/// println!("The best Game Time score is being held by {}", leaderboard[0].user.username);
 	/// # Ok(()) }
@@ -644,46 +1408,100 @@ impl Session {
 		&self,
 		chartkey: impl AsRef<str>,
 	) -> Result<Vec<ChartLeaderboardScore>, Error> {
-		let json = self
-			.get(
-				&format!("charts/{}/leaderboards", chartkey.as_ref()),
+		let chartkey = chartkey.as_ref();
+		let cache = match &self.leaderboard_cache {
+			Some(cache) => cache,
+			None => return self.chart_leaderboard_uncached(chartkey).await,
+		};
+
+		// UNWRAP: propagate panics
+		if let Some((retrieved, leaderboard)) = cache.entries.lock().unwrap().get(chartkey) {
+			if self.clock.now() < *retrieved + cache.ttl {
+				crate::instrumentation::cache_lookup("chart_leaderboard", true);
+				return Ok(leaderboard.clone());
+			}
+		}
+		crate::instrumentation::cache_lookup("chart_leaderboard", false);
+
+		let leaderboard = self.chart_leaderboard_uncached(chartkey).await?;
+		// UNWRAP: propagate panics
+		cache
+			.entries
+			.lock()
+			.unwrap()
+			.insert(chartkey.to_owned(), (self.clock.now(), leaderboard.clone()));
+		Ok(leaderboard)
+	}
+
+	/// Retrieves the leaderboard for the specified chart, restricted to scores at exactly the given
+	/// rate. Invalid scores are excluded and only each user's best score is kept, so this is the
+	/// view that tournaments usually need.
+	///
+	/// Scores are ordered by wifescore. Ties are broken in favor of the earlier score, then by
+	/// scorekey, so the order is always the same.
+	///
+	/// # Errors
+	/// - [`Error::ChartNotTracked`] if the chartkey provided is not tracked by EO
+	///
+	/// # Example
+	/// ```rust,no_run
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
+	/// # use etternaonline_api::v2::*;
+	/// # let mut session: Session = unimplemented!();
+	/// let leaderboard = session.chart_leaderboard_at_rate(
+	/// 	"X4a15f62b66a80b62ec64521704f98c6c03d98e03",
+	/// 	etterna::Rate::from_f32(1.0).unwrap(),
+	/// ).await?;
+	/// # Ok(()) }
+	/// ```
+	pub async fn chart_leaderboard_at_rate(
+		&self,
+		chartkey: impl AsRef<str>,
+		rate: Rate,
+	) -> Result<Vec<ChartLeaderboardScore>, Error> {
+		let mut scores = self
+			.chart_leaderboard(chartkey)
+			.await?
+			.into_iter()
+			.filter(|score| score.is_valid && score.rate == rate)
+			.collect::<Vec<_>>();
+
+		// EO's datetime format sorts lexicographically
+		scores.sort_by(|a, b| {
+			b.wifescore
+				.as_proportion()
+				.total_cmp(&a.wifescore.as_proportion())
+				.then_with(|| a.datetime.cmp(&b.datetime))
+				.then_with(|| a.scorekey.as_str().cmp(b.scorekey.as_str()))
+		});
+		// Scores are sorted best first, so this keeps each user's best
+		let mut seen_users = std::collections::HashSet::new();
+		scores.retain(|score| seen_users.insert(score.user.username.to_lowercase()));
+
+		Ok(scores)
+	}
+
+	async fn chart_leaderboard_uncached(
+		&self,
+		chartkey: &str,
+	) -> Result<Vec<ChartLeaderboardScore>, Error> {
+		crate::common::validate_chartkey(chartkey)?;
+		// These can be huge, so don't parse them into one big JSON tree if avoidable
+		let response = self
+			.generic_request(
+				reqwest::Method::GET,
+				&format!("charts/{}/leaderboards", chartkey),
+				|x| x,
 				RequestContext::default(),
+				true,
 			)
 			.await?;
 
-		json.array()?
-			.iter()
-			.map(|json| {
-				Ok(ChartLeaderboardScore {
-					scorekey: json["id"].parse()?,
-					wifescore: json["attributes"]["wife"].wifescore_percent_float()?,
-					max_combo: json["attributes"]["maxCombo"].u32_()?,
-					is_valid: json["attributes"]["valid"].bool_()?,
-					modifiers: json["attributes"]["modifiers"].string()?,
-					has_chord_cohesion: !json["attributes"]["noCC"].bool_()?,
-					rate: json["attributes"]["rate"].rate_float()?,
-					datetime: json["attributes"]["datetime"].string()?,
-					ssr: etterna::Skillsets8 {
-						overall: json["attributes"]["skillsets"]["Overall"].f32_()?,
-						stream: json["attributes"]["skillsets"]["Stream"].f32_()?,
-						jumpstream: json["attributes"]["skillsets"]["Jumpstream"].f32_()?,
-						handstream: json["attributes"]["skillsets"]["Handstream"].f32_()?,
-						stamina: json["attributes"]["skillsets"]["Stamina"].f32_()?,
-						jackspeed: json["attributes"]["skillsets"]["JackSpeed"].f32_()?,
-						chordjack: json["attributes"]["skillsets"]["Chordjack"].f32_()?,
-						technical: json["attributes"]["skillsets"]["Technical"].f32_()?,
-					},
-					judgements: parse_judgements(&json["attributes"]["judgements"])?,
-					has_replay: json["attributes"]["hasReplay"].bool_()?, // API docs are wrong again
-					user: ScoreUser {
-						username: json["attributes"]["user"]["userName"].string()?,
-						avatar: json["attributes"]["user"]["avatar"].string()?,
-						country_code: json["attributes"]["user"]["countryCode"].string()?,
-						overall_rating: json["attributes"]["user"]["playerRating"].f32_()?,
-					},
-				})
-			})
-			.collect()
+		let mut scores = Vec::new();
+		crate::common::stream_array_field(&response, "data", |json| {
+			scores.push(parse_chart_leaderboard_score(&json))
+		})?;
+		self.parse_settings.collect("chart leaderboard", scores)
 	}
 
 	/// Retrieves the player leaderboard for the given country.
@@ -693,10 +1511,10 @@ impl Session {
 	///
 	/// # Example
 	/// ```rust,no_run
-	/// # fn main() -> Result<(), etternaonline_api::Error> {
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
 	/// # use etternaonline_api::v2::*;
 	/// # let mut session: Session = unimplemented!();
-	/// let leaderboard = session.country_leaderboard("DE")?;
+	/// let leaderboard = session.country_leaderboard("DE").await?;
 	///
 	/// println!(
 	/// 	"The best German Etterna player is {} with a rating of {}",
@@ -715,40 +1533,160 @@ impl Session {
 				RequestContext::default(),
 			)
 			.await?;
+		let json = Json::new(&json, "data");
 
-		json.array()?
-			.iter()
-			.map(|json| {
-				Ok(LeaderboardEntry {
-					user: ScoreUser {
-						username: json["attributes"]["user"]["username"].string()?,
-						avatar: json["attributes"]["user"]["avatar"].string()?,
-						country_code: json["attributes"]["user"]["countryCode"].string()?,
-						overall_rating: json["attributes"]["user"]["Overall"].f32_()?,
-					},
-					rating: etterna::Skillsets8 {
-						overall: json["attributes"]["user"]["Overall"].f32_()?,
-						stream: json["attributes"]["skillsets"]["Stream"].f32_()?,
-						jumpstream: json["attributes"]["skillsets"]["Jumpstream"].f32_()?,
-						handstream: json["attributes"]["skillsets"]["Handstream"].f32_()?,
-						stamina: json["attributes"]["skillsets"]["Stamina"].f32_()?,
-						jackspeed: json["attributes"]["skillsets"]["JackSpeed"].f32_()?,
-						chordjack: json["attributes"]["skillsets"]["Chordjack"].f32_()?,
-						technical: json["attributes"]["skillsets"]["Technical"].f32_()?,
-					},
-				})
-			})
-			.collect()
+		self.parse_settings.collect(
+			"leaderboard",
+			json.elements()?.map(|json| parse_leaderboard_entry(&json)),
+		)
+	}
+
+	/// Retrieves the given range of the player leaderboard for the given country. Positions are
+	/// zero-based, so `0..100` are the top 100 players and `100..200` the next 100.
+	///
+	/// The range is fetched in pages of at most 100 players by following EO's pagination links, so
+	/// large ranges take multiple requests. Ranges extending past the last player are cut short.
+	///
+	/// # Errors
+	/// - [`Error::NoUsersFound`] if there are no users registered in this country
+	///
+	/// # Example
+	/// ```rust,no_run
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
+	/// # use etternaonline_api::v2::*;
+	/// # let mut session: Session = unimplemented!();
+	/// let leaderboard = session.country_leaderboard_range("DE", 100..200).await?;
+	/// for (i, entry) in leaderboard.iter().enumerate() {
+	/// 	println!("#{}: {}", 101 + i, entry.user.username);
+	/// }
+	/// # Ok(()) }
+	/// ```
+	pub async fn country_leaderboard_range(
+		&self,
+		country_code: &str,
+		range: impl crate::web::EoRange,
+	) -> Result<Vec<LeaderboardEntry>, Error> {
+		const PAGE_SIZE: u32 = 100;
+
+		// A length of zero means the full range, see the RangeFull impl of EoRange
+		let (start, length) = range.start_length();
+		let (max_items, limit) = match length {
+			0 => (usize::MAX, PAGE_SIZE),
+			length => (length as usize, u32::min(length, PAGE_SIZE)),
+		};
+
+		let first_page = self
+			.fetch_page(&PageCursor::from_path(format!(
+				"leaderboard/{}?page[offset]={}&page[limit]={}",
+				country_code, start, limit
+			)))
+			.await?;
+		let mut entries = self.collect_pages(first_page, max_items).await?;
+		entries.truncate(max_items);
+		Ok(entries)
+	}
+
+	/// Retrieves the first page of the given country's player leaderboard. Unlike
+	/// [`Self::country_leaderboard`], this gives access to the following pages via
+	/// [`Page::next`].
+	///
+	/// # Errors
+	/// - [`Error::NoUsersFound`] if there are no users registered in this country
+	///
+	/// # Example
+	/// ```rust,no_run
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
+	/// # use etternaonline_api::v2::*;
+	/// # let mut session: Session = unimplemented!();
+	/// let first_page = session.country_leaderboard_page("DE").await?;
+	/// if let Some(cursor) = &first_page.next {
+	/// 	let second_page = session.fetch_page(cursor).await?;
+	/// }
+	///
+	/// // Or just retrieve everything
+	/// let leaderboard = session.all_pages(first_page).await?;
+	/// # Ok(()) }
+	/// ```
+	pub async fn country_leaderboard_page(
+		&self,
+		country_code: &str,
+	) -> Result<Page<LeaderboardEntry>, Error> {
+		self.fetch_page(&PageCursor::from_path(format!(
+			"leaderboard/{}",
+			country_code
+		)))
+		.await
+	}
+
+	/// Retrieves the first page of the worldwide player leaderboard. See
+	/// [`Self::country_leaderboard_page`]
+	pub async fn world_leaderboard_page(&self) -> Result<Page<LeaderboardEntry>, Error> {
+		self.country_leaderboard_page("").await
+	}
+
+	/// Retrieves the page that the cursor points to, e.g. [`Page::next`] of a previous page
+	pub async fn fetch_page<T>(&self, cursor: &PageCursor<T>) -> Result<Page<T>, Error> {
+		let response = self
+			.generic_request(
+				reqwest::Method::GET,
+				&cursor.path,
+				|x| x,
+				RequestContext::default(),
+				true,
+			)
+			.await?;
+		let response: serde_json::Value = serde_json::from_str(&response)?;
+
+		let items = self.parse_settings.collect(
+			&cursor.path,
+			Json::new(&response["data"], "data")
+				.elements()?
+				.map(|json| (cursor.parse_item)(&json)),
+		)?;
+		let meta = match &response["meta"] {
+			serde_json::Value::Object(meta) => meta.clone(),
+			_ => serde_json::Map::new(),
+		};
+		let next = next_page_path(&response)?.map(|path| PageCursor {
+			path,
+			parse_item: cursor.parse_item,
+		});
+
+		Ok(Page { items, meta, next })
+	}
+
+	/// Retrieves all pages following the given one, and returns the items of all pages together
+	pub async fn all_pages<T>(&self, first_page: Page<T>) -> Result<Vec<T>, Error> {
+		self.collect_pages(first_page, usize::MAX).await
+	}
+
+	/// Like [`Self::all_pages`], but stops fetching pages once there are at least `max_items`
+	async fn collect_pages<T>(
+		&self,
+		first_page: Page<T>,
+		max_items: usize,
+	) -> Result<Vec<T>, Error> {
+		let mut items = first_page.items;
+		let mut next = first_page.next.filter(|_| items.len() < max_items);
+		while let Some(cursor) = next {
+			let page = self.fetch_page(&cursor).await?;
+			items.extend(page.items);
+			// Don't loop forever if EO links a page to itself
+			next = page
+				.next
+				.filter(|next| next.path != cursor.path && items.len() < max_items);
+		}
+		Ok(items)
 	}
 
 	/// Retrieves the worldwide leaderboard of players.
 	///
 	/// # Example
 	/// ```rust,no_run
-	/// # fn main() -> Result<(), etternaonline_api::Error> {
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
 	/// # use etternaonline_api::v2::*;
 	/// # let mut session: Session = unimplemented!();
-	/// let leaderboard = session.world_leaderboard()?;
+	/// let leaderboard = session.world_leaderboard().await?;
 	///
 	/// println!(
 	/// 	"The world's best Etterna player is {} with a rating of {}",
@@ -761,6 +1699,62 @@ impl Session {
 		self.country_leaderboard("").await
 	}
 
+	/// Retrieves the given range of the worldwide leaderboard of players. See
+	/// [`Self::country_leaderboard_range`] for details.
+	///
+	/// # Example
+	/// ```rust,no_run
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
+	/// # use etternaonline_api::v2::*;
+	/// # let mut session: Session = unimplemented!();
+	/// // Places 501 to 1000
+	/// let leaderboard = session.world_leaderboard_range(500..1000).await?;
+	/// # Ok(()) }
+	/// ```
+	pub async fn world_leaderboard_range(
+		&self,
+		range: impl crate::web::EoRange,
+	) -> Result<Vec<LeaderboardEntry>, Error> {
+		self.country_leaderboard_range("", range).await
+	}
+
+	/// Retrieves the player leaderboards of multiple countries, keyed by country code. The requests
+	/// are made one after another, so they're subject to this session's rate limit like any other
+	/// request.
+	///
+	/// Countries without any registered users are mapped to an empty leaderboard. Duplicate country
+	/// codes are only fetched once.
+	///
+	/// # Example
+	/// ```rust,no_run
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
+	/// # use etternaonline_api::v2::*;
+	/// # let mut session: Session = unimplemented!();
+	/// let leaderboards = session.country_leaderboards(&["DE", "AT", "CH"]).await?;
+	/// for (country_code, leaderboard) in &leaderboards {
+	/// 	println!("{} has {} players", country_code, leaderboard.len());
+	/// }
+	/// # Ok(()) }
+	/// ```
+	pub async fn country_leaderboards(
+		&self,
+		country_codes: &[&str],
+	) -> Result<std::collections::HashMap<String, Vec<LeaderboardEntry>>, Error> {
+		let mut leaderboards = std::collections::HashMap::new();
+		for &country_code in country_codes {
+			if leaderboards.contains_key(country_code) {
+				continue;
+			}
+			let leaderboard = match self.country_leaderboard(country_code).await {
+				Ok(leaderboard) => leaderboard,
+				Err(Error::NoUsersFound) => Vec::new(),
+				Err(e) => return Err(e),
+			};
+			leaderboards.insert(country_code.to_owned(), leaderboard);
+		}
+		Ok(leaderboards)
+	}
+
 	/// Retrieves the user's favorites. Returns a vector of chartkeys.
 	///
 	/// # Errors
@@ -768,10 +1762,10 @@ impl Session {
 	///
 	/// # Example
 	/// ```rust,no_run
-	/// # fn main() -> Result<(), etternaonline_api::Error> {
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
 	/// # use etternaonline_api::v2::*;
 	/// # let mut session: Session = unimplemented!();
-	/// let favorites = session.user_favorites("kangalioo")?;
+	/// let favorites = session.user_favorites("kangalioo").await?;
 	/// println!("kangalioo has {} favorites", favorites.len());
 	/// # Ok(()) }
 	/// ```
@@ -784,13 +1778,85 @@ impl Session {
 				},
 			)
 			.await?;
+		let json = Json::new(&json, "data");
 
-		json.array()?
-			.iter()
-			.map(|obj| Ok(obj["attributes"]["chartkey"].string()?))
-			.collect()
+		self.parse_settings.collect(
+			"favorites",
+			json.elements()?
+				.map(|obj| Ok(obj.at("attributes").at("chartkey").string()?)),
+		)
+	}
+
+	/// Like [`Self::user_favorites`], but with the song name, difficulty and MSD of each chart.
+	/// **This can be expensive:** every favorite that the user hasn't set one of their top
+	/// [`FAVORITES_TOP_SCORES_LIMIT`](Self::FAVORITES_TOP_SCORES_LIMIT) Stream scores on costs two
+	/// more requests, so a user with hundreds of such favorites means hundreds of requests.
+	///
+	/// EO's v2 API can't look up charts directly, so the information is gathered from the user's
+	/// top Stream scores in a single request. For favorites that aren't among them, the song name
+	/// is looked up through the best score on the chart's leaderboard; difficulty and MSD stay
+	/// unknown for those. All requests go through the rate limiter.
+	///
+	/// # Errors
+	/// - [`Error::UserNotFound`] if the supplied username was not found
+	pub async fn user_favorites_detailed(
+		&self,
+		username: &str,
+	) -> Result<Vec<FavoriteChart>, Error> {
+		let favorites = self.user_favorites(username).await?;
+		if favorites.is_empty() {
+			return Ok(Vec::new());
+		}
+
+		// There's no overall variant, but the skillset only decides which scores are cut off by the
+		// limit, which doesn't happen for most players
+		let top_scores = self
+			.user_top_skillset_scores(
+				username,
+				Skillset7::Stream,
+				Self::FAVORITES_TOP_SCORES_LIMIT,
+			)
+			.await?;
+
+		let mut detailed = Vec::with_capacity(favorites.len());
+		for chartkey in favorites {
+			let chartkey: Chartkey = chartkey.parse().map_err(|_| {
+				Error::InvalidDataStructure(format!("invalid chartkey {}", chartkey))
+			})?;
+			let favorite = match top_scores.iter().find(|s| s.chartkey == chartkey) {
+				Some(score) => FavoriteChart {
+					song_name: Some(score.song_name.clone()),
+					difficulty: Some(score.difficulty),
+					msd: Some(score.base_msd.clone()),
+					chartkey,
+				},
+				None => {
+					let leaderboard = match self.chart_leaderboard(&chartkey).await {
+						Ok(leaderboard) => leaderboard,
+						Err(Error::ChartNotTracked) => Vec::new(),
+						Err(e) => return Err(e),
+					};
+					let song_name = match leaderboard.first() {
+						Some(best) => Some(self.score_data(&best.scorekey).await?.song_name),
+						None => None,
+					};
+					FavoriteChart {
+						song_name,
+						difficulty: None,
+						msd: None,
+						chartkey,
+					}
+				}
+			};
+			detailed.push(favorite);
+		}
+		Ok(detailed)
 	}
 
+	/// How many of the user's top scores [`Self::user_favorites_detailed`] searches for chart
+	/// information
+	pub const FAVORITES_TOP_SCORES_LIMIT: u32 = 1000;
+
 	/// Add a chart to the user's favorites.
 	///
 	/// # Errors
@@ -799,11 +1865,11 @@ impl Session {
 	///
 	/// # Example
 	/// ```rust,no_run
-	/// # fn main() -> Result<(), etternaonline_api::Error> {
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
 	/// # use etternaonline_api::v2::*;
 	/// # let mut session: Session = unimplemented!();
 	/// // Favorite Game Time
-	/// session.add_user_favorite("kangalioo", "X4a15f62b66a80b62ec64521704f98c6c03d98e03")?;
+	/// session.add_user_favorite("kangalioo", "X4a15f62b66a80b62ec64521704f98c6c03d98e03").await?;
 	/// # Ok(()) }
 	/// ```
 	pub async fn add_user_favorite(
@@ -812,6 +1878,7 @@ impl Session {
 		chartkey: impl AsRef<str>,
 	) -> Result<(), Error> {
 		let chartkey = chartkey.as_ref();
+		crate::common::validate_chartkey(chartkey)?;
 		self.request(
 			reqwest::Method::POST,
 			&format!("user/{}/favorites", username),
@@ -829,11 +1896,11 @@ impl Session {
 	///
 	/// # Example
 	/// ```rust,no_run
-	/// # fn main() -> Result<(), etternaonline_api::Error> {
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
 	/// # use etternaonline_api::v2::*;
 	/// # let mut session: Session = unimplemented!();
 	/// // Unfavorite Game Time
-	/// session.remove_user_favorite("kangalioo", "X4a15f62b66a80b62ec64521704f98c6c03d98e03")?;
+	/// session.remove_user_favorite("kangalioo", "X4a15f62b66a80b62ec64521704f98c6c03d98e03").await?;
 	/// # Ok(()) }
 	/// ```
 	pub async fn remove_user_favorite(
@@ -841,6 +1908,7 @@ impl Session {
 		username: &str,
 		chartkey: impl AsRef<str>,
 	) -> Result<(), Error> {
+		crate::common::validate_chartkey(chartkey.as_ref())?;
 		self.request(
 			reqwest::Method::DELETE,
 			&format!("user/{}/favorites/{}", username, chartkey.as_ref()),
@@ -854,6 +1922,116 @@ impl Session {
 		Ok(())
 	}
 
+	/// Makes sure the chart is in the user's favorites. Unlike [`Self::add_user_favorite`], a
+	/// chart that's already favorited is not an error.
+	///
+	/// Returns whether the chart was added, i.e. `false` if it was already favorited.
+	///
+	/// # Errors
+	/// - [`Error::ChartNotTracked`] if the chartkey provided is not tracked by EO
+	///
+	/// # Example
+	/// ```rust,no_run
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
+	/// # use etternaonline_api::v2::*;
+	/// # let mut session: Session = unimplemented!();
+	/// if session.ensure_favorite("kangalioo", "X4a15f62b66a80b62ec64521704f98c6c03d98e03").await? {
+	/// 	println!("Added Game Time to the favorites");
+	/// }
+	/// # Ok(()) }
+	/// ```
+	pub async fn ensure_favorite(
+		&self,
+		username: &str,
+		chartkey: impl AsRef<str>,
+	) -> Result<bool, Error> {
+		match self.add_user_favorite(username, chartkey).await {
+			Ok(()) => Ok(true),
+			Err(Error::ChartAlreadyFavorited) => Ok(false),
+			Err(e) => Err(e),
+		}
+	}
+
+	/// Makes sure the chart is not in the user's favorites.
+	///
+	/// Returns whether the chart was removed, i.e. `false` if it wasn't favorited. EO doesn't
+	/// report whether a removal changed anything, so this retrieves the user's favorites first.
+	///
+	/// # Errors
+	/// - [`Error::UserNotFound`] if the supplied username was not found
+	///
+	/// # Example
+	/// ```rust,no_run
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
+	/// # use etternaonline_api::v2::*;
+	/// # let mut session: Session = unimplemented!();
+	/// session.ensure_not_favorite("kangalioo", "X4a15f62b66a80b62ec64521704f98c6c03d98e03").await?;
+	/// # Ok(()) }
+	/// ```
+	pub async fn ensure_not_favorite(
+		&self,
+		username: &str,
+		chartkey: impl AsRef<str>,
+	) -> Result<bool, Error> {
+		let chartkey = chartkey.as_ref();
+		let favorites = self.user_favorites(username).await?;
+		if !favorites.iter().any(|favorite| favorite == chartkey) {
+			return Ok(false);
+		}
+
+		self.remove_user_favorite(username, chartkey).await?;
+		Ok(true)
+	}
+
+	/// Makes the user's favorites exactly the given charts: favorites that aren't in `desired`
+	/// are removed and missing ones are added. Returns which chartkeys were added and removed.
+	///
+	/// If a request fails midway, the error is returned and the changes made until then are kept.
+	///
+	/// # Errors
+	/// - [`Error::UserNotFound`] if the supplied username was not found
+	/// - [`Error::ChartNotTracked`] if a desired chartkey is not tracked by EO
+	///
+	/// # Example
+	/// ```rust,no_run
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
+	/// # use etternaonline_api::v2::*;
+	/// # let mut session: Session = unimplemented!();
+	/// let playlist = ["X4a15f62b66a80b62ec64521704f98c6c03d98e03".parse().unwrap()];
+	/// let changes = session.sync_favorites("kangalioo", &playlist).await?;
+	/// println!("Added {}, removed {}", changes.added.len(), changes.removed.len());
+	/// # Ok(()) }
+	/// ```
+	pub async fn sync_favorites(
+		&self,
+		username: &str,
+		desired: &[Chartkey],
+	) -> Result<FavoritesSync, Error> {
+		let current = self.user_favorites(username).await?;
+		let mut changes = FavoritesSync::default();
+
+		for chartkey in &current {
+			if !desired.iter().any(|desired| desired.as_str() == chartkey) {
+				self.remove_user_favorite(username, chartkey).await?;
+				changes.removed.push(chartkey.clone());
+			}
+		}
+		for chartkey in desired {
+			let chartkey = chartkey.as_str();
+			if current.iter().any(|current| current == chartkey)
+				|| changes.added.iter().any(|added| added == chartkey)
+			{
+				continue;
+			}
+			// Someone else may have added it in the meantime, which is fine
+			if self.ensure_favorite(username, chartkey).await? {
+				changes.added.push(chartkey.to_owned());
+			}
+		}
+
+		Ok(changes)
+	}
+
 	/// Retrieves a user's score goals.
 	///
 	/// # Errors
@@ -862,10 +2040,10 @@ impl Session {
 	///
 	/// # Example
 	/// ```rust,no_run
-	/// # fn main() -> Result<(), etternaonline_api::Error> {
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
 	/// # use etternaonline_api::v2::*;
 	/// # let mut session: Session = unimplemented!();
-	/// let score_goals = session.user_goals("theropfather")?;
+	/// let score_goals = session.user_goals("theropfather").await?;
 	///
 	/// println!("theropfather has {} goals", score_goals.len());
 	/// # Ok(()) }
@@ -879,23 +2057,36 @@ impl Session {
 				},
 			)
 			.await?;
+		let json = Json::new(&json, "data");
 
-		json.array()?
-			.iter()
-			.map(|json| {
+		self.parse_settings.collect(
+			"goals",
+			json.elements()?.map(|json| {
 				Ok(ScoreGoal {
-					chartkey: json["attributes"]["chartkey"].parse()?,
-					rate: json["attributes"]["rate"].rate_float()?,
-					wifescore: json["attributes"]["wife"].wifescore_proportion_float()?,
-					time_assigned: json["attributes"]["timeAssigned"].string()?,
-					time_achieved: if json["attributes"]["achieved"].bool_int()? {
-						Some(json["attributes"]["timeAchieved"].string()?)
+					chartkey: json.at("attributes").at("chartkey").parse()?,
+					rate: json.at("attributes").at("rate").rate_float()?,
+					wifescore: json
+						.at("attributes")
+						.at("wife")
+						.wifescore_proportion_float()?,
+					time_assigned: json.at("attributes").at("timeAssigned").string()?,
+					time_achieved: if json.at("attributes").at("achieved").bool_int()? {
+						Some(json.at("attributes").at("timeAchieved").string()?)
 					} else {
 						None
 					},
+					#[cfg(feature = "extra-fields")]
+					extra: json.at("attributes").unknown_fields(&[
+						"chartkey",
+						"rate",
+						"wife",
+						"timeAssigned",
+						"achieved",
+						"timeAchieved",
+					]),
 				})
-			})
-			.collect()
+			}),
+		)
 	}
 
 	/// Add a new score goal.
@@ -907,7 +2098,7 @@ impl Session {
 	///
 	/// # Example
 	/// ```rust,no_run
-	/// # fn main() -> Result<(), etternaonline_api::Error> {
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
 	/// # use etternaonline_api::v2::*;
 	/// # let mut session: Session = unimplemented!();
 	/// // Add a Game Time 1.0x AA score goal
@@ -917,7 +2108,7 @@ impl Session {
 	/// 	1.0,
 	/// 	0.93,
 	/// 	"2020-07-13 22:48:26",
-	/// )?;
+	/// ).await?;
 	/// # Ok(()) }
 	/// ```
 	// TODO: somehow enforce that `time_assigned` is valid ISO 8601
@@ -930,6 +2121,7 @@ impl Session {
 		time_assigned: &str,
 	) -> Result<(), Error> {
 		let chartkey = chartkey.as_ref();
+		crate::common::validate_chartkey(chartkey)?;
 		self.request(
 			reqwest::Method::POST,
 			&format!("user/{}/goals", username),
@@ -956,19 +2148,19 @@ impl Session {
 	///
 	/// # Example
 	/// ```rust,no_run
-	/// # fn main() -> Result<(), etternaonline_api::Error> {
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
 	/// # use etternaonline_api::v2::*;
 	/// # let mut session: Session = unimplemented!();
 	/// // Let's delete theropfather's first score goal
 	///
-	/// let score_goal = session.user_goals("theropfather")?[0];
+	/// let score_goal = session.user_goals("theropfather").await?[0];
 	///
 	/// session.remove_user_goal(
 	/// 	"theropfather",
 	/// 	score_goal.chartkey,
 	/// 	score_goal.rate,
 	/// 	score_goal.wifescore
-	/// )?;
+	/// ).await?;
 	/// # Ok(()) }
 	/// ```
 	pub async fn remove_user_goal(
@@ -978,6 +2170,7 @@ impl Session {
 		rate: Rate,
 		wifescore: Wifescore,
 	) -> Result<(), Error> {
+		crate::common::validate_chartkey(chartkey.as_ref())?;
 		self.request(
 			reqwest::Method::DELETE,
 			&format!(
@@ -1001,18 +2194,18 @@ impl Session {
 	///
 	/// # Example
 	/// ```rust,no_run
-	/// # fn main() -> Result<(), etternaonline_api::Error> {
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
 	/// # use etternaonline_api::v2::*;
 	/// # use etterna::*;
 	/// # let mut session: Session = unimplemented!();
 	/// // Let's up kangalioo's first score goal's rate by 0.05
 	///
-	/// let mut score_goal = &mut session.user_goals("kangalioo")?[0];
+	/// let mut score_goal = &mut session.user_goals("kangalioo").await?[0];
 	///
 	/// // Add 0.05 to the rate
 	/// score_goal.rate += Rate::from(0.05);
 	///
-	/// session.update_user_goal("kangalioo", score_goal)?;
+	/// session.update_user_goal("kangalioo", score_goal).await?;
 	/// # Ok(()) }
 	/// ```
 	pub async fn update_user_goal(&self, username: &str, goal: &ScoreGoal) -> Result<(), Error> {
@@ -1050,6 +2243,117 @@ impl Session {
 		Ok(())
 	}
 
+	/// Adds a score goal, or if the user already has a goal on this chart, updates it to the given
+	/// rate, wifescore and assignment time. Whether the existing goal was achieved is kept as is.
+	///
+	/// Returns whether a new goal was added, i.e. `false` if an existing one was updated.
+	///
+	/// # Errors
+	/// - [`Error::ChartNotTracked`] if the chartkey provided is not tracked by EO
+	/// - [`Error::DatabaseError`] if there was a problem with the database
+	/// - [`Error::GoalAlreadyExists`] if EO rejected the goal as existing, but no goal on this
+	///   chart could be found in the user's goals
+	///
+	/// # Example
+	/// ```rust,no_run
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
+	/// # use etternaonline_api::v2::*;
+	/// # use etterna::*;
+	/// # let mut session: Session = unimplemented!();
+	/// // Make sure kangalioo's Game Time goal is 1.1x AA
+	/// session.upsert_user_goal(
+	/// 	"kangalioo",
+	/// 	"X4a15f62b66a80b62ec64521704f98c6c03d98e03",
+	/// 	Rate::from_f32(1.1).unwrap(),
+	/// 	Wifescore::from_percent(93.0).unwrap(),
+	/// 	"2020-07-13 22:48:26",
+	/// ).await?;
+	/// # Ok(()) }
+	/// ```
+	pub async fn upsert_user_goal(
+		&self,
+		username: &str,
+		chartkey: impl AsRef<str>,
+		rate: Rate,
+		wifescore: Wifescore,
+		time_assigned: &str,
+	) -> Result<bool, Error> {
+		let chartkey = chartkey.as_ref();
+		match self
+			.add_user_goal(
+				username,
+				chartkey,
+				rate.as_f32() as f64,
+				wifescore.as_proportion() as f64,
+				time_assigned,
+			)
+			.await
+		{
+			Ok(()) => return Ok(true),
+			Err(Error::GoalAlreadyExists) => {}
+			Err(e) => return Err(e),
+		}
+
+		let goals = self.user_goals(username).await?;
+		// If there are several goals on this chart, prefer the one with the same rate
+		let mut goal = match goals
+			.iter()
+			.filter(|goal| goal.chartkey.as_str() == chartkey)
+			.max_by_key(|goal| goal.rate == rate)
+		{
+			Some(goal) => goal.clone(),
+			None => return Err(Error::GoalAlreadyExists),
+		};
+
+		goal.rate = rate;
+		goal.wifescore = wifescore;
+		goal.time_assigned = time_assigned.to_owned();
+		self.update_user_goal(username, &goal).await?;
+
+		Ok(false)
+	}
+
+	/// Adds all the goals to the user's goals, e.g. ones read from a backup made with
+	/// [`crate::export::goals`]. Goals on charts the user already has a goal on replace the
+	/// existing one, like in [`Self::upsert_user_goal`]. Achieved goals are marked as achieved
+	/// with their original time.
+	///
+	/// The goals are imported one after another, at the pace of the session's rate limit. If a
+	/// goal fails to import, the import stops and the error is returned; goals before it are
+	/// already imported.
+	///
+	/// # Errors
+	/// - [`Error::ChartNotTracked`] if a goal's chart is not tracked by EO
+	/// - [`Error::DatabaseError`] if there was a problem with the database
+	pub async fn import_user_goals(
+		&self,
+		username: &str,
+		goals: &[ScoreGoal],
+	) -> Result<GoalImportSummary, Error> {
+		let mut summary = GoalImportSummary::default();
+		for goal in goals {
+			let added = self
+				.upsert_user_goal(
+					username,
+					&goal.chartkey,
+					goal.rate,
+					goal.wifescore,
+					&goal.time_assigned,
+				)
+				.await?;
+			if goal.time_achieved.is_some() {
+				self.update_user_goal(username, goal).await?;
+			}
+
+			if added {
+				summary.added += 1;
+			} else {
+				summary.updated += 1;
+			}
+		}
+		Ok(summary)
+	}
+
 	// Let's find out how this works and properly implement it, when I finally find out how to login
 	// into the fucking v2 API again >:(
 	// pub fn pack_list(&self) -> Result<(), Error> {
@@ -1094,3 +2398,37 @@ impl Session {
 	// Ok(())
 	// }
 }
+
+#[cfg(feature = "v2")]
+impl crate::pool::PooledSession for Session {
+	fn time_until_next_request(&self) -> std::time::Duration {
+		self.rate_limiter.time_until_next_slot(&*self.clock)
+	}
+}
+
+#[cfg(feature = "v2")]
+impl crate::session::EoSession for Session {
+	fn user_details<'a>(
+		&'a self,
+		username: &'a str,
+	) -> BoxFuture<'a, Result<crate::session::UserProfile, Error>> {
+		Box::pin(async move { Ok(Session::user_details(self, username).await?.into()) })
+	}
+
+	fn score_data<'a>(
+		&'a self,
+		scorekey: &'a str,
+	) -> BoxFuture<'a, Result<crate::session::Score, Error>> {
+		Box::pin(async move { Ok(Session::score_data(self, scorekey).await?.into()) })
+	}
+
+	fn chart_leaderboard<'a>(
+		&'a self,
+		chartkey: &'a str,
+	) -> BoxFuture<'a, Result<Vec<crate::session::LeaderboardScore>, Error>> {
+		Box::pin(async move {
+			let leaderboard = Session::chart_leaderboard(self, chartkey).await?;
+			Ok(leaderboard.into_iter().map(Into::into).collect())
+		})
+	}
+}
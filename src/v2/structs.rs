@@ -4,12 +4,20 @@ pub use crate::common::structs::*;
 use etterna::*;
 
 /// Details about a user. See [`Session::user_details`](super::Session::user_details)
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Default)]
 #[cfg_attr(
 	feature = "serde",
 	derive(serde::Serialize, serde::Deserialize),
 	serde(crate = "serde_")
 )]
+#[cfg_attr(
+	feature = "wire",
+	serde(
+		into = "super::wire::WireUserDetails",
+		try_from = "super::wire::WireUserDetails"
+	)
+)]
+#[non_exhaustive]
 pub struct UserDetails {
 	pub username: String,
 	pub about_me: String,
@@ -21,6 +29,9 @@ pub struct UserDetails {
 	pub player_rating: f32,
 	pub default_modifiers: Option<String>,
 	pub rating: Skillsets8,
+	/// Fields of the server response that this crate doesn't model (yet)
+	#[cfg(feature = "extra-fields")]
+	pub extra: std::collections::HashMap<String, serde_json::Value>,
 }
 
 /// Score from a top scores enumeration like [`Session::user_top_10_scores`](super::Session::user_top_10_scores)
@@ -30,6 +41,14 @@ pub struct UserDetails {
 	derive(serde::Serialize, serde::Deserialize),
 	serde(crate = "serde_")
 )]
+#[cfg_attr(
+	feature = "wire",
+	serde(
+		into = "super::wire::WireTopScore",
+		try_from = "super::wire::WireTopScore"
+	)
+)]
+#[non_exhaustive]
 pub struct TopScore {
 	pub scorekey: Scorekey,
 	pub song_name: String,
@@ -39,6 +58,28 @@ pub struct TopScore {
 	pub difficulty: Difficulty,
 	pub chartkey: Chartkey,
 	pub base_msd: Skillsets8,
+	/// Fields of the server response that this crate doesn't model (yet)
+	#[cfg(feature = "extra-fields")]
+	pub extra: std::collections::HashMap<String, serde_json::Value>,
+}
+
+impl TopScore {
+	/// Create a top score with the given keys. All other fields are zeroed and can be filled in
+	/// afterwards
+	pub fn new(scorekey: Scorekey, chartkey: Chartkey, difficulty: Difficulty) -> Self {
+		Self {
+			scorekey,
+			song_name: String::new(),
+			ssr_overall: 0.0,
+			wifescore: Wifescore::default(),
+			rate: Rate::default(),
+			difficulty,
+			chartkey,
+			base_msd: Skillsets8::default(),
+			#[cfg(feature = "extra-fields")]
+			extra: Default::default(),
+		}
+	}
 }
 
 /// Score from a latest scores enumeration like [`Session::user_latest_scores`](super::Session::user_latest_scores)
@@ -48,6 +89,14 @@ pub struct TopScore {
 	derive(serde::Serialize, serde::Deserialize),
 	serde(crate = "serde_")
 )]
+#[cfg_attr(
+	feature = "wire",
+	serde(
+		into = "super::wire::WireLatestScore",
+		try_from = "super::wire::WireLatestScore"
+	)
+)]
+#[non_exhaustive]
 pub struct LatestScore {
 	pub scorekey: Scorekey,
 	pub song_name: String,
@@ -55,6 +104,26 @@ pub struct LatestScore {
 	pub wifescore: Wifescore,
 	pub rate: Rate,
 	pub difficulty: Difficulty,
+	/// Fields of the server response that this crate doesn't model (yet)
+	#[cfg(feature = "extra-fields")]
+	pub extra: std::collections::HashMap<String, serde_json::Value>,
+}
+
+impl LatestScore {
+	/// Create a latest score with the given scorekey and difficulty. All other fields are zeroed
+	/// and can be filled in afterwards
+	pub fn new(scorekey: Scorekey, difficulty: Difficulty) -> Self {
+		Self {
+			scorekey,
+			song_name: String::new(),
+			ssr_overall: 0.0,
+			wifescore: Wifescore::default(),
+			rate: Rate::default(),
+			difficulty,
+			#[cfg(feature = "extra-fields")]
+			extra: Default::default(),
+		}
+	}
 }
 
 /// Score from a [top scores per skillset enumeration](super::Session::user_top_scores_per_skillset)
@@ -64,6 +133,14 @@ pub struct LatestScore {
 	derive(serde::Serialize, serde::Deserialize),
 	serde(crate = "serde_")
 )]
+#[cfg_attr(
+	feature = "wire",
+	serde(
+		into = "super::wire::WireTopScorePerSkillset",
+		try_from = "super::wire::WireTopScorePerSkillset"
+	)
+)]
+#[non_exhaustive]
 pub struct TopScorePerSkillset {
 	pub song_name: String,
 	pub rate: Rate,
@@ -74,6 +151,22 @@ pub struct TopScorePerSkillset {
 	pub ssr: Skillsets8,
 }
 
+impl TopScorePerSkillset {
+	/// Create a top score with the given keys. All other fields are zeroed and can be filled in
+	/// afterwards
+	pub fn new(scorekey: Scorekey, chartkey: Chartkey, difficulty: Difficulty) -> Self {
+		Self {
+			song_name: String::new(),
+			rate: Rate::default(),
+			wifescore: Wifescore::default(),
+			chartkey,
+			scorekey,
+			difficulty,
+			ssr: Skillsets8::default(),
+		}
+	}
+}
+
 /// User's best scores in each skillset category. See [`Session::user_top_scores_per_skillset`](super::Session::user_top_scores_per_skillset)
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(
@@ -92,6 +185,21 @@ pub struct UserTopScoresPerSkillset {
 	pub technical: Vec<TopScorePerSkillset>,
 }
 
+/// Everything needed to display a user's profile at a glance. See
+/// [`Session::user_profile_card`](super::Session::user_profile_card)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct UserProfileCard {
+	pub details: UserDetails,
+	pub ranks: UserRank,
+	pub top_scores: Vec<TopScore>,
+}
+
 /// Generic information about a score
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(
@@ -99,6 +207,14 @@ pub struct UserTopScoresPerSkillset {
 	derive(serde::Serialize, serde::Deserialize),
 	serde(crate = "serde_")
 )]
+#[cfg_attr(
+	feature = "wire",
+	serde(
+		into = "super::wire::WireScoreData",
+		try_from = "super::wire::WireScoreData"
+	)
+)]
+#[non_exhaustive]
 pub struct ScoreData {
 	pub scorekey: Scorekey,
 	pub modifiers: String,
@@ -110,14 +226,46 @@ pub struct ScoreData {
 	pub has_chord_cohesion: bool,
 	pub judgements: FullJudgements,
 	pub replay: Option<Replay>,
+	/// The replay exactly as EO sent it, if it was present but couldn't be parsed into
+	/// [`Self::replay`]
+	pub replay_raw: Option<String>,
 	pub user: ScoreUser,
 	pub song_name: String,
 	pub artist: String,
-	pub song_id: u32,
+	pub song_id: SongId,
+	/// Fields of the server response that this crate doesn't model (yet)
+	#[cfg(feature = "extra-fields")]
+	pub extra: std::collections::HashMap<String, serde_json::Value>,
+}
+
+impl ScoreData {
+	/// Create score data with the given scorekey. All other fields are zeroed and can be filled in
+	/// afterwards
+	pub fn new(scorekey: Scorekey) -> Self {
+		Self {
+			scorekey,
+			modifiers: String::new(),
+			ssr: Skillsets8::default(),
+			wifescore: Wifescore::default(),
+			rate: Rate::default(),
+			max_combo: 0,
+			is_valid: false,
+			has_chord_cohesion: false,
+			judgements: FullJudgements::default(),
+			replay: None,
+			replay_raw: None,
+			user: ScoreUser::default(),
+			song_name: String::new(),
+			artist: String::new(),
+			song_id: SongId::default(),
+			#[cfg(feature = "extra-fields")]
+			extra: Default::default(),
+		}
+	}
 }
 
 /// User information contained within a score information struct
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Default)]
 #[cfg_attr(
 	feature = "serde",
 	derive(serde::Serialize, serde::Deserialize),
@@ -137,6 +285,14 @@ pub struct ScoreUser {
 	derive(serde::Serialize, serde::Deserialize),
 	serde(crate = "serde_")
 )]
+#[cfg_attr(
+	feature = "wire",
+	serde(
+		into = "super::wire::WireChartLeaderboardScore",
+		try_from = "super::wire::WireChartLeaderboardScore"
+	)
+)]
+#[non_exhaustive]
 pub struct ChartLeaderboardScore {
 	pub scorekey: Scorekey,
 	pub ssr: Skillsets8,
@@ -150,6 +306,50 @@ pub struct ChartLeaderboardScore {
 	pub has_replay: bool,
 	pub judgements: FullJudgements,
 	pub user: ScoreUser,
+	/// Fields of the server response that this crate doesn't model (yet)
+	#[cfg(feature = "extra-fields")]
+	pub extra: std::collections::HashMap<String, serde_json::Value>,
+}
+
+impl ChartLeaderboardScore {
+	/// Create a leaderboard score with the given scorekey. All other fields are zeroed and can be
+	/// filled in afterwards
+	pub fn new(scorekey: Scorekey) -> Self {
+		Self {
+			scorekey,
+			ssr: Skillsets8::default(),
+			wifescore: Wifescore::default(),
+			rate: Rate::default(),
+			max_combo: 0,
+			is_valid: false,
+			has_chord_cohesion: false,
+			datetime: String::new(),
+			modifiers: String::new(),
+			has_replay: false,
+			judgements: FullJudgements::default(),
+			user: ScoreUser::default(),
+			#[cfg(feature = "extra-fields")]
+			extra: Default::default(),
+		}
+	}
+}
+
+/// A score together with the context needed to announce it. See
+/// [`Session::score_card`](super::Session::score_card)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct ScoreCard {
+	pub score: ScoreData,
+	/// Position of this score on the chart leaderboard, starting at 1. `None` if the score is not
+	/// on the leaderboard, e.g. because the user has a better score on the chart
+	pub leaderboard_rank: Option<u32>,
+	/// The wifescore rescored on J4 using the replay. `None` if the score has no usable replay
+	pub wifescore_j4: Option<Wifescore>,
 }
 
 /// Entry in a score leaderboard
@@ -159,6 +359,13 @@ pub struct ChartLeaderboardScore {
 	derive(serde::Serialize, serde::Deserialize),
 	serde(crate = "serde_")
 )]
+#[cfg_attr(
+	feature = "wire",
+	serde(
+		into = "super::wire::WireLeaderboardEntry",
+		from = "super::wire::WireLeaderboardEntry"
+	)
+)]
 pub struct LeaderboardEntry {
 	pub user: ScoreUser,
 	pub rating: Skillsets8,
@@ -171,10 +378,142 @@ pub struct LeaderboardEntry {
 	derive(serde::Serialize, serde::Deserialize),
 	serde(crate = "serde_")
 )]
+#[cfg_attr(
+	feature = "wire",
+	serde(
+		into = "super::wire::WireScoreGoal",
+		try_from = "super::wire::WireScoreGoal"
+	)
+)]
+#[non_exhaustive]
 pub struct ScoreGoal {
 	pub chartkey: Chartkey,
 	pub rate: Rate,
 	pub wifescore: Wifescore,
 	pub time_assigned: String,
 	pub time_achieved: Option<String>,
+	/// Fields of the server response that this crate doesn't model (yet)
+	#[cfg(feature = "extra-fields")]
+	pub extra: std::collections::HashMap<String, serde_json::Value>,
+}
+
+impl ScoreGoal {
+	/// Create a score goal that hasn't been achieved yet
+	pub fn new(
+		chartkey: Chartkey,
+		rate: Rate,
+		wifescore: Wifescore,
+		time_assigned: String,
+	) -> Self {
+		Self {
+			chartkey,
+			rate,
+			wifescore,
+			time_assigned,
+			time_achieved: None,
+			#[cfg(feature = "extra-fields")]
+			extra: Default::default(),
+		}
+	}
+}
+
+/// A favorited chart with information about it. See
+/// [`Session::user_favorites_detailed`](super::Session::user_favorites_detailed)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct FavoriteChart {
+	pub chartkey: Chartkey,
+	/// None if nobody has a score on the chart, so the song couldn't be looked up
+	pub song_name: Option<String>,
+	/// Only known for charts among the user's top scores
+	pub difficulty: Option<Difficulty>,
+	/// MSD at 1.0x. Only known for charts among the user's top scores
+	pub msd: Option<Skillsets8>,
+}
+
+/// Changes made by [`Session::sync_favorites`](super::Session::sync_favorites)
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct FavoritesSync {
+	/// Chartkeys that were added to the favorites
+	pub added: Vec<String>,
+	/// Chartkeys that were removed from the favorites
+	pub removed: Vec<String>,
+}
+
+/// Result of [`Session::import_user_goals`](super::Session::import_user_goals)
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct GoalImportSummary {
+	/// Number of goals that were newly added
+	pub added: u32,
+	/// Number of goals that replaced an existing goal on the same chart
+	pub updated: u32,
+}
+
+/// A request that changed something on EO, or tried to. See
+/// [`Session::set_audit_callback`](super::Session::set_audit_callback)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct AuditEntry {
+	/// HTTP method, e.g. `POST` or `DELETE`
+	pub method: String,
+	/// Path of the request relative to the API base URL, e.g. `user/kangalioo/favorites`
+	pub endpoint: String,
+	/// Form parameters of the request, with secrets redacted
+	pub parameters: Vec<(String, String)>,
+	/// The error message if the request failed
+	pub error: Option<String>,
+	/// When the request finished
+	pub timestamp: std::time::SystemTime,
+}
+
+/// One page of a response that EO paginates server-side, see e.g.
+/// [`Session::world_leaderboard_page`](super::Session::world_leaderboard_page)
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Page<T> {
+	pub items: Vec<T>,
+	/// The response's `meta` object, e.g. with the total number of items
+	pub meta: serde_json::Map<String, serde_json::Value>,
+	/// Where to continue, or None if this is the last page. Retrieve the next page with
+	/// [`Session::fetch_page`](super::Session::fetch_page)
+	pub next: Option<PageCursor<T>>,
+}
+
+/// Points to a page of a paginated response. Cursors into the player leaderboard can be saved
+/// and restored with [`Self::path`] and [`PageCursor::from_path`], or with serde, to continue
+/// later, e.g. after a crawl was interrupted
+#[derive(Debug, Clone)]
+pub struct PageCursor<T> {
+	/// Request path relative to the API base URL, including the page parameters
+	pub(crate) path: String,
+	pub(crate) parse_item: fn(&crate::extension_traits::Json<'_>) -> Result<T, crate::Error>,
+}
+
+impl<T> PageCursor<T> {
+	/// Request path relative to the API base URL, including the page parameters
+	pub fn path(&self) -> &str {
+		&self.path
+	}
 }
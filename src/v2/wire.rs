@@ -0,0 +1,800 @@
+//! The v2 response JSON that the structs are (de)serialized as with the `wire` feature. v2 follows
+//! JSON:API, so most structs correspond to a resource object: `{"id": ..., "attributes": {...}}`
+
+use std::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+
+use etterna::{Chartkey, Difficulty, Scorekey};
+
+use super::structs::*;
+use crate::common::wire::*;
+use crate::Error;
+
+/// A JSON:API resource object. `type` is the same for all resources of an endpoint, so it isn't
+/// kept: it's ignored when deserializing and left out when serializing
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_")]
+pub(crate) struct Resource<A> {
+	id: Scorekey,
+	attributes: A,
+}
+
+/// A JSON:API resource object whose `id` isn't kept either
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_")]
+pub(crate) struct Attributes<A> {
+	attributes: A,
+}
+
+/// How the user is sent in score data and score leaderboards
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_")]
+struct User {
+	username: String,
+	avatar: String,
+	#[serde(rename = "countryCode")]
+	country_code: String,
+	#[serde(rename = "Overall")]
+	overall: f32,
+}
+
+impl From<ScoreUser> for User {
+	fn from(user: ScoreUser) -> Self {
+		Self {
+			username: user.username,
+			avatar: user.avatar,
+			country_code: user.country_code,
+			overall: user.overall_rating,
+		}
+	}
+}
+
+impl From<User> for ScoreUser {
+	fn from(user: User) -> Self {
+		Self {
+			username: user.username,
+			avatar: user.avatar,
+			country_code: user.country_code,
+			overall_rating: user.overall,
+		}
+	}
+}
+
+/// How the user is sent in chart leaderboards
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_")]
+struct ChartLeaderboardUser {
+	#[serde(rename = "userName")]
+	username: String,
+	avatar: String,
+	#[serde(rename = "countryCode")]
+	country_code: String,
+	#[serde(rename = "playerRating")]
+	player_rating: f32,
+}
+
+impl From<ScoreUser> for ChartLeaderboardUser {
+	fn from(user: ScoreUser) -> Self {
+		Self {
+			username: user.username,
+			avatar: user.avatar,
+			country_code: user.country_code,
+			player_rating: user.overall_rating,
+		}
+	}
+}
+
+impl From<ChartLeaderboardUser> for ScoreUser {
+	fn from(user: ChartLeaderboardUser) -> Self {
+		Self {
+			username: user.username,
+			avatar: user.avatar,
+			country_code: user.country_code,
+			overall_rating: user.player_rating,
+		}
+	}
+}
+
+pub(crate) type WireUserDetails = Attributes<UserDetailsAttributes>;
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_", rename_all = "camelCase")]
+pub(crate) struct UserDetailsAttributes {
+	user_name: String,
+	about_me: String,
+	moderator: bool,
+	patreon: bool,
+	avatar: String,
+	country_code: String,
+	player_rating: f32,
+	/// Empty if the user has none
+	default_modifiers: String,
+	skillsets: Skillsets,
+	#[cfg(feature = "extra-fields")]
+	#[serde(flatten)]
+	extra: std::collections::HashMap<String, serde_json::Value>,
+}
+
+impl From<UserDetails> for WireUserDetails {
+	fn from(user: UserDetails) -> Self {
+		Self {
+			attributes: UserDetailsAttributes {
+				skillsets: Skillsets::without_overall(&user.rating),
+				user_name: user.username,
+				about_me: user.about_me,
+				moderator: user.is_moderator,
+				patreon: user.is_patreon,
+				avatar: user.avatar_url,
+				country_code: user.country_code,
+				player_rating: user.player_rating,
+				default_modifiers: user.default_modifiers.unwrap_or_default(),
+				#[cfg(feature = "extra-fields")]
+				extra: user.extra,
+			},
+		}
+	}
+}
+
+impl TryFrom<WireUserDetails> for UserDetails {
+	type Error = Error;
+
+	fn try_from(Attributes { attributes: user }: WireUserDetails) -> Result<Self, Error> {
+		Ok(Self {
+			rating: user
+				.skillsets
+				.into_skillsets8_with_overall(user.player_rating),
+			username: user.user_name,
+			about_me: user.about_me,
+			is_moderator: user.moderator,
+			is_patreon: user.patreon,
+			avatar_url: user.avatar,
+			country_code: user.country_code,
+			player_rating: user.player_rating,
+			default_modifiers: Some(user.default_modifiers).filter(|m| !m.is_empty()),
+			#[cfg(feature = "extra-fields")]
+			extra: user.extra,
+		})
+	}
+}
+
+pub(crate) type WireTopScore = Resource<TopScoreAttributes>;
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_", rename_all = "camelCase")]
+pub(crate) struct TopScoreAttributes {
+	song_name: String,
+	#[serde(rename = "Overall")]
+	overall: f32,
+	/// In percent
+	wife: f32,
+	rate: f32,
+	difficulty: Difficulty,
+	chart_key: Chartkey,
+	skillsets: Skillsets,
+	#[cfg(feature = "extra-fields")]
+	#[serde(flatten)]
+	extra: std::collections::HashMap<String, serde_json::Value>,
+}
+
+impl From<TopScore> for WireTopScore {
+	fn from(score: TopScore) -> Self {
+		Self {
+			id: score.scorekey,
+			attributes: TopScoreAttributes {
+				song_name: score.song_name,
+				overall: score.ssr_overall,
+				wife: score.wifescore.as_percent(),
+				rate: score.rate.as_f32(),
+				difficulty: score.difficulty,
+				chart_key: score.chartkey,
+				skillsets: Skillsets::without_overall(&score.base_msd),
+				#[cfg(feature = "extra-fields")]
+				extra: score.extra,
+			},
+		}
+	}
+}
+
+impl TryFrom<WireTopScore> for TopScore {
+	type Error = Error;
+
+	fn try_from(
+		Resource {
+			id,
+			attributes: score,
+		}: WireTopScore,
+	) -> Result<Self, Error> {
+		Ok(Self {
+			scorekey: id,
+			song_name: score.song_name,
+			ssr_overall: score.overall,
+			wifescore: wifescore_from_percent(score.wife)?,
+			rate: rate_from_f32(score.rate)?,
+			difficulty: score.difficulty,
+			chartkey: score.chart_key,
+			base_msd: score.skillsets.into_skillsets8_with_overall(score.overall),
+			#[cfg(feature = "extra-fields")]
+			extra: score.extra,
+		})
+	}
+}
+
+pub(crate) type WireLatestScore = Resource<LatestScoreAttributes>;
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_", rename_all = "camelCase")]
+pub(crate) struct LatestScoreAttributes {
+	song_name: String,
+	#[serde(rename = "Overall")]
+	overall: f32,
+	/// In percent
+	wife: f32,
+	rate: f32,
+	difficulty: Difficulty,
+	#[cfg(feature = "extra-fields")]
+	#[serde(flatten)]
+	extra: std::collections::HashMap<String, serde_json::Value>,
+}
+
+impl From<LatestScore> for WireLatestScore {
+	fn from(score: LatestScore) -> Self {
+		Self {
+			id: score.scorekey,
+			attributes: LatestScoreAttributes {
+				song_name: score.song_name,
+				overall: score.ssr_overall,
+				wife: score.wifescore.as_percent(),
+				rate: score.rate.as_f32(),
+				difficulty: score.difficulty,
+				#[cfg(feature = "extra-fields")]
+				extra: score.extra,
+			},
+		}
+	}
+}
+
+impl TryFrom<WireLatestScore> for LatestScore {
+	type Error = Error;
+
+	fn try_from(
+		Resource {
+			id,
+			attributes: score,
+		}: WireLatestScore,
+	) -> Result<Self, Error> {
+		Ok(Self {
+			scorekey: id,
+			song_name: score.song_name,
+			ssr_overall: score.overall,
+			wifescore: wifescore_from_percent(score.wife)?,
+			rate: rate_from_f32(score.rate)?,
+			difficulty: score.difficulty,
+			#[cfg(feature = "extra-fields")]
+			extra: score.extra,
+		})
+	}
+}
+
+/// Not a resource object, these are the elements of the per-skillset arrays in the attributes of
+/// the `user/{username}/all` response
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_")]
+pub(crate) struct WireTopScorePerSkillset {
+	songname: String,
+	user_chart_rate_rate: f32,
+	/// Proportion, not percent
+	wifescore: f32,
+	chartkey: Chartkey,
+	scorekey: Scorekey,
+	difficulty: Difficulty,
+	#[serde(flatten)]
+	skillsets: Skillsets,
+}
+
+impl From<TopScorePerSkillset> for WireTopScorePerSkillset {
+	fn from(score: TopScorePerSkillset) -> Self {
+		Self {
+			songname: score.song_name,
+			user_chart_rate_rate: score.rate.as_f32(),
+			wifescore: score.wifescore.as_proportion(),
+			chartkey: score.chartkey,
+			scorekey: score.scorekey,
+			difficulty: score.difficulty,
+			skillsets: Skillsets::new(&score.ssr),
+		}
+	}
+}
+
+impl TryFrom<WireTopScorePerSkillset> for TopScorePerSkillset {
+	type Error = Error;
+
+	fn try_from(score: WireTopScorePerSkillset) -> Result<Self, Error> {
+		Ok(Self {
+			song_name: score.songname,
+			rate: rate_from_f32(score.user_chart_rate_rate)?,
+			wifescore: wifescore_from_proportion(score.wifescore)?,
+			chartkey: score.chartkey,
+			scorekey: score.scorekey,
+			difficulty: score.difficulty,
+			ssr: score.skillsets.into_skillsets8()?,
+		})
+	}
+}
+
+pub(crate) type WireScoreData = Resource<ScoreDataAttributes>;
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_", rename_all = "camelCase")]
+pub(crate) struct ScoreDataAttributes {
+	modifiers: String,
+	/// Proportion, not percent
+	wife: f32,
+	rate: f32,
+	max_combo: u32,
+	valid: bool,
+	#[serde(rename = "nocc")]
+	no_cc: bool,
+	song: Song,
+	skillsets: Skillsets,
+	judgements: Judgements,
+	/// Null, the replay string or an array containing the replay string
+	#[serde(default)]
+	replay: serde_json::Value,
+	user: User,
+	#[cfg(feature = "extra-fields")]
+	#[serde(flatten)]
+	extra: std::collections::HashMap<String, serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_", rename_all = "camelCase")]
+struct Song {
+	song_name: String,
+	artist: String,
+	id: SongId,
+}
+
+impl From<ScoreData> for WireScoreData {
+	fn from(score: ScoreData) -> Self {
+		Self {
+			id: score.scorekey,
+			attributes: ScoreDataAttributes {
+				replay: replay_to_eo(score.replay.as_ref(), score.replay_raw.as_deref()),
+				modifiers: score.modifiers,
+				wife: score.wifescore.as_proportion(),
+				rate: score.rate.as_f32(),
+				max_combo: score.max_combo,
+				valid: score.is_valid,
+				no_cc: !score.has_chord_cohesion,
+				song: Song {
+					song_name: score.song_name,
+					artist: score.artist,
+					id: score.song_id,
+				},
+				skillsets: Skillsets::new(&score.ssr),
+				judgements: score.judgements.into(),
+				user: score.user.into(),
+				#[cfg(feature = "extra-fields")]
+				extra: score.extra,
+			},
+		}
+	}
+}
+
+impl TryFrom<WireScoreData> for ScoreData {
+	type Error = Error;
+
+	fn try_from(
+		Resource {
+			id,
+			attributes: score,
+		}: WireScoreData,
+	) -> Result<Self, Error> {
+		let (replay, replay_raw) = crate::common::parse_replay(&score.replay);
+		Ok(Self {
+			scorekey: id,
+			modifiers: score.modifiers,
+			ssr: score.skillsets.into_skillsets8()?,
+			wifescore: wifescore_from_proportion(score.wife)?,
+			rate: rate_from_f32(score.rate)?,
+			max_combo: score.max_combo,
+			is_valid: score.valid,
+			has_chord_cohesion: !score.no_cc,
+			judgements: score.judgements.into(),
+			replay,
+			replay_raw,
+			user: score.user.into(),
+			song_name: score.song.song_name,
+			artist: score.song.artist,
+			song_id: score.song.id,
+			#[cfg(feature = "extra-fields")]
+			extra: score.extra,
+		})
+	}
+}
+
+pub(crate) type WireChartLeaderboardScore = Resource<ChartLeaderboardScoreAttributes>;
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_", rename_all = "camelCase")]
+pub(crate) struct ChartLeaderboardScoreAttributes {
+	/// In percent
+	wife: f32,
+	max_combo: u32,
+	valid: bool,
+	modifiers: String,
+	#[serde(rename = "noCC")]
+	no_cc: bool,
+	rate: f32,
+	datetime: String,
+	skillsets: Skillsets,
+	judgements: Judgements,
+	has_replay: bool,
+	user: ChartLeaderboardUser,
+	#[cfg(feature = "extra-fields")]
+	#[serde(flatten)]
+	extra: std::collections::HashMap<String, serde_json::Value>,
+}
+
+impl From<ChartLeaderboardScore> for WireChartLeaderboardScore {
+	fn from(score: ChartLeaderboardScore) -> Self {
+		Self {
+			id: score.scorekey,
+			attributes: ChartLeaderboardScoreAttributes {
+				wife: score.wifescore.as_percent(),
+				max_combo: score.max_combo,
+				valid: score.is_valid,
+				modifiers: score.modifiers,
+				no_cc: !score.has_chord_cohesion,
+				rate: score.rate.as_f32(),
+				datetime: score.datetime,
+				skillsets: Skillsets::new(&score.ssr),
+				judgements: score.judgements.into(),
+				has_replay: score.has_replay,
+				user: score.user.into(),
+				#[cfg(feature = "extra-fields")]
+				extra: score.extra,
+			},
+		}
+	}
+}
+
+impl TryFrom<WireChartLeaderboardScore> for ChartLeaderboardScore {
+	type Error = Error;
+
+	fn try_from(
+		Resource {
+			id,
+			attributes: score,
+		}: WireChartLeaderboardScore,
+	) -> Result<Self, Error> {
+		Ok(Self {
+			scorekey: id,
+			ssr: score.skillsets.into_skillsets8()?,
+			wifescore: wifescore_from_percent(score.wife)?,
+			rate: rate_from_f32(score.rate)?,
+			max_combo: score.max_combo,
+			is_valid: score.valid,
+			has_chord_cohesion: !score.no_cc,
+			datetime: score.datetime,
+			modifiers: score.modifiers,
+			has_replay: score.has_replay,
+			judgements: score.judgements.into(),
+			user: score.user.into(),
+			#[cfg(feature = "extra-fields")]
+			extra: score.extra,
+		})
+	}
+}
+
+pub(crate) type WireLeaderboardEntry = Attributes<LeaderboardEntryAttributes>;
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_")]
+pub(crate) struct LeaderboardEntryAttributes {
+	user: User,
+	skillsets: Skillsets,
+}
+
+impl From<LeaderboardEntry> for WireLeaderboardEntry {
+	fn from(entry: LeaderboardEntry) -> Self {
+		Self {
+			attributes: LeaderboardEntryAttributes {
+				skillsets: Skillsets::without_overall(&entry.rating),
+				user: entry.user.into(),
+			},
+		}
+	}
+}
+
+impl From<WireLeaderboardEntry> for LeaderboardEntry {
+	fn from(Attributes { attributes: entry }: WireLeaderboardEntry) -> Self {
+		Self {
+			rating: entry
+				.skillsets
+				.into_skillsets8_with_overall(entry.user.overall),
+			user: entry.user.into(),
+		}
+	}
+}
+
+pub(crate) type WireScoreGoal = Attributes<ScoreGoalAttributes>;
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_", rename_all = "camelCase")]
+pub(crate) struct ScoreGoalAttributes {
+	chartkey: Chartkey,
+	rate: f32,
+	/// Proportion, not percent
+	wife: f32,
+	time_assigned: String,
+	/// 0 or 1
+	achieved: u8,
+	time_achieved: Option<String>,
+	#[cfg(feature = "extra-fields")]
+	#[serde(flatten)]
+	extra: std::collections::HashMap<String, serde_json::Value>,
+}
+
+impl From<ScoreGoal> for WireScoreGoal {
+	fn from(goal: ScoreGoal) -> Self {
+		Self {
+			attributes: ScoreGoalAttributes {
+				chartkey: goal.chartkey,
+				rate: goal.rate.as_f32(),
+				wife: goal.wifescore.as_proportion(),
+				time_assigned: goal.time_assigned,
+				achieved: goal.time_achieved.is_some() as u8,
+				time_achieved: goal.time_achieved,
+				#[cfg(feature = "extra-fields")]
+				extra: goal.extra,
+			},
+		}
+	}
+}
+
+impl TryFrom<WireScoreGoal> for ScoreGoal {
+	type Error = Error;
+
+	fn try_from(Attributes { attributes: goal }: WireScoreGoal) -> Result<Self, Error> {
+		Ok(Self {
+			chartkey: goal.chartkey,
+			rate: rate_from_f32(goal.rate)?,
+			wifescore: wifescore_from_proportion(goal.wife)?,
+			time_assigned: goal.time_assigned,
+			time_achieved: match goal.achieved {
+				0 => None,
+				1 => Some(
+					goal.time_achieved
+						.ok_or_else(|| invalid("timeAchieved of an achieved goal", "null"))?,
+				),
+				other => return Err(invalid("achieved 0 or 1", other)),
+			},
+			#[cfg(feature = "extra-fields")]
+			extra: goal.extra,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Deserializes the sample, checks that it serializes back to the same JSON and returns the
+	/// deserialized value. `type` is dropped before comparing, since it isn't kept
+	fn round_trip<T>(mut json: serde_json::Value) -> T
+	where
+		T: Serialize + serde::de::DeserializeOwned,
+	{
+		let value = serde_json::from_value::<T>(json.clone()).unwrap();
+		json.as_object_mut().unwrap().remove("type");
+		assert_eq!(serde_json::to_value(&value).unwrap(), json);
+		value
+	}
+
+	fn sample_skillsets() -> serde_json::Value {
+		serde_json::json!({
+			"Overall": 25.5,
+			"Stream": 24.25,
+			"Jumpstream": 23.75,
+			"Handstream": 24.5,
+			"Stamina": 22.5,
+			"JackSpeed": 20.0,
+			"Chordjack": 23.0,
+			"Technical": 21.25,
+		})
+	}
+
+	fn sample_judgements() -> serde_json::Value {
+		serde_json::json!({
+			"marvelous": 1000,
+			"perfect": 200,
+			"great": 30,
+			"good": 4,
+			"bad": 2,
+			"miss": 1,
+			"hitMines": 0,
+			"heldHold": 50,
+			"letGoHold": 1,
+			"missedHold": 0,
+		})
+	}
+
+	#[test]
+	fn test_chart_leaderboard_score() {
+		let json = serde_json::json!({
+			"id": "S65565b5bc377c6d78b60c0aecfd9e05955b4cf63",
+			"type": "score",
+			"attributes": {
+				"wife": 87.5,
+				"maxCombo": 1026,
+				"valid": true,
+				"modifiers": "C900, Overhead",
+				"noCC": true,
+				"rate": 1.5,
+				"datetime": "2020-06-29 18:54:00",
+				"skillsets": sample_skillsets(),
+				"judgements": sample_judgements(),
+				"hasReplay": true,
+				"user": {
+					"userName": "kangalioo",
+					"avatar": "251c375b7c64494a304ea4d3a55afa92.jpg",
+					"countryCode": "DE",
+					"playerRating": 25.5,
+				},
+			},
+		});
+
+		#[cfg(feature = "v2")]
+		let parsed = super::super::parse_chart_leaderboard_score(&crate::extension_traits::Json::new(
+			&json, "",
+		))
+		.unwrap();
+		let score = round_trip::<ChartLeaderboardScore>(json);
+		assert!(!score.has_chord_cohesion);
+		assert_eq!(score.wifescore.as_proportion(), 0.875);
+		#[cfg(feature = "v2")]
+		assert_eq!(score, parsed);
+	}
+
+	#[test]
+	fn test_leaderboard_entry() {
+		let mut skillsets = sample_skillsets();
+		skillsets.as_object_mut().unwrap().remove("Overall");
+		let json = serde_json::json!({
+			"attributes": {
+				"user": {
+					"username": "kangalioo",
+					"avatar": "251c375b7c64494a304ea4d3a55afa92.jpg",
+					"countryCode": "DE",
+					"Overall": 25.5,
+				},
+				"skillsets": skillsets,
+			},
+		});
+
+		#[cfg(feature = "v2")]
+		let parsed =
+			super::super::parse_leaderboard_entry(&crate::extension_traits::Json::new(&json, ""))
+				.unwrap();
+		let entry = round_trip::<LeaderboardEntry>(json);
+		assert_eq!(entry.rating.overall, 25.5);
+		#[cfg(feature = "v2")]
+		assert_eq!(entry, parsed);
+	}
+
+	#[test]
+	fn test_score_data() {
+		let json = serde_json::json!({
+			"id": "S65565b5bc377c6d78b60c0aecfd9e05955b4cf63",
+			"attributes": {
+				"modifiers": "C900",
+				"wife": 0.875,
+				"rate": 1.0,
+				"maxCombo": 1026,
+				"valid": true,
+				"nocc": false,
+				"song": {"songName": "Game Time", "artist": "Bill Nye", "id": 1234},
+				"skillsets": sample_skillsets(),
+				"judgements": sample_judgements(),
+				"replay": "[[0.5,0.0,0,1,96],[1.0,180.0,1,1,192]]",
+				"user": {
+					"username": "kangalioo",
+					"avatar": "251c375b7c64494a304ea4d3a55afa92.jpg",
+					"countryCode": "DE",
+					"Overall": 25.5,
+				},
+			},
+		});
+
+		let score = round_trip::<ScoreData>(json);
+		assert!(score.has_chord_cohesion);
+		assert_eq!(score.song_id, SongId(1234));
+		assert_eq!(score.replay.unwrap().notes.len(), 2);
+	}
+
+	#[test]
+	fn test_user_details() {
+		let mut skillsets = sample_skillsets();
+		skillsets.as_object_mut().unwrap().remove("Overall");
+		let json = serde_json::json!({
+			"attributes": {
+				"userName": "kangalioo",
+				"aboutMe": "",
+				"moderator": false,
+				"patreon": false,
+				"avatar": "251c375b7c64494a304ea4d3a55afa92.jpg",
+				"countryCode": "DE",
+				"playerRating": 25.5,
+				"defaultModifiers": "",
+				"skillsets": skillsets,
+			},
+		});
+
+		let user = round_trip::<UserDetails>(json);
+		assert_eq!(user.rating.overall, 25.5);
+		assert_eq!(user.default_modifiers, None);
+	}
+
+	#[test]
+	fn test_top_scores() {
+		let mut skillsets = sample_skillsets();
+		skillsets.as_object_mut().unwrap().remove("Overall");
+		let score = round_trip::<TopScore>(serde_json::json!({
+			"id": "S65565b5bc377c6d78b60c0aecfd9e05955b4cf63",
+			"attributes": {
+				"songName": "Game Time",
+				"Overall": 25.5,
+				"wife": 87.5,
+				"rate": 1.5,
+				"difficulty": "Challenge",
+				"chartKey": "X4b537c03eb1f72168f51a0ab92f8a58a62fbe4b4",
+				"skillsets": skillsets,
+			},
+		}));
+		assert_eq!(score.base_msd.overall, 25.5);
+
+		round_trip::<LatestScore>(serde_json::json!({
+			"id": "S65565b5bc377c6d78b60c0aecfd9e05955b4cf63",
+			"attributes": {
+				"songName": "Game Time",
+				"Overall": 25.5,
+				"wife": 87.5,
+				"rate": 1.5,
+				"difficulty": "Challenge",
+			},
+		}));
+
+		let mut json = sample_skillsets();
+		json.as_object_mut().unwrap().extend(
+			serde_json::json!({
+				"songname": "Game Time",
+				"user_chart_rate_rate": 1.5,
+				"wifescore": 0.875,
+				"chartkey": "X4b537c03eb1f72168f51a0ab92f8a58a62fbe4b4",
+				"scorekey": "S65565b5bc377c6d78b60c0aecfd9e05955b4cf63",
+				"difficulty": "Challenge",
+			})
+			.as_object()
+			.unwrap()
+			.clone(),
+		);
+		round_trip::<TopScorePerSkillset>(json);
+	}
+
+	#[test]
+	fn test_score_goal() {
+		let goal = round_trip::<ScoreGoal>(serde_json::json!({
+			"attributes": {
+				"chartkey": "X4b537c03eb1f72168f51a0ab92f8a58a62fbe4b4",
+				"rate": 1.5,
+				"wife": 0.875,
+				"timeAssigned": "2020-06-29 18:54:00",
+				"achieved": 1,
+				"timeAchieved": "2020-07-01 12:00:00",
+			},
+		}));
+		assert_eq!(goal.time_achieved.as_deref(), Some("2020-07-01 12:00:00"));
+	}
+}
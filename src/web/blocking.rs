@@ -0,0 +1,143 @@
+//! A blocking version of [`web::Session`](super::Session), for programs without an async runtime.
+//!
+//! Each method blocks the current thread until the request is done. Like reqwest's blocking
+//! client, this must not be used from within an async runtime; calling it there panics.
+//!
+//! # Example
+//! ```rust,no_run
+//! # fn main() -> Result<(), etternaonline_api::Error> {
+//! use etternaonline_api::web::blocking::Session;
+//!
+//! let session = Session::new(
+//! 	std::time::Duration::from_millis(1000), // wait 1s inbetween requests
+//! 	None, // no timeout
+//! );
+//!
+//! println!("EO has {} players", session.num_players()?);
+//! # Ok(()) }
+//! ```
+
+use super::*;
+use crate::Error;
+
+pub struct Session {
+	inner: super::Session,
+	runtime: tokio::runtime::Runtime,
+}
+
+impl Session {
+	/// See [`web::Session::new`](super::Session::new)
+	pub fn new(
+		request_cooldown: std::time::Duration,
+		timeout: Option<std::time::Duration>,
+	) -> Self {
+		Self::from_async(super::Session::new(request_cooldown, timeout))
+	}
+
+	/// Wraps an async session, e.g. one that was already configured
+	pub fn from_async(session: super::Session) -> Self {
+		Self {
+			inner: session,
+			runtime: crate::blocking::runtime(),
+		}
+	}
+
+	/// The wrapped async session, for its non-request methods like
+	/// [`username_for_id`](super::Session::username_for_id)
+	pub fn as_async(&self) -> &super::Session {
+		&self.inner
+	}
+
+	/// The wrapped async session, for its settings like
+	/// [`set_lenient`](super::Session::set_lenient)
+	pub fn as_async_mut(&mut self) -> &mut super::Session {
+		&mut self.inner
+	}
+
+	pub fn into_async(self) -> super::Session {
+		self.inner
+	}
+}
+
+blocking_methods! {
+	fn packlist(&self, range_to_retrieve: impl EoRange) -> Result<Vec<PackEntry>, Error>;
+	fn leaderboard(
+		&self,
+		range_to_retrieve: impl EoRange,
+		sort_criterium: LeaderboardSortBy,
+		sort_direction: SortDirection,
+	) -> Result<Vec<LeaderboardEntry>, Error>;
+	fn site_stats(&self) -> Result<SiteStats, Error>;
+	fn num_players(&self) -> Result<u32, Error>;
+	fn rating_percentile(&self, rating: f32) -> Result<RatingPercentile, Error>;
+	fn country_leaderboard_full(
+		&self,
+		country_code: &str,
+		range_to_retrieve: impl EoRange,
+	) -> Result<Vec<LeaderboardEntry>, Error>;
+	fn user_scores(
+		&self,
+		user_id: u32,
+		range_to_retrieve: impl EoRange,
+		song_name_search_query: Option<&str>,
+		sort_criterium: UserScoresSortBy,
+		sort_direction: SortDirection,
+		include_invalid: bool,
+	) -> Result<UserScores, Error>;
+	fn user_scores_query(&self, query: &UserScoresQuery) -> Result<UserScores, Error>;
+	fn user_scores_filtered(
+		&self,
+		user_id: u32,
+		filter: &ScoreFilter,
+	) -> Result<Vec<UserScore>, Error>;
+	fn compare_to_personal_best(
+		&self,
+		user_id: u32,
+		score: &UserScore,
+	) -> Result<Option<PersonalBestComparison>, Error>;
+	fn user_scores_since(&self, user_id: u32, datetime: &str) -> Result<Vec<UserScore>, Error>;
+	fn user_scores_after_scorekey(
+		&self,
+		user_id: u32,
+		scorekey: &etterna::Scorekey,
+	) -> Result<Vec<UserScore>, Error>;
+	fn user_details(&self, username: &str) -> Result<UserDetails, Error>;
+	fn chart_leaderboard_per_rate(
+		&self,
+		chartkey: impl AsRef<str>,
+		include_invalid: bool,
+	) -> Result<RateBreakdown, Error>;
+	fn chart_leaderboard(
+		&self,
+		chartkey: impl AsRef<str>,
+		range_to_retrieve: impl EoRange,
+		user_name_search_query: Option<&str>,
+		sort_criterium: ChartLeaderboardSortBy,
+		sort_direction: SortDirection,
+		all_rates: bool,
+		include_invalid: bool,
+	) -> Result<ChartLeaderboard, Error>;
+	fn chart_leaderboard_query(
+		&self,
+		query: &ChartLeaderboardQuery,
+	) -> Result<ChartLeaderboard, Error>;
+	fn user_chart_history(
+		&self,
+		user_id: u32,
+		chartkey: impl AsRef<str>,
+	) -> Result<Vec<ChartLeaderboardEntry>, Error>;
+	fn user_id(&self, username: &str) -> Result<u32, Error>;
+	fn user_scores_by_username(
+		&self,
+		username: &str,
+		range_to_retrieve: impl EoRange,
+		song_name_search_query: Option<&str>,
+		sort_criterium: UserScoresSortBy,
+		sort_direction: SortDirection,
+		include_invalid: bool,
+	) -> Result<UserScores, Error>;
+	fn user_top_scores_per_skillset(
+		&self,
+		username: &str,
+	) -> Result<UserTopScoresPerSkillset, Error>;
+}
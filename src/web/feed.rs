@@ -0,0 +1,167 @@
+use super::*;
+
+/// A new score found by [`ScoreFeed`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct FeedScore {
+	pub username: String,
+	pub user_id: u32,
+	pub score: UserScore,
+}
+
+struct FeedUser {
+	username: String,
+	user_id: Option<u32>,
+	/// Datetime of the newest score seen so far. `None` until the user was polled once
+	newest_date: Option<String>,
+}
+
+/// Watches the scores of multiple users and merges their new scores into a single chronological
+/// feed, e.g. for a community score channel.
+///
+/// Each poll visits the users one after another, so the session's rate limit is shared fairly
+/// between them. The first poll of a user only records where their scores currently end, so
+/// that only scores set after the feed started are reported.
+///
+/// # Example
+/// ```rust,no_run
+/// # async fn run() {
+/// # use etternaonline_api::web::*;
+/// # let session: Session = unimplemented!();
+/// let mut feed = ScoreFeed::new(&session, ["kangalioo", "Rubyyi"]);
+/// feed.run(std::time::Duration::from_secs(300), |scores| {
+/// 	for new in scores {
+/// 		println!("{} played {}", new.username, new.score.song_name);
+/// 	}
+/// })
+/// .await;
+/// # }
+/// ```
+pub struct ScoreFeed<'a> {
+	session: &'a Session,
+	users: Vec<FeedUser>,
+}
+
+impl<'a> ScoreFeed<'a> {
+	pub fn new(
+		session: &'a Session,
+		usernames: impl IntoIterator<Item = impl Into<String>>,
+	) -> Self {
+		let mut feed = Self {
+			session,
+			users: Vec::new(),
+		};
+		for username in usernames {
+			feed.add_user(username);
+		}
+		feed
+	}
+
+	/// Starts watching another user. Does nothing if the user is already being watched
+	pub fn add_user(&mut self, username: impl Into<String>) {
+		let username = username.into();
+		if self.users.iter().any(|user| user.username == username) {
+			return;
+		}
+		self.users.push(FeedUser {
+			username,
+			user_id: None,
+			newest_date: None,
+		});
+	}
+
+	/// Stops watching a user
+	pub fn remove_user(&mut self, username: &str) {
+		self.users.retain(|user| user.username != username);
+	}
+
+	/// Checks every user once for new scores and returns them ordered from oldest to newest.
+	///
+	/// If retrieving a user's scores fails, the error is logged and the user is retried on the
+	/// next poll, without losing any scores
+	pub async fn poll(&mut self) -> Vec<FeedScore> {
+		let mut new_scores = Vec::new();
+		for user in &mut self.users {
+			match Self::poll_user(self.session, user).await {
+				Ok(scores) => new_scores.extend(scores),
+				Err(e) => log::warn!("failed to poll scores of {}: {}", user.username, e),
+			}
+		}
+
+		// EO's datetime format sorts lexicographically. The sort is stable, so scores with the
+		// same datetime stay in user order
+		new_scores.sort_by(|a, b| a.score.date.cmp(&b.score.date));
+		new_scores
+	}
+
+	/// Polls all users every `interval` forever, passing each batch of new scores to the callback.
+	/// Empty batches are not passed on
+	pub async fn run(
+		&mut self,
+		interval: std::time::Duration,
+		mut on_new_scores: impl FnMut(Vec<FeedScore>),
+	) {
+		loop {
+			let next_poll = self.session.clock.now() + interval;
+
+			let new_scores = self.poll().await;
+			if !new_scores.is_empty() {
+				on_new_scores(new_scores);
+			}
+
+			self.session.clock.sleep_until(next_poll).await;
+		}
+	}
+
+	async fn poll_user(session: &Session, user: &mut FeedUser) -> Result<Vec<FeedScore>, Error> {
+		let user_id = match user.user_id {
+			Some(user_id) => user_id,
+			None => {
+				let user_id = session.user_id(&user.username).await?;
+				user.user_id = Some(user_id);
+				user_id
+			}
+		};
+
+		let newest_date = match &user.newest_date {
+			Some(newest_date) => newest_date,
+			None => {
+				let latest = session
+					.user_scores(
+						user_id,
+						0..1,
+						None,
+						UserScoresSortBy::Date,
+						SortDirection::Descending,
+						true,
+					)
+					.await?
+					.scores;
+				// A user without scores gets an empty datetime, which precedes all others
+				user.newest_date =
+					Some(latest.first().map_or_else(String::new, |s| s.date.clone()));
+				return Ok(Vec::new());
+			}
+		};
+
+		let scores = session.user_scores_since(user_id, newest_date).await?;
+		// Newest first
+		if let Some(newest) = scores.first() {
+			user.newest_date = Some(newest.date.clone());
+		}
+
+		Ok(scores
+			.into_iter()
+			.map(|score| FeedScore {
+				username: user.username.clone(),
+				user_id,
+				score,
+			})
+			.collect())
+	}
+}
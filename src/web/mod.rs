@@ -1,9 +1,18 @@
+#[cfg(all(feature = "web", feature = "blocking"))]
+pub mod blocking;
+#[cfg(feature = "web")]
+mod feed;
 mod structs;
+#[cfg(feature = "web")]
+pub use feed::*;
 pub use structs::*;
 
+#[cfg(feature = "web")]
 use etterna::*;
 
+#[cfg(feature = "web")]
 use crate::extension_traits::*;
+#[cfg(feature = "web")]
 use crate::Error;
 
 /// The kind of ranges that EO can process. Ranges can never be empty! They must have one or more
@@ -54,39 +63,118 @@ impl EoRange for std::ops::RangeFull {
 	}
 }
 
+/// Usernames and user ids seen so far, so that resolving between the two doesn't cost a request
+/// every time. Usernames are keyed case-insensitively, like EO treats them
+#[cfg(feature = "web")]
+#[derive(Default)]
+struct UserIdCache {
+	ids_by_username: std::collections::HashMap<String, u32>,
+	usernames_by_id: std::collections::HashMap<u32, String>,
+}
+
+#[cfg(feature = "web")]
+impl UserIdCache {
+	fn insert(&mut self, username: &str, user_id: u32) {
+		self.ids_by_username
+			.insert(username.to_lowercase(), user_id);
+		self.usernames_by_id.insert(user_id, username.to_owned());
+	}
+}
+
+#[cfg(feature = "web")]
 pub struct Session {
 	// Rate limiting stuff
-	last_request: std::sync::Mutex<std::time::Instant>, // could replace this was smth like a AtomicInstant
-	request_cooldown: std::time::Duration,
+	rate_limiter: crate::rate_limit::RateLimiter,
+
+	user_id_cache: std::sync::Mutex<UserIdCache>,
 
 	timeout: Option<std::time::Duration>,
 
 	http: reqwest::Client,
+	parse_settings: crate::ParseSettings,
+	stats: crate::StatsRecorder,
+	clock: Box<dyn crate::clock::Clock>,
 }
 
+#[cfg(feature = "web")]
 impl Session {
 	pub fn new(
 		request_cooldown: std::time::Duration,
 		timeout: Option<std::time::Duration>,
 	) -> Self {
 		Self {
-			request_cooldown,
 			timeout,
-			last_request: std::sync::Mutex::new(std::time::Instant::now() - request_cooldown),
+			rate_limiter: crate::rate_limit::RateLimiter::new(
+				request_cooldown,
+				&crate::clock::RealClock,
+			),
+			user_id_cache: std::sync::Mutex::new(UserIdCache::default()),
 			http: reqwest::Client::new(),
+			parse_settings: crate::ParseSettings::default(),
+			stats: crate::StatsRecorder::default(),
+			clock: Box::new(crate::clock::RealClock),
 		}
 	}
 
+	/// Enables or disables lenient parsing. In lenient mode, entries of a list response that fail
+	/// to parse are skipped and reported to the parse warning callback instead of failing the
+	/// whole request
+	pub fn set_lenient(&mut self, lenient: bool) {
+		self.parse_settings.lenient = lenient;
+	}
+
+	/// Registers a callback that receives every [`ParseWarning`](crate::ParseWarning), so that you
+	/// can monitor EO for format changes without hard failures
+	pub fn set_parse_warning_callback(
+		&mut self,
+		callback: impl Fn(crate::ParseWarning) + Send + Sync + 'static,
+	) {
+		self.parse_settings.warning_callback = Some(Box::new(callback));
+	}
+
+	/// Persists the rate limiting state using the given store, so that the cooldown carries over
+	/// when the application restarts. If the store contains previously saved state, it is restored
+	/// immediately
+	pub fn set_rate_limit_store(
+		&mut self,
+		store: impl crate::rate_limit::RateLimitStore + 'static,
+	) {
+		self.rate_limiter.set_store(Box::new(store), &*self.clock);
+	}
+
+	/// Reserves request time slots from the given backend instead of limiting the rate
+	/// in-process, so that multiple processes can share one cooldown. The session's own
+	/// cooldown is still passed to the backend
+	pub fn set_rate_limit_backend(
+		&mut self,
+		backend: impl crate::rate_limit::RateLimitBackend + 'static,
+	) {
+		self.rate_limiter.set_backend(Box::new(backend));
+	}
+
+	/// Returns statistics about the requests this session has sent so far
+	pub fn stats(&self) -> crate::RequestStats {
+		self.stats.get()
+	}
+
+	/// Replaces the time source used for rate limiting, e.g. with a
+	/// [`InstantClock`](crate::clock::InstantClock) in tests
+	pub fn set_clock(&mut self, clock: impl crate::clock::Clock + 'static) {
+		self.rate_limiter.set_clock(&clock);
+		self.clock = Box::new(clock);
+	}
+
+	pub(crate) fn clock(&self) -> &dyn crate::clock::Clock {
+		&*self.clock
+	}
+
 	async fn request(
 		&self,
 		method: reqwest::Method,
 		path: &str,
 		request_callback: impl Fn(reqwest::RequestBuilder) -> reqwest::RequestBuilder,
 	) -> Result<String, Error> {
-		// UNWRAP: propagate panics
-		let rate_limit =
-			crate::rate_limit(self.last_request.lock().unwrap(), self.request_cooldown);
-		rate_limit.await;
+		self.rate_limiter.wait(&*self.clock).await;
 
 		let mut request = self
 			.http
@@ -96,7 +184,24 @@ impl Session {
 		}
 		request = request_callback(request);
 
-		let response = request.send().await?.text().await?;
+		let start = std::time::Instant::now();
+		let result = self.send(request).await;
+		self.stats
+			.record(path, None, start.elapsed(), result.as_ref().err());
+		result
+	}
+
+	async fn send(&self, request: reqwest::RequestBuilder) -> Result<String, Error> {
+		let response = request.send().await?;
+		let status = response.status();
+		let headers = response.headers().clone();
+		let response = response.text().await?;
+
+		if crate::is_cloudflare_challenge(status, &headers, &response) {
+			return Err(Error::CloudflareChallenge {
+				status_code: status.as_u16(),
+			});
+		}
 
 		if response.trim().is_empty() {
 			return Err(Error::EmptyServerResponse);
@@ -118,36 +223,39 @@ impl Session {
 			})
 			.await?;
 		let json: serde_json::Value = serde_json::from_str(&json)?;
+		let json = Json::new(&json, "");
 
-		json["data"]
-			.array()?
-			.iter()
-			.map(|json| {
+		self.parse_settings.collect(
+			"packlist",
+			json.at("data").elements()?.map(|json| {
 				Ok(PackEntry {
-					average_msd: json["average"].attempt_get("average_msd", |j| {
+					average_msd: json.at("average").attempt_get("average_msd", |j| {
 						Some(j.as_str()?.extract("\" />", "</span>")?.parse().ok()?)
 					})?,
-					datetime: json["date"]
+					datetime: json
+						.at("date")
 						.attempt_get("datetime", |j| Some(j.as_str()?.to_owned()))?,
-					size: json["size"].attempt_get("size", |j| Some(j.as_str()?.parse().ok()?))?,
-					name: json["packname"].attempt_get("name", |j| {
+					size: json
+						.at("size")
+						.attempt_get("size", |j| Some(j.as_str()?.parse().ok()?))?,
+					name: json.at("packname").attempt_get("name", |j| {
 						Some(j.as_str()?.extract(">", "</a>")?.to_owned())
 					})?,
-					id: json["packname"].attempt_get("id", |j| {
+					id: json.at("packname").attempt_get("id", |j| {
 						Some(j.as_str()?.extract("pack/", "\"")?.parse().ok()?)
 					})?,
-					num_votes: json["r_avg"].attempt_get("num_votes", |j| {
+					num_votes: json.at("r_avg").attempt_get("num_votes", |j| {
 						Some(j.as_str()?.extract("title='", " votes")?.parse().ok()?)
 					})?,
-					average_vote: json["r_avg"].attempt_get("average_vote", |j| {
+					average_vote: json.at("r_avg").attempt_get("average_vote", |j| {
 						Some(j.as_str()?.extract("votes'>", "</div>")?.parse().ok()?)
 					})?,
-					download_link: json["download"].attempt_get("download_link", |j| {
+					download_link: json.at("download").attempt_get("download_link", |j| {
 						Some(j.as_str()?.extract("href=\"", "\">")?.to_owned())
 					})?,
 				})
-			})
-			.collect()
+			}),
+		)
 	}
 
 	/// Panics if the provided range is empty or negative
@@ -189,49 +297,191 @@ impl Session {
 			})
 			.await?;
 		let json: serde_json::Value = serde_json::from_str(&json)?;
+		let json = Json::new(&json, "");
 
-		json["data"]
-			.array()?
-			.iter()
-			.map(|json| {
+		self.parse_settings.collect(
+			"leaderboard",
+			json.at("data").elements()?.map(|json| {
 				Ok(LeaderboardEntry {
-					rank: json["rank"].attempt_get("rank int", |j| {
+					rank: json.at("rank").attempt_get("rank int", |j| {
 						Some(j.as_str()?.trim_start_matches('#').parse().ok()?)
 					})?,
-					username: json["username"].attempt_get("leaderboard username", |j| {
-						Some(j.as_str()?.extract("/user/", "\"")?.to_owned())
-					})?,
+					username: json
+						.at("username")
+						.attempt_get("leaderboard username", |j| {
+							Some(j.as_str()?.extract("/user/", "\"")?.to_owned())
+						})?,
 					country: (|| {
 						Some(Country {
-							code: json["username"]
+							code: json
+								.at("username")
 								.as_str()?
 								.extract("/img/flags/", ".svg")?
 								.to_owned(),
-							name: json["username"]
+							name: json
+								.at("username")
 								.as_str()?
 								.extract("title=\"", "\"")?
 								.to_owned(),
 						})
 					})(),
-					avatar: json["username"].attempt_get("leaderboard username", |j| {
-						Some(j.as_str()?.extract("/avatars/", "\"")?.to_owned())
-					})?,
+					avatar: json
+						.at("username")
+						.attempt_get("leaderboard username", |j| {
+							Some(j.as_str()?.extract("/avatars/", "\"")?.to_owned())
+						})?,
 					rating: etterna::Skillsets8 {
-						overall: json["player_rating"].f32_()?,
-						stamina: json["Stamina"].f32_()?,
-						stream: json["Stream"].f32_()?,
-						jumpstream: json["Jumpstream"].f32_()?,
-						handstream: json["Handstream"].f32_()?,
-						jackspeed: json["JackSpeed"].f32_()?,
-						chordjack: json["Chordjack"].f32_()?,
-						technical: json["Technical"].f32_()?,
+						overall: json.at("player_rating").f32_()?,
+						stamina: json.at("Stamina").f32_()?,
+						stream: json.at("Stream").f32_()?,
+						jumpstream: json.at("Jumpstream").f32_()?,
+						handstream: json.at("Handstream").f32_()?,
+						jackspeed: json.at("JackSpeed").f32_()?,
+						chordjack: json.at("Chordjack").f32_()?,
+						technical: json.at("Technical").f32_()?,
 					},
 				})
+			}),
+		)
+	}
+
+	/// Scrapes the site-wide statistics from the EO front page.
+	///
+	/// # Errors
+	/// - [`Error::InvalidDataStructure`] if the user or score count couldn't be found
+	pub async fn site_stats(&self) -> Result<SiteStats, Error> {
+		let response = self.request(reqwest::Method::GET, "", |r| r).await?;
+		let response = response.as_str();
+
+		let missing = |what: &str| {
+			Error::InvalidDataStructure(format!("Couldn't find {} on the front page", what))
+		};
+		Ok(SiteStats {
+			total_users: number_after(response, "Users").ok_or_else(|| missing("total users"))?,
+			total_scores: number_after(response, "Scores")
+				.ok_or_else(|| missing("total scores"))?,
+			total_charts: number_after(response, "Charts"),
+		})
+	}
+
+	/// Retrieves the number of players on the leaderboard, i.e. all registered players that have
+	/// a rating
+	pub async fn num_players(&self) -> Result<u32, Error> {
+		let json = self
+			.request(reqwest::Method::POST, "leaderboard/leaderboard", |r| {
+				r.form(&[("start", "0"), ("length", "1")])
 			})
-			.collect()
+			.await?;
+		let json: serde_json::Value = serde_json::from_str(&json)?;
+		Json::new(&json, "").at("recordsTotal").u32_()
+	}
+
+	/// Determines which worldwide rank the given overall rating would have, and which percentage
+	/// of players that is.
+	///
+	/// The rank is found by binary search over the leaderboard, so this takes about 20 requests.
+	///
+	/// # Example
+	/// ```rust,no_run
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
+	/// # use etternaonline_api::web::*;
+	/// # let session: Session = unimplemented!();
+	/// let percentile = session.rating_percentile(25.0).await?;
+	/// println!("Top {:.1}% worldwide", percentile.top_percent());
+	/// # Ok(()) }
+	/// ```
+	pub async fn rating_percentile(&self, rating: f32) -> Result<RatingPercentile, Error> {
+		let num_players = self.num_players().await?;
+
+		// Number of players with a higher rating
+		let (mut low, mut high) = (0, num_players);
+		while low < high {
+			let mid = low + (high - low) / 2;
+			let is_higher = self
+				.leaderboard(
+					mid..=mid,
+					LeaderboardSortBy::Rating(Skillset8::Overall),
+					SortDirection::Descending,
+				)
+				.await?
+				.first()
+				.is_some_and(|entry| entry.rating.overall > rating);
+			if is_higher {
+				low = mid + 1;
+			} else {
+				high = mid;
+			}
+		}
+
+		Ok(RatingPercentile {
+			rank: low + 1,
+			num_players,
+		})
+	}
+
+	/// Retrieves the overall leaderboard of a single country, like the v1 and v2 country
+	/// leaderboards, but not limited to the top 10. The range refers to positions within the
+	/// country, while [`LeaderboardEntry::rank`] stays the global rank.
+	///
+	/// EO can't filter the leaderboard by country, so the global leaderboard is walked page by page
+	/// until enough players from the country were found. Ranges deep into a small country's
+	/// leaderboard may therefore take many requests.
+	///
+	/// Panics if the provided range is empty or negative
+	pub async fn country_leaderboard_full(
+		&self,
+		country_code: &str,
+		range_to_retrieve: impl EoRange,
+	) -> Result<Vec<LeaderboardEntry>, Error> {
+		const PAGE_SIZE: u32 = 100;
+
+		// A length of zero means the full range, see the RangeFull impl of EoRange
+		let (start, length) = range_to_retrieve.start_length();
+		let end = match length {
+			0 => None,
+			length => Some(start.saturating_add(length)),
+		};
+
+		let mut entries = Vec::new();
+		let mut num_seen_in_country = 0;
+		for page in 0.. {
+			let page = self
+				.leaderboard(
+					page * PAGE_SIZE..(page + 1) * PAGE_SIZE,
+					LeaderboardSortBy::Rating(Skillset8::Overall),
+					SortDirection::Descending,
+				)
+				.await?;
+			let page_len = page.len();
+
+			for entry in page {
+				let is_in_country = entry
+					.country
+					.as_ref()
+					.is_some_and(|country| country.code.eq_ignore_ascii_case(country_code));
+				if !is_in_country {
+					continue;
+				}
+
+				if num_seen_in_country >= start {
+					entries.push(entry);
+				}
+				num_seen_in_country += 1;
+				if Some(num_seen_in_country) == end {
+					return Ok(entries);
+				}
+			}
+
+			if page_len < PAGE_SIZE as usize {
+				break;
+			}
+		}
+		Ok(entries)
 	}
 
 	/// Panics if the provided range is empty or negative
+	///
+	/// See [`Self::user_scores_query`] for a version that doesn't need all parameters
 	pub async fn user_scores(
 		&self,
 		user_id: u32,
@@ -241,12 +491,35 @@ impl Session {
 		sort_direction: SortDirection,
 		include_invalid: bool,
 	) -> Result<UserScores, Error> {
-		let (start, length) = range_to_retrieve.start_length();
+		let mut query = UserScoresQuery::new(user_id)
+			.range(range_to_retrieve)
+			.sort_by(sort_criterium, sort_direction)
+			.include_invalid(include_invalid);
+		query.song_name_search = song_name_search_query.map(|s| s.to_owned());
+		self.user_scores_query(&query).await
+	}
+
+	/// Retrieves the user's scores as specified by the query
+	///
+	/// # Example
+	/// ```rust,no_run
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
+	/// # use etternaonline_api::web::*;
+	/// # let session: Session = unimplemented!();
+	/// // The user's 10 most recent valid scores
+	/// let scores = session.user_scores_query(&UserScoresQuery::new(2).range(0..10)).await?;
+	/// # Ok(()) }
+	/// ```
+	pub async fn user_scores_query(&self, query: &UserScoresQuery) -> Result<UserScores, Error> {
+		let (start, length) = query.start_length;
+		let user_id = query.user_id;
+		let song_name_search_query = query.song_name_search.as_deref();
+		let (sort_criterium, sort_direction) = (query.sort_by, query.sort_direction);
 
 		let json = self
 			.request(
 				reqwest::Method::POST,
-				if include_invalid {
+				if query.include_invalid {
 					"score/userScores"
 				} else {
 					"valid_score/userScores"
@@ -289,21 +562,21 @@ impl Session {
 			)
 			.await?;
 		let json: serde_json::Value = serde_json::from_str(&json)?;
+		let json = Json::new(&json, "");
 
-		let scores = json["data"]
-			.array()?
-			.iter()
-			.map(|json| {
+		let scores = self.parse_settings.collect(
+			"userScores",
+			json.at("data").elements()?.map(|json| {
 				Ok(UserScore {
-					song_name: json["songname"].attempt_get("song name", |j| {
+					song_name: json.at("songname").attempt_get("song name", |j| {
 						Some(j.as_str()?.extract("\">", "</a>")?.to_owned())
 					})?,
-					song_id: json["songname"].attempt_get("song id", |j| {
+					song_id: json.at("songname").attempt_get("song id", |j| {
 						Some(j.as_str()?.extract("song/view/", "\"")?.parse().ok()?)
 					})?,
-					// scorekey: json["scorekey"].parse()?, // this disappeared
-					rate: json["user_chart_rate_rate"].parse()?,
-					wifescore: json["wifescore"].attempt_get("wifescore", |j| {
+					// scorekey: json.at("scorekey").parse()?, // this disappeared
+					rate: json.at("user_chart_rate_rate").parse()?,
+					wifescore: json.at("wifescore").attempt_get("wifescore", |j| {
 						Some(etterna::Wifescore::from_percent(
 							j.as_str()?
 								.extract("<span class=", "</span>")?
@@ -312,7 +585,7 @@ impl Session {
 								.ok()?,
 						)?)
 					})?,
-					judgements: json["wifescore"].attempt_get("judgements", |j| {
+					judgements: json.at("wifescore").attempt_get("judgements", |j| {
 						let string = j.as_str()?;
 						Some(etterna::TapJudgements {
 							marvelouses: string.extract("Marvelous: ", "<br")?.parse().ok()?,
@@ -323,26 +596,27 @@ impl Session {
 							misses: string.extract("Miss: ", "<br")?.parse().ok()?,
 						})
 					})?,
-					date: json["datetime"].string()?,
-					has_chord_cohesion: json["nocc"].attempt_get("'Off' or 'On'", |j| {
-						match j.as_str()? {
+					date: json.at("datetime").string()?,
+					has_chord_cohesion: json.at("nocc").attempt_get(
+						"'Off' or 'On'",
+						|j| match j.as_str()? {
 							"On" => Some(true),
 							"Off" => Some(false),
 							_ => None,
-						}
-					})?,
-					validity_dependant: if json["Overall"].str_()?.contains("Invalid Score") {
+						},
+					)?,
+					validity_dependant: if json.at("Overall").str_()?.contains("Invalid Score") {
 						None
 					} else {
 						Some(ValidUserScoreInfo {
-							scorekey: json["Overall"].attempt_get("scorekey", |j| {
+							scorekey: json.at("Overall").attempt_get("scorekey", |j| {
 								Some(
 									j.as_str()?.extract("score/view/", "\"")?[..41]
 										.parse()
 										.ok()?,
 								)
 							})?,
-							user_id: json["Overall"].attempt_get("user id", |j| {
+							user_id: json.at("Overall").attempt_get("user id", |j| {
 								Some(
 									j.as_str()?.extract("score/view/", "\"")?[41..]
 										.parse()
@@ -351,31 +625,162 @@ impl Session {
 							})?,
 							// The following are zero if the score is invalid
 							ssr: etterna::Skillsets8 {
-								overall: json["Overall"].attempt_get("overall", |j| {
+								overall: json.at("Overall").attempt_get("overall", |j| {
 									Some(j.as_str()?.extract("\">", "<")?.parse().ok()?)
 								})?,
-								stream: json["stream"].parse()?,
-								jumpstream: json["jumpstream"].parse()?,
-								handstream: json["handstream"].parse()?,
-								stamina: json["stamina"].parse()?,
-								jackspeed: json["jackspeed"].parse()?,
-								chordjack: json["chordjack"].parse()?,
-								technical: json["technical"].parse()?,
+								stream: json.at("stream").parse()?,
+								jumpstream: json.at("jumpstream").parse()?,
+								handstream: json.at("handstream").parse()?,
+								stamina: json.at("stamina").parse()?,
+								jackspeed: json.at("jackspeed").parse()?,
+								chordjack: json.at("chordjack").parse()?,
+								technical: json.at("technical").parse()?,
 							},
-							ssr_overall_nerfed: json["Nerf"].f32_()?,
+							ssr_overall_nerfed: json.at("Nerf").f32_()?,
 						})
 					},
 				})
-			})
-			.collect::<Result<Vec<UserScore>, Error>>()?;
+			}),
+		)?;
 
 		Ok(UserScores {
-			entries_before_search_filtering: json["recordsTotal"].u32_()?,
-			entries_after_search_filtering: json["recordsFiltered"].u32_()?,
+			entries_before_search_filtering: json.at("recordsTotal").u32_()?,
+			entries_after_search_filtering: json.at("recordsFiltered").u32_()?,
 			scores,
 		})
 	}
 
+	/// Retrieves all of the user's scores that match the filter. The song name search and the
+	/// validity criterion are applied by EO, the rest is filtered locally
+	pub async fn user_scores_filtered(
+		&self,
+		user_id: u32,
+		filter: &ScoreFilter,
+	) -> Result<Vec<UserScore>, Error> {
+		let mut query = UserScoresQuery::new(user_id).include_invalid(!filter.valid_only);
+		query.song_name_search = filter.song_name.clone();
+		let mut scores = self.user_scores_query(&query).await?.scores;
+		filter.apply(&mut scores);
+		Ok(scores)
+	}
+
+	/// Compares a score of the user to their best earlier score on the same song and rate, e.g. to
+	/// announce a new personal best. Returns `None` if the user had no earlier score there.
+	///
+	/// The candidates are found by searching the user's scores for the song name. EO doesn't
+	/// expose the chartkey of user scores, so different difficulties of the same song are
+	/// treated as the same chart.
+	pub async fn compare_to_personal_best(
+		&self,
+		user_id: u32,
+		score: &UserScore,
+	) -> Result<Option<PersonalBestComparison>, Error> {
+		let candidates = self
+			.user_scores(
+				user_id,
+				..,
+				Some(&score.song_name),
+				UserScoresSortBy::Wifescore,
+				SortDirection::Descending,
+				true,
+			)
+			.await?
+			.scores;
+
+		// Sorted by wifescore already, so the first match is the best one
+		let previous_best = candidates.into_iter().find(|candidate| {
+			candidate.song_id == score.song_id
+				&& candidate.rate == score.rate
+				// EO's datetime format sorts lexicographically
+				&& candidate.date < score.date
+		});
+
+		Ok(previous_best.map(|previous_best| PersonalBestComparison {
+			wifescore_delta: score.wifescore.as_percent() - previous_best.wifescore.as_percent(),
+			ssr_overall_delta: match (&score.validity_dependant, &previous_best.validity_dependant)
+			{
+				(Some(new), Some(old)) => Some(new.ssr.overall - old.ssr.overall),
+				_ => None,
+			},
+			previous_best,
+		}))
+	}
+
+	/// Retrieves all scores of the user, including invalid ones, that were set after the given
+	/// datetime, newest first. The datetime must be in EO's format, i.e. `YYYY-MM-DD HH:MM:SS`.
+	///
+	/// Scores are retrieved page by page, and no more pages are requested once a score at or
+	/// before the cutoff is reached. This makes it cheap to periodically sync a user's new scores.
+	pub async fn user_scores_since(
+		&self,
+		user_id: u32,
+		datetime: &str,
+	) -> Result<Vec<UserScore>, Error> {
+		// EO's datetime format sorts lexicographically
+		self.user_scores_newest_first_until(user_id, true, |score| score.date.as_str() <= datetime)
+			.await
+	}
+
+	/// Retrieves all valid scores of the user that were set after the score with the given
+	/// scorekey, newest first.
+	///
+	/// Like [`Self::user_scores_since`], but anchored on a score you already know instead of a
+	/// datetime, which is more robust because EO's datetimes are not always reliable or unique.
+	/// Only valid scores are considered, because EO doesn't reveal the scorekeys of invalid ones.
+	/// If the scorekey is never encountered, all of the user's valid scores are returned.
+	pub async fn user_scores_after_scorekey(
+		&self,
+		user_id: u32,
+		scorekey: &etterna::Scorekey,
+	) -> Result<Vec<UserScore>, Error> {
+		self.user_scores_newest_first_until(user_id, false, |score| {
+			score
+				.validity_dependant
+				.as_ref()
+				.is_some_and(|info| &info.scorekey == scorekey)
+		})
+		.await
+	}
+
+	/// Retrieves the user's scores page by page, newest first, until a score matches `is_known`.
+	/// That score and all older ones are not included
+	async fn user_scores_newest_first_until(
+		&self,
+		user_id: u32,
+		include_invalid: bool,
+		mut is_known: impl FnMut(&UserScore) -> bool,
+	) -> Result<Vec<UserScore>, Error> {
+		const PAGE_SIZE: u32 = 100;
+
+		let mut new_scores = Vec::new();
+		for page in 0.. {
+			let page = self
+				.user_scores(
+					user_id,
+					page * PAGE_SIZE..(page + 1) * PAGE_SIZE,
+					None,
+					UserScoresSortBy::Date,
+					SortDirection::Descending,
+					include_invalid,
+				)
+				.await?
+				.scores;
+			let page_len = page.len();
+
+			for score in page {
+				if is_known(&score) {
+					return Ok(new_scores);
+				}
+				new_scores.push(score);
+			}
+
+			if page_len < PAGE_SIZE as usize {
+				break;
+			}
+		}
+		Ok(new_scores)
+	}
+
 	pub async fn user_details(&self, username: &str) -> Result<UserDetails, Error> {
 		let response = self
 			.request(reqwest::Method::GET, &format!("user/{}", username), |r| r)
@@ -391,11 +796,15 @@ impl Session {
 			});
 		}
 
+		let user_id = (|| response.as_str().extract("'userid': '", "'")?.parse().ok())()
+			.ok_or_else(|| {
+				Error::InvalidDataStructure("No userid found in user page".to_owned())
+			})?;
+		// UNWRAP: propagate panics
+		self.user_id_cache.lock().unwrap().insert(username, user_id);
+
 		Ok(UserDetails {
-			user_id: (|| response.as_str().extract("'userid': '", "'")?.parse().ok())()
-				.ok_or_else(|| {
-					Error::InvalidDataStructure("No userid found in user page".to_owned())
-				})?,
+			user_id,
 			// // The following code is not yet tested
 			// total_scores: (|| {
 			// 	response
@@ -422,7 +831,30 @@ impl Session {
 		})
 	}
 
+	/// Retrieves every score on the chart, on all rates, and reduces them to the best score per
+	/// rate, both per user and globally. See [`ChartLeaderboard::best_per_rate`]
+	pub async fn chart_leaderboard_per_rate(
+		&self,
+		chartkey: impl AsRef<str>,
+		include_invalid: bool,
+	) -> Result<RateBreakdown, Error> {
+		let leaderboard = self
+			.chart_leaderboard(
+				chartkey,
+				..,
+				None,
+				ChartLeaderboardSortBy::Rate,
+				SortDirection::Descending,
+				true,
+				include_invalid,
+			)
+			.await?;
+		Ok(leaderboard.best_per_rate())
+	}
+
 	/// `all_rates` - if true, show users' scores for all rates instead of just their best score
+	///
+	/// See [`Self::chart_leaderboard_query`] for a version that doesn't need all parameters
 	pub async fn chart_leaderboard(
 		&self,
 		chartkey: impl AsRef<str>,
@@ -433,12 +865,42 @@ impl Session {
 		all_rates: bool,
 		include_invalid: bool,
 	) -> Result<ChartLeaderboard, Error> {
-		let (start, length) = range_to_retrieve.start_length();
+		let mut query = ChartLeaderboardQuery::new(chartkey.as_ref())
+			.range(range_to_retrieve)
+			.sort_by(sort_criterium, sort_direction)
+			.all_rates(all_rates)
+			.include_invalid(include_invalid);
+		query.user_name_search = user_name_search_query.map(|s| s.to_owned());
+		self.chart_leaderboard_query(&query).await
+	}
+
+	/// Retrieves the chart leaderboard as specified by the query
+	///
+	/// # Example
+	/// ```rust,no_run
+	/// # async fn f() -> Result<(), etternaonline_api::Error> {
+	/// # use etternaonline_api::web::*;
+	/// # let session: Session = unimplemented!();
+	/// let query = ChartLeaderboardQuery::new("X4a15f62b66a80b62ec64521704f98c6c03d98e03")
+	/// 	.range(0..10)
+	/// 	.all_rates(true);
+	/// let leaderboard = session.chart_leaderboard_query(&query).await?;
+	/// # Ok(()) }
+	/// ```
+	pub async fn chart_leaderboard_query(
+		&self,
+		query: &ChartLeaderboardQuery,
+	) -> Result<ChartLeaderboard, Error> {
+		let (start, length) = query.start_length;
+		let chartkey = &query.chartkey;
+		crate::common::validate_chartkey(chartkey)?;
+		let user_name_search_query = query.user_name_search.as_deref();
+		let (sort_criterium, sort_direction) = (query.sort_by, query.sort_direction);
 
 		let json = self
 			.request(
 				reqwest::Method::POST,
-				if include_invalid {
+				if query.include_invalid {
 					"score/chartOverallScores"
 				} else {
 					"valid_score/chartOverallScores"
@@ -447,8 +909,8 @@ impl Session {
 					r.form(&[
 						("start", &start.to_string() as &str),
 						("length", &length.to_string()),
-						("chartkey", chartkey.as_ref()),
-						("top", if all_rates { "" } else { "true" }),
+						("chartkey", chartkey.as_str()),
+						("top", if query.all_rates { "" } else { "true" }),
 						(
 							"order[0][dir]",
 							match sort_direction {
@@ -479,61 +941,272 @@ impl Session {
 				},
 			)
 			.await?;
-		let json: serde_json::Value = serde_json::from_str(&json)?;
 
-		Ok(ChartLeaderboard {
-			entries_before_search_filtering: json["recordsTotal"].u32_()?,
-			entries_after_search_filtering: json["recordsFiltered"].u32_()?,
-			entries: json["data"]
-				.array()?
-				.iter()
-				.map(|json| {
-					Ok(ChartLeaderboardEntry {
-						// turns out this is actually not a rank but just an index, i.e. if you sort by
-						// date, rank #1 would be the latest score, not the best score. _That_ kind of rank
-						// is pretty useless so let's not parse it to not confuse users about what this is
-						// rank: json.attempt_get("rank string", |json| {
-						// 	let s = json["rank"].as_str()?;
-						// 	if &s[0..1] != "#" { return None; }
-						// 	Some(s[1..].parse::<u32>().ok()? - 1)
-						// })?,
-						date: json["date"].string()?,
-						judgements: TapJudgements {
-							marvelouses: json["marv"].parse()?,
-							perfects: json["perfect"].parse()?,
-							greats: json["great"].parse()?,
-							goods: json["good"].parse()?,
-							bads: json["bad"].parse()?,
-							misses: json["miss"].parse()?,
-						},
-						max_combo: json["combo"].parse()?,
-						rate: json["rate"].parse()?,
-						ssr_overall: json["score"].attempt_get("SSR from score html", |json| {
-							Some(json.as_str()?.extract("\">", "<")?.parse().ok()?)
-						})?,
-						ssr_overall_nerfed: json["nerf"].f32_()?,
-						scorekey: json["score"]
-							.attempt_get("scorekey from score html", |json| {
-								Some(json.as_str()?.extract("view/", "\"")?[..41].parse().ok()?)
-							})?,
-						user_id: json["score"].attempt_get("scorekey from score html", |json| {
-							Some(json.as_str()?.extract("view/", "\"")?[41..].parse().ok()?)
-						})?,
-						username: json["username"]
-							.attempt_get("username from username html", |json| {
-								Some(json.as_str()?.extract("user/", "\"")?.to_owned())
-							})?,
-						wifescore: json["wife"].attempt_get(
-							"wifescore from wife html",
-							|json| {
-								Some(Wifescore::from_percent(
-									json.as_str()?.extract(">", "%")?.parse::<f32>().ok()?,
-								)?)
-							},
-						)?,
-					})
-				})
-				.collect::<Result<Vec<_>, Error>>()?,
+		// These can be huge, so don't parse them into one big JSON tree if avoidable
+		let mut entries = Vec::new();
+		let rest = crate::common::stream_array_field(&json, "data", |json| {
+			entries.push(parse_chart_leaderboard_entry(&json))
+		})?;
+		let rest = serde_json::Value::Object(rest);
+		let rest = Json::new(&rest, "");
+
+		let leaderboard = ChartLeaderboard {
+			entries_before_search_filtering: rest.at("recordsTotal").u32_()?,
+			entries_after_search_filtering: rest.at("recordsFiltered").u32_()?,
+			entries: self.parse_settings.collect("chartOverallScores", entries)?,
+		};
+
+		// UNWRAP: propagate panics
+		let mut user_id_cache = self.user_id_cache.lock().unwrap();
+		for entry in &leaderboard.entries {
+			user_id_cache.insert(&entry.username, entry.user_id);
+		}
+		drop(user_id_cache);
+
+		Ok(leaderboard)
+	}
+
+	/// Retrieves every score the user has on the chart, on all rates and including invalid
+	/// scores, ordered from oldest to newest. Useful for graphing a player's progress on a chart.
+	///
+	/// If this session already knows the user's name (see [`Self::username_for_id`]), the
+	/// leaderboard search is narrowed down to it; otherwise the whole chart leaderboard is
+	/// retrieved and filtered.
+	pub async fn user_chart_history(
+		&self,
+		user_id: u32,
+		chartkey: impl AsRef<str>,
+	) -> Result<Vec<ChartLeaderboardEntry>, Error> {
+		let username = self.username_for_id(user_id);
+		let leaderboard = self
+			.chart_leaderboard(
+				chartkey,
+				..,
+				username.as_deref(),
+				ChartLeaderboardSortBy::Date,
+				SortDirection::Ascending,
+				true,
+				true,
+			)
+			.await?;
+
+		// The search matches substrings of usernames, so other users may be included
+		let mut history = leaderboard
+			.entries
+			.into_iter()
+			.filter(|entry| entry.user_id == user_id)
+			.collect::<Vec<_>>();
+		// EO's datetime format sorts lexicographically, so we don't rely on EO's ordering
+		history.sort_by(|a, b| a.date.cmp(&b.date));
+		Ok(history)
+	}
+
+	/// Resolves a username to the numeric user id that e.g. [`Self::user_scores`] needs. Results
+	/// are cached, so only the first lookup for a given user makes a request
+	pub async fn user_id(&self, username: &str) -> Result<u32, Error> {
+		let cached = self.cached_user_id(username);
+		crate::instrumentation::cache_lookup("user_id", cached.is_some());
+		match cached {
+			Some(user_id) => Ok(user_id),
+			None => Ok(self.user_details(username).await?.user_id),
+		}
+	}
+
+	/// Like [`Self::user_id`], but only looks into the cache and never makes a request
+	pub(crate) fn cached_user_id(&self, username: &str) -> Option<u32> {
+		// UNWRAP: propagate panics
+		self.user_id_cache
+			.lock()
+			.unwrap()
+			.ids_by_username
+			.get(&username.to_lowercase())
+			.copied()
+	}
+
+	/// Looks up the username belonging to a user id.
+	///
+	/// EO has no way to query a user by id, so this only knows users that this session has
+	/// already come across, i.e. through [`Self::user_id`], [`Self::user_details`] or
+	/// [`Self::chart_leaderboard`]. Returns `None` for users that haven't been seen yet
+	pub fn username_for_id(&self, user_id: u32) -> Option<String> {
+		// UNWRAP: propagate panics
+		self.user_id_cache
+			.lock()
+			.unwrap()
+			.usernames_by_id
+			.get(&user_id)
+			.cloned()
+	}
+
+	/// Like [`Self::user_scores`], but takes a username and resolves it to a user id using
+	/// [`Self::user_id`]
+	///
+	/// Panics if the provided range is empty or negative
+	pub async fn user_scores_by_username(
+		&self,
+		username: &str,
+		range_to_retrieve: impl EoRange,
+		song_name_search_query: Option<&str>,
+		sort_criterium: UserScoresSortBy,
+		sort_direction: SortDirection,
+		include_invalid: bool,
+	) -> Result<UserScores, Error> {
+		let user_id = self.user_id(username).await?;
+		self.user_scores(
+			user_id,
+			range_to_retrieve,
+			song_name_search_query,
+			sort_criterium,
+			sort_direction,
+			include_invalid,
+		)
+		.await
+	}
+
+	/// Retrieves the user's 25 best valid scores in each skillset, like
+	/// [`v2::Session::user_top_scores_per_skillset`](crate::v2::Session::user_top_scores_per_skillset).
+	/// Useful as a fallback when the v2 API is down.
+	///
+	/// This takes nine requests: one to resolve the username (unless cached) and one per
+	/// skillset.
+	///
+	/// # Errors
+	/// - [`Error::UserNotFound`] if the supplied username was not found
+	pub async fn user_top_scores_per_skillset(
+		&self,
+		username: &str,
+	) -> Result<UserTopScoresPerSkillset, Error> {
+		const NUM_SCORES: u32 = 25;
+
+		let user_id = self.user_id(username).await?;
+		let top_scores = |sort_by| async move {
+			let query = UserScoresQuery::new(user_id)
+				.range(0..NUM_SCORES)
+				.sort_by(sort_by, SortDirection::Descending);
+			Ok::<_, Error>(self.user_scores_query(&query).await?.scores)
+		};
+
+		Ok(UserTopScoresPerSkillset {
+			overall: top_scores(UserScoresSortBy::SsrOverall).await?,
+			stream: top_scores(UserScoresSortBy::Stream).await?,
+			jumpstream: top_scores(UserScoresSortBy::Jumpstream).await?,
+			handstream: top_scores(UserScoresSortBy::Handstream).await?,
+			stamina: top_scores(UserScoresSortBy::Stamina).await?,
+			jackspeed: top_scores(UserScoresSortBy::Jacks).await?,
+			chordjack: top_scores(UserScoresSortBy::Chordjacks).await?,
+			technical: top_scores(UserScoresSortBy::Technical).await?,
 		})
 	}
 }
+
+#[cfg(feature = "web")]
+impl crate::session::EoSession for Session {
+	fn user_details<'a>(
+		&'a self,
+		_username: &'a str,
+	) -> crate::session::BoxFuture<'a, Result<crate::session::UserProfile, Error>> {
+		// The user page only has the user id in machine-readable form
+		Box::pin(async move {
+			Err(Error::Unsupported {
+				operation: "user details",
+			})
+		})
+	}
+
+	fn score_data<'a>(
+		&'a self,
+		_scorekey: &'a str,
+	) -> crate::session::BoxFuture<'a, Result<crate::session::Score, Error>> {
+		Box::pin(async move {
+			Err(Error::Unsupported {
+				operation: "score data",
+			})
+		})
+	}
+
+	fn chart_leaderboard<'a>(
+		&'a self,
+		chartkey: &'a str,
+	) -> crate::session::BoxFuture<'a, Result<Vec<crate::session::LeaderboardScore>, Error>> {
+		Box::pin(async move {
+			let leaderboard = self
+				.chart_leaderboard_query(&ChartLeaderboardQuery::new(chartkey))
+				.await?;
+			Ok(leaderboard.entries.into_iter().map(Into::into).collect())
+		})
+	}
+}
+
+/// Parses an element of the chart leaderboard response
+#[cfg(feature = "web")]
+fn parse_chart_leaderboard_entry(json: &Json<'_>) -> Result<ChartLeaderboardEntry, Error> {
+	Ok(ChartLeaderboardEntry {
+		// turns out this is actually not a rank but just an index, i.e. if you sort by
+		// date, rank #1 would be the latest score, not the best score. _That_ kind of rank
+		// is pretty useless so let's not parse it to not confuse users about what this is
+		// rank: json.attempt_get("rank string", |json| {
+		// 	let s = json.at("rank").as_str()?;
+		// 	if &s[0..1] != "#" { return None; }
+		// 	Some(s[1..].parse::<u32>().ok()? - 1)
+		// })?,
+		date: json.at("date").string()?,
+		judgements: TapJudgements {
+			marvelouses: json.at("marv").parse()?,
+			perfects: json.at("perfect").parse()?,
+			greats: json.at("great").parse()?,
+			goods: json.at("good").parse()?,
+			bads: json.at("bad").parse()?,
+			misses: json.at("miss").parse()?,
+		},
+		max_combo: json.at("combo").parse()?,
+		rate: json.at("rate").parse()?,
+		ssr_overall: json
+			.at("score")
+			.attempt_get("SSR from score html", |json| {
+				Some(json.as_str()?.extract("\">", "<")?.parse().ok()?)
+			})?,
+		ssr_overall_nerfed: json.at("nerf").f32_()?,
+		scorekey: json
+			.at("score")
+			.attempt_get("scorekey from score html", |json| {
+				Some(json.as_str()?.extract("view/", "\"")?[..41].parse().ok()?)
+			})?,
+		user_id: json
+			.at("score")
+			.attempt_get("scorekey from score html", |json| {
+				Some(json.as_str()?.extract("view/", "\"")?[41..].parse().ok()?)
+			})?,
+		username: json
+			.at("username")
+			.attempt_get("username from username html", |json| {
+				Some(json.as_str()?.extract("user/", "\"")?.to_owned())
+			})?,
+		wifescore: json
+			.at("wife")
+			.attempt_get("wifescore from wife html", |json| {
+				Some(Wifescore::from_percent(
+					json.as_str()?.extract(">", "%")?.parse::<f32>().ok()?,
+				)?)
+			})?,
+	})
+}
+
+/// Finds the first number after `label` in the page, ignoring markup and thousands separators in
+/// between, e.g. `12,345` in `<h4>Scores</h4><span>12,345</span>`
+#[cfg(feature = "web")]
+fn number_after(html: &str, label: &str) -> Option<u64> {
+	// Don't wander into unrelated parts of the page
+	const MAX_DISTANCE: usize = 300;
+
+	let after = &html[html.find(label)? + label.len()..];
+	let (start, _) = after
+		.char_indices()
+		.take_while(|&(i, _)| i < MAX_DISTANCE)
+		.find(|(_, c)| c.is_ascii_digit())?;
+	let digits = after[start..]
+		.chars()
+		.take_while(|&c| c.is_ascii_digit() || c == ',')
+		.filter(|&c| c != ',')
+		.collect::<String>();
+	digits.parse().ok()
+}
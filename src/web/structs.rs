@@ -3,15 +3,18 @@ pub use crate::common::structs::*;
 
 use etterna::*;
 
+use super::EoRange;
+
 #[derive(Debug, Clone, PartialEq, Default)]
 #[cfg_attr(
 	feature = "serde",
 	derive(serde::Serialize, serde::Deserialize),
 	serde(crate = "serde_")
 )]
+#[non_exhaustive]
 pub struct PackEntry {
 	pub name: String,
-	pub id: u32,
+	pub id: PackId,
 	pub datetime: String,
 	pub size: FileSize,
 	pub average_msd: f64,
@@ -34,6 +37,47 @@ pub struct LeaderboardEntry {
 	pub rating: Skillsets8,
 }
 
+/// Site-wide statistics. See [`Session::site_stats`](super::Session::site_stats)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct SiteStats {
+	pub total_users: u64,
+	pub total_scores: u64,
+	/// None if the front page doesn't show it
+	pub total_charts: Option<u64>,
+}
+
+/// Where a rating stands among all players. See
+/// [`Session::rating_percentile`](super::Session::rating_percentile)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct RatingPercentile {
+	/// The worldwide rank the rating would have, starting at 1
+	pub rank: u32,
+	/// Total number of players on the leaderboard
+	pub num_players: u32,
+}
+
+impl RatingPercentile {
+	/// Percentage of players that are at least as good, e.g. `2.3` for "top 2.3%"
+	pub fn top_percent(&self) -> f32 {
+		if self.num_players == 0 {
+			return 100.0;
+		}
+		(self.rank.min(self.num_players) as f32 / self.num_players as f32) * 100.0
+	}
+}
+
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
 #[cfg_attr(
 	feature = "serde",
@@ -54,15 +98,16 @@ pub struct UserScores {
 	pub scores: Vec<UserScore>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 #[cfg_attr(
 	feature = "serde",
 	derive(serde::Serialize, serde::Deserialize),
 	serde(crate = "serde_")
 )]
+#[non_exhaustive]
 pub struct UserScore {
 	pub song_name: String,
-	pub song_id: u32,
+	pub song_id: SongId,
 	/// This is data that is only present if the score is valid. You can also check score validity
 	/// by calling `user_score.validity_dependant.is_some()`
 	pub validity_dependant: Option<ValidUserScoreInfo>,
@@ -73,6 +118,186 @@ pub struct UserScore {
 	pub has_chord_cohesion: bool,
 }
 
+/// User's best valid scores in each skillset, best first. See
+/// [`Session::user_top_scores_per_skillset`](super::Session::user_top_scores_per_skillset)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct UserTopScoresPerSkillset {
+	pub overall: Vec<UserScore>,
+	pub stream: Vec<UserScore>,
+	pub jumpstream: Vec<UserScore>,
+	pub handstream: Vec<UserScore>,
+	pub stamina: Vec<UserScore>,
+	pub jackspeed: Vec<UserScore>,
+	pub chordjack: Vec<UserScore>,
+	pub technical: Vec<UserScore>,
+}
+
+/// Criteria for selecting [`UserScore`]s. All criteria must match for a score to be selected;
+/// criteria that weren't set match everything.
+///
+/// Use [`Self::matches`] or [`Self::apply`] on scores you already have, or
+/// [`Session::user_scores_filtered`](super::Session::user_scores_filtered) to let EO do as much
+/// of the filtering as possible.
+///
+/// # Example
+/// ```rust
+/// # use etternaonline_api::web::*;
+/// let filter = ScoreFilter::new()
+/// 	.rate(etterna::Rate::from_f32(1.0).unwrap()..=etterna::Rate::from_f32(1.5).unwrap())
+/// 	.dominant_skillset(etterna::Skillset7::Stream)
+/// 	.chord_cohesion(false)
+/// 	.valid_only();
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ScoreFilter {
+	pub rate: Option<std::ops::RangeInclusive<Rate>>,
+	pub wifescore: Option<std::ops::RangeInclusive<Wifescore>>,
+	/// Datetimes in EO's format, i.e. `YYYY-MM-DD HH:MM:SS`. Prefixes like `YYYY-MM-DD` work too
+	pub date: Option<std::ops::RangeInclusive<String>>,
+	/// The skillset in which the score has its highest SSR. Only valid scores have SSRs, so this
+	/// excludes invalid scores
+	pub dominant_skillset: Option<Skillset7>,
+	pub chord_cohesion: Option<bool>,
+	pub valid_only: bool,
+	/// Part of the song name. This criterion is applied server-side by
+	/// [`Session::user_scores_filtered`](super::Session::user_scores_filtered)
+	pub song_name: Option<String>,
+}
+
+impl ScoreFilter {
+	/// Create a filter that matches all scores
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn rate(mut self, rate: std::ops::RangeInclusive<Rate>) -> Self {
+		self.rate = Some(rate);
+		self
+	}
+
+	pub fn wifescore(mut self, wifescore: std::ops::RangeInclusive<Wifescore>) -> Self {
+		self.wifescore = Some(wifescore);
+		self
+	}
+
+	pub fn date(mut self, date: std::ops::RangeInclusive<String>) -> Self {
+		self.date = Some(date);
+		self
+	}
+
+	pub fn dominant_skillset(mut self, skillset: Skillset7) -> Self {
+		self.dominant_skillset = Some(skillset);
+		self
+	}
+
+	pub fn chord_cohesion(mut self, chord_cohesion: bool) -> Self {
+		self.chord_cohesion = Some(chord_cohesion);
+		self
+	}
+
+	pub fn valid_only(mut self) -> Self {
+		self.valid_only = true;
+		self
+	}
+
+	pub fn song_name(mut self, song_name: impl Into<String>) -> Self {
+		self.song_name = Some(song_name.into());
+		self
+	}
+
+	/// Whether the score matches all criteria
+	pub fn matches(&self, score: &UserScore) -> bool {
+		if let Some(rate) = &self.rate {
+			if !rate.contains(&score.rate) {
+				return false;
+			}
+		}
+		if let Some(wifescore) = &self.wifescore {
+			// Wifescore's Ord implementation can't be relied on, so compare the raw values
+			let wifescore_proportion = score.wifescore.as_proportion();
+			if wifescore_proportion < wifescore.start().as_proportion()
+				|| wifescore_proportion > wifescore.end().as_proportion()
+			{
+				return false;
+			}
+		}
+		if let Some(date) = &self.date {
+			// EO's datetime format sorts lexicographically. Compare only as much of the score's
+			// date as the bounds specify, so that e.g. "2021-01-31" includes that whole day
+			let prefix = |bound: &str| score.date.get(..bound.len()).unwrap_or(&score.date);
+			if prefix(date.start()) < date.start().as_str()
+				|| prefix(date.end()) > date.end().as_str()
+			{
+				return false;
+			}
+		}
+		if let Some(skillset) = self.dominant_skillset {
+			let ssr = match &score.validity_dependant {
+				Some(info) => info.ssr.to_skillsets7(),
+				None => return false,
+			};
+			if Skillset7::iter().any(|other| ssr.get(other) > ssr.get(skillset)) {
+				return false;
+			}
+		}
+		if let Some(chord_cohesion) = self.chord_cohesion {
+			if score.has_chord_cohesion != chord_cohesion {
+				return false;
+			}
+		}
+		if self.valid_only && score.validity_dependant.is_none() {
+			return false;
+		}
+		if let Some(song_name) = &self.song_name {
+			if !score
+				.song_name
+				.to_lowercase()
+				.contains(&song_name.to_lowercase())
+			{
+				return false;
+			}
+		}
+		true
+	}
+
+	/// Removes all scores that don't match
+	pub fn apply(&self, scores: &mut Vec<UserScore>) {
+		scores.retain(|score| self.matches(score));
+	}
+}
+
+/// How a score compares to the user's previous best on the same song and rate. See
+/// [`Session::compare_to_personal_best`](super::Session::compare_to_personal_best)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(crate = "serde_")
+)]
+#[non_exhaustive]
+pub struct PersonalBestComparison {
+	/// The user's best score on the same song and rate that was set before the compared score
+	pub previous_best: UserScore,
+	/// Difference in wifescore, in percentage points. Positive if the compared score is better
+	pub wifescore_delta: f32,
+	/// Difference in overall SSR. `None` if either score is invalid, because EO only reports the
+	/// SSR of valid scores
+	pub ssr_overall_delta: Option<f32>,
+}
+
+impl PersonalBestComparison {
+	/// Whether the compared score beats the previous best
+	pub fn is_new_personal_best(&self) -> bool {
+		self.wifescore_delta > 0.0
+	}
+}
+
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(
 	feature = "serde",
@@ -119,6 +344,65 @@ pub struct UserDetails {
 	// pub unique_songs: u32,
 }
 
+/// Parameters for [`Session::user_scores_query`](super::Session::user_scores_query).
+///
+/// By default, all valid scores of the user are retrieved, newest first.
+///
+/// # Example
+/// ```rust
+/// # use etternaonline_api::web::*;
+/// let query = UserScoresQuery::new(2)
+/// 	.song_name_search("Game Time")
+/// 	.sort_by(UserScoresSortBy::Wifescore, SortDirection::Descending);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct UserScoresQuery {
+	pub user_id: u32,
+	/// Start and length of the range to retrieve. A length of zero retrieves everything
+	pub start_length: (u32, u32),
+	/// Only retrieve scores on songs whose name contains this
+	pub song_name_search: Option<String>,
+	pub sort_by: UserScoresSortBy,
+	pub sort_direction: SortDirection,
+	pub include_invalid: bool,
+}
+
+impl UserScoresQuery {
+	pub fn new(user_id: u32) -> Self {
+		Self {
+			user_id,
+			start_length: (0, 0),
+			song_name_search: None,
+			sort_by: UserScoresSortBy::Date,
+			sort_direction: SortDirection::Descending,
+			include_invalid: false,
+		}
+	}
+
+	/// Panics if the provided range is empty or negative
+	pub fn range(mut self, range: impl EoRange) -> Self {
+		self.start_length = range.start_length();
+		self
+	}
+
+	pub fn song_name_search(mut self, search: impl Into<String>) -> Self {
+		self.song_name_search = Some(search.into());
+		self
+	}
+
+	pub fn sort_by(mut self, sort_by: UserScoresSortBy, direction: SortDirection) -> Self {
+		self.sort_by = sort_by;
+		self.sort_direction = direction;
+		self
+	}
+
+	pub fn include_invalid(mut self, include_invalid: bool) -> Self {
+		self.include_invalid = include_invalid;
+		self
+	}
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 #[cfg_attr(
 	feature = "serde",
@@ -169,12 +453,59 @@ pub struct ChartLeaderboard {
 	pub entries: Vec<ChartLeaderboardEntry>,
 }
 
+impl ChartLeaderboard {
+	/// Reduces the entries to the best score per rate, both per user and globally. "Best" means
+	/// highest wifescore.
+	///
+	/// This is only meaningful if the leaderboard was retrieved with `all_rates` enabled, see
+	/// [`Session::chart_leaderboard_per_rate`](super::Session::chart_leaderboard_per_rate)
+	pub fn best_per_rate(&self) -> RateBreakdown {
+		fn insert_if_better(
+			map: &mut std::collections::BTreeMap<Rate, ChartLeaderboardEntry>,
+			entry: &ChartLeaderboardEntry,
+		) {
+			match map.get(&entry.rate) {
+				Some(best) if best.wifescore.as_proportion() >= entry.wifescore.as_proportion() => {
+				}
+				_ => {
+					map.insert(entry.rate, entry.clone());
+				}
+			}
+		}
+
+		let mut breakdown = RateBreakdown::default();
+		for entry in &self.entries {
+			insert_if_better(&mut breakdown.best_per_rate, entry);
+			insert_if_better(
+				breakdown
+					.best_per_user_and_rate
+					.entry(entry.user_id)
+					.or_default(),
+				entry,
+			);
+		}
+		breakdown
+	}
+}
+
+/// The best scores on a chart for each rate, see [`ChartLeaderboard::best_per_rate`]
+// No serde support, because JSON can't represent maps with `Rate` keys
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RateBreakdown {
+	/// The best score at each rate, across all users
+	pub best_per_rate: std::collections::BTreeMap<Rate, ChartLeaderboardEntry>,
+	/// Each user's best score at each rate, keyed by user id
+	pub best_per_user_and_rate:
+		std::collections::HashMap<u32, std::collections::BTreeMap<Rate, ChartLeaderboardEntry>>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(
 	feature = "serde",
 	derive(serde::Serialize, serde::Deserialize),
 	serde(crate = "serde_")
 )]
+#[non_exhaustive]
 pub struct ChartLeaderboardEntry {
 	pub username: String,
 	pub scorekey: Scorekey,
@@ -189,6 +520,23 @@ pub struct ChartLeaderboardEntry {
 }
 
 impl ChartLeaderboardEntry {
+	/// Create a leaderboard entry with the given scorekey. All other fields are zeroed and can be
+	/// filled in afterwards
+	pub fn new(scorekey: Scorekey) -> Self {
+		Self {
+			username: String::new(),
+			scorekey,
+			user_id: 0,
+			ssr_overall: 0.0,
+			ssr_overall_nerfed: 0.0,
+			rate: Rate::default(),
+			wifescore: Wifescore::default(),
+			date: String::new(),
+			judgements: TapJudgements::default(),
+			max_combo: 0,
+		}
+	}
+
 	/// Generate a link to this score's score page
 	pub fn score_link(&self) -> String {
 		format!(
@@ -198,6 +546,75 @@ impl ChartLeaderboardEntry {
 	}
 }
 
+/// Parameters for [`Session::chart_leaderboard_query`](super::Session::chart_leaderboard_query).
+///
+/// By default, the whole leaderboard is retrieved with each user's best valid score, sorted by
+/// overall SSR, best first.
+///
+/// # Example
+/// ```rust
+/// # use etternaonline_api::web::*;
+/// let query = ChartLeaderboardQuery::new("X4a15f62b66a80b62ec64521704f98c6c03d98e03")
+/// 	.range(0..50)
+/// 	.sort_by(ChartLeaderboardSortBy::Date, SortDirection::Descending)
+/// 	.include_invalid(true);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ChartLeaderboardQuery {
+	pub chartkey: String,
+	/// Start and length of the range to retrieve. A length of zero retrieves everything
+	pub start_length: (u32, u32),
+	/// Only retrieve scores by users whose name contains this
+	pub user_name_search: Option<String>,
+	pub sort_by: ChartLeaderboardSortBy,
+	pub sort_direction: SortDirection,
+	/// Include each user's scores on all rates instead of just their best score
+	pub all_rates: bool,
+	pub include_invalid: bool,
+}
+
+impl ChartLeaderboardQuery {
+	pub fn new(chartkey: impl Into<String>) -> Self {
+		Self {
+			chartkey: chartkey.into(),
+			start_length: (0, 0),
+			user_name_search: None,
+			sort_by: ChartLeaderboardSortBy::SsrOverall,
+			sort_direction: SortDirection::Descending,
+			all_rates: false,
+			include_invalid: false,
+		}
+	}
+
+	/// Panics if the provided range is empty or negative
+	pub fn range(mut self, range: impl EoRange) -> Self {
+		self.start_length = range.start_length();
+		self
+	}
+
+	pub fn user_name_search(mut self, search: impl Into<String>) -> Self {
+		self.user_name_search = Some(search.into());
+		self
+	}
+
+	pub fn sort_by(mut self, sort_by: ChartLeaderboardSortBy, direction: SortDirection) -> Self {
+		self.sort_by = sort_by;
+		self.sort_direction = direction;
+		self
+	}
+
+	pub fn all_rates(mut self, all_rates: bool) -> Self {
+		self.all_rates = all_rates;
+		self
+	}
+
+	pub fn include_invalid(mut self, include_invalid: bool) -> Self {
+		self.include_invalid = include_invalid;
+		self
+	}
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 #[cfg_attr(
 	feature = "serde",
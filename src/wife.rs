@@ -0,0 +1,128 @@
+//! Converting wifescores between the Wife2 and Wife3 algorithms.
+//!
+//! Etterna switched from Wife2 to Wife3 in 0.70, and EO has scores of both kinds. Statistics
+//! across old and new scores are skewed unless the scores are converted to one algorithm first.
+//!
+//! With a replay, the conversion is exact, see [`replay_wifescore`]. With only the judgement
+//! counts, it's an estimate, see [`convert_wifescore`].
+
+use etterna::Wife;
+
+/// Estimates what a score with the given wifescore, set with the `From` algorithm, would have
+/// scored with the `To` algorithm, e.g. `convert_wifescore::<Wife2, Wife3>(...)`.
+///
+/// Each judgement is assumed to have been hit in the middle of its timing window. The estimate
+/// only uses the difference between the two algorithms on those deviations, so the score's actual
+/// wifescore still dominates the result. Mine hits and hold drops are accounted for with the
+/// respective weights of both algorithms.
+///
+/// Returns None if there are no judgements
+pub fn convert_wifescore<From: Wife, To: Wife>(
+	wifescore: etterna::Wifescore,
+	judgements: &etterna::TapJudgements,
+	num_hit_mines: u32,
+	num_dropped_holds: u32,
+	judge: &etterna::Judge,
+) -> Option<etterna::Wifescore> {
+	let from = estimate::<From>(judgements, num_hit_mines, num_dropped_holds, judge)?;
+	let to = estimate::<To>(judgements, num_hit_mines, num_dropped_holds, judge)?;
+	etterna::Wifescore::from_proportion(wifescore.as_proportion() + to - from)
+}
+
+/// Wifescore proportion for the judgement counts, with each judgement in the middle of its window
+fn estimate<W: Wife>(
+	judgements: &etterna::TapJudgements,
+	num_hit_mines: u32,
+	num_dropped_holds: u32,
+	judge: &etterna::Judge,
+) -> Option<f32> {
+	let windows = [
+		(judgements.marvelouses, 0.0, judge.marvelous_window),
+		(
+			judgements.perfects,
+			judge.marvelous_window,
+			judge.perfect_window,
+		),
+		(judgements.greats, judge.perfect_window, judge.great_window),
+		(judgements.goods, judge.great_window, judge.good_window),
+		(judgements.bads, judge.good_window, judge.bad_window),
+	];
+
+	let mut num_notes = judgements.misses;
+	let mut points = judgements.misses as f32 * W::MISS_WEIGHT;
+	for (count, window_start, window_end) in windows {
+		num_notes += count;
+		points += count as f32 * W::calc_deviation((window_start + window_end) / 2.0, judge);
+	}
+	points += num_hit_mines as f32 * W::MINE_HIT_WEIGHT;
+	points += num_dropped_holds as f32 * W::HOLD_DROP_WEIGHT;
+
+	if num_notes == 0 {
+		return None;
+	}
+	Some(points / num_notes as f32)
+}
+
+/// Computes the exact wifescore of a replay with the given algorithm, e.g.
+/// `replay_wifescore::<Wife3>(...)`. Unlike [`crate::rescore`], the notes aren't re-matched to
+/// hits; every note keeps the deviation it was recorded with.
+///
+/// Only taps and hold heads are counted, if the replay has note type information.
+///
+/// Returns None if the replay has no notes
+pub fn replay_wifescore<W: Wife>(
+	replay: &crate::Replay,
+	num_hit_mines: u32,
+	num_dropped_holds: u32,
+	judge: &etterna::Judge,
+) -> Option<etterna::Wifescore> {
+	let hits = match replay.iter_taps() {
+		Some(taps) => taps.map(|note| note.hit).collect::<Vec<_>>(),
+		None => replay.notes.iter().map(|note| note.hit).collect(),
+	};
+	if hits.is_empty() {
+		return None;
+	}
+	W::apply(hits, num_hit_mines, num_dropped_holds, judge)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use etterna::{Wife2, Wife3};
+
+	#[test]
+	fn test_convert_wifescore() {
+		let judgements = etterna::TapJudgements {
+			marvelouses: 900,
+			perfects: 80,
+			greats: 10,
+			goods: 5,
+			bads: 3,
+			misses: 2,
+		};
+		let wifescore = etterna::Wifescore::from_percent(95.0).unwrap();
+
+		let same =
+			convert_wifescore::<Wife3, Wife3>(wifescore, &judgements, 0, 0, etterna::J4).unwrap();
+		assert!((same.as_percent() - 95.0).abs() < 1e-4);
+
+		// Wife3 punishes misses less than Wife2
+		let with_misses = |misses| etterna::TapJudgements {
+			misses,
+			..judgements.clone()
+		};
+		let convert = |judgements: &etterna::TapJudgements| {
+			convert_wifescore::<Wife2, Wife3>(wifescore, judgements, 0, 0, etterna::J4)
+				.unwrap()
+				.as_percent()
+		};
+		assert!(convert(&with_misses(20)) > convert(&with_misses(2)));
+
+		let empty = etterna::TapJudgements::default();
+		assert_eq!(
+			convert_wifescore::<Wife2, Wife3>(wifescore, &empty, 0, 0, etterna::J4),
+			None
+		);
+	}
+}
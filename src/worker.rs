@@ -0,0 +1,159 @@
+//! Sharing one v2 session between many tasks through a background worker.
+//!
+//! [`spawn`] moves the session into a tokio task and returns a [`WorkerHandle`]. Handles are
+//! cheap to clone, so each bot command handler can hold its own. Every handle method sends a
+//! [`Command`] to the worker over a channel and waits for the reply; the worker runs the commands
+//! one after another, which keeps all requests behind the session's single rate limit.
+//!
+//! # Example
+//! ```rust,no_run
+//! # async fn run() -> Result<(), etternaonline_api::Error> {
+//! # let session: etternaonline_api::v2::Session = unimplemented!();
+//! let worker = etternaonline_api::worker::spawn(session);
+//!
+//! let handle = worker.clone();
+//! tokio::spawn(async move {
+//! 	let details = handle.user_details("kangalioo").await;
+//! });
+//!
+//! let leaderboard = worker.world_leaderboard().await?;
+//! # Ok(()) }
+//! ```
+
+use etterna::Skillset7;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::v2::*;
+use crate::Error;
+
+/// How many commands can wait for the worker before senders have to wait too
+const QUEUE_SIZE: usize = 64;
+
+type Reply<T> = oneshot::Sender<Result<T, Error>>;
+
+macro_rules! commands {
+	(session = $session:ident; $(
+		$(#[$attr:meta])*
+		$method:ident => $variant:ident { $($arg:ident: $arg_ty:ty),* } -> $ret:ty = $call:expr;
+	)*) => {
+		/// A request for the worker. Each command carries the channel the result is sent back on.
+		///
+		/// Usually you don't construct these yourself but call the matching [`WorkerHandle`]
+		/// method
+		#[derive(Debug)]
+		#[non_exhaustive]
+		pub enum Command {
+			$(
+				$variant {
+					$($arg: $arg_ty,)*
+					reply: Reply<$ret>,
+				},
+			)*
+		}
+
+		impl Command {
+			async fn execute(self, $session: &Session) {
+				// A send error means the caller stopped waiting for the result, which is fine
+				match self {
+					$(
+						Self::$variant { $($arg,)* reply } => {
+							let _ = reply.send($call.await);
+						}
+					)*
+				}
+			}
+		}
+
+		impl WorkerHandle {
+			$(
+				$(#[$attr])*
+				pub async fn $method(&self, $($arg: impl Into<$arg_ty>),*) -> Result<$ret, Error> {
+					let (reply, response) = oneshot::channel();
+					self.send(Command::$variant { $($arg: $arg.into(),)* reply }).await?;
+					response.await.map_err(|_| Error::WorkerStopped)?
+				}
+			)*
+		}
+	};
+}
+
+/// Sends commands to a worker started with [`spawn`]. The worker stops once all handles are
+/// dropped.
+#[derive(Debug, Clone)]
+pub struct WorkerHandle {
+	sender: mpsc::Sender<Command>,
+}
+
+impl WorkerHandle {
+	/// Queues a raw command. Prefer the typed methods, which also wait for the result.
+	///
+	/// # Errors
+	/// - [`Error::WorkerStopped`] if the worker task is no longer running
+	pub async fn send(&self, command: Command) -> Result<(), Error> {
+		self.sender
+			.send(command)
+			.await
+			.map_err(|_| Error::WorkerStopped)
+	}
+}
+
+commands! {
+	session = session;
+
+	/// See [`Session::user_details`]
+	user_details => UserDetails { username: String } -> UserDetails =
+		session.user_details(&username);
+	/// See [`Session::user_top_skillset_scores`]
+	user_top_skillset_scores => UserTopSkillsetScores {
+		username: String,
+		skillset: Skillset7,
+		limit: u32
+	} -> Vec<TopScore> = session.user_top_skillset_scores(&username, skillset, limit);
+	/// See [`Session::user_top_10_scores`]
+	user_top_10_scores => UserTop10Scores { username: String } -> Vec<TopScore> =
+		session.user_top_10_scores(&username);
+	/// See [`Session::user_latest_scores`]
+	user_latest_scores => UserLatestScores { username: String } -> Vec<LatestScore> =
+		session.user_latest_scores(&username);
+	/// See [`Session::user_ranks_per_skillset`]
+	user_ranks_per_skillset => UserRanksPerSkillset { username: String } -> etterna::UserRank =
+		session.user_ranks_per_skillset(&username);
+	/// See [`Session::user_top_scores_per_skillset`]
+	user_top_scores_per_skillset => UserTopScoresPerSkillset {
+		username: String
+	} -> UserTopScoresPerSkillset = session.user_top_scores_per_skillset(&username);
+	/// See [`Session::user_profile_card`]
+	user_profile_card => UserProfileCard { username: String } -> UserProfileCard =
+		session.user_profile_card(&username);
+	/// See [`Session::score_data`]
+	score_data => ScoreData { scorekey: String } -> ScoreData = session.score_data(&scorekey);
+	/// See [`Session::chart_leaderboard`]
+	chart_leaderboard => ChartLeaderboard { chartkey: String } -> Vec<ChartLeaderboardScore> =
+		session.chart_leaderboard(&chartkey);
+	/// See [`Session::country_leaderboard`]
+	country_leaderboard => CountryLeaderboard { country_code: String } -> Vec<LeaderboardEntry> =
+		session.country_leaderboard(&country_code);
+	/// See [`Session::world_leaderboard`]
+	world_leaderboard => WorldLeaderboard {} -> Vec<LeaderboardEntry> =
+		session.world_leaderboard();
+	/// See [`Session::user_favorites`]
+	user_favorites => UserFavorites { username: String } -> Vec<String> =
+		session.user_favorites(&username);
+	/// See [`Session::user_goals`]
+	user_goals => UserGoals { username: String } -> Vec<ScoreGoal> =
+		session.user_goals(&username);
+}
+
+/// Moves the session into a background tokio task and returns a handle for sending it commands.
+///
+/// Must be called from within a tokio runtime.
+pub fn spawn(session: Session) -> WorkerHandle {
+	let (sender, mut receiver) = mpsc::channel::<Command>(QUEUE_SIZE);
+	tokio::spawn(async move {
+		while let Some(command) = receiver.recv().await {
+			command.execute(&session).await;
+		}
+		log::debug!("all worker handles dropped, stopping worker");
+	});
+	WorkerHandle { sender }
+}